@@ -1,214 +1,623 @@
 extern crate rand;
 use crate::deck::Deck;
-use crate::user::User;
+use crate::hand::Hand;
+use crate::history::{Action, Event, History, Seat};
+use crate::render;
+use crate::tui::Tui;
+use crate::user::{PlayerHand, User};
+use rand::{thread_rng, Rng};
+use std::collections::VecDeque;
 use std::io;
 use std::{thread, time};
 
+/// most hands a player can split into
+const MAX_HANDS: usize = 4;
+/// smallest bet the table takes. Anyone with less than this sits the round out.
+pub const MIN_BET: isize = 5;
+
+/// Where the bets and decisions come from, and where the game talks back to
+enum Input {
+    /// a person typing numbers at the terminal
+    Terminal,
+    /// keypresses in the full screen table
+    Tui(Box<Tui>),
+    /// answers taken from a logged round, used by `BlackJack::replay`
+    Scripted {
+        bets: VecDeque<isize>,
+        actions: VecDeque<Action>,
+    },
+}
+
 pub struct BlackJack {
     players: Vec<User>,
     pub deck: Deck,
-    dealer: User,
+    dealer: Hand,
+    pub history: History,
+    input: Input,
+    /// the dealer's hole card stays face down until the dealer plays
+    reveal_dealer: bool,
+    /// (player, hand) whose turn it is
+    turn: Option<(usize, usize)>,
 }
 
 impl BlackJack {
-    pub fn new(num_players: isize) -> BlackJack {
-        let mut players: Vec<User> = Vec::new();
-        for i in 0..num_players {
-            players.push(User::new(i));
-        }
-        let d: Deck = Deck::new();
+    pub fn new(players: Vec<User>) -> BlackJack {
+        BlackJack::with_seed(players, thread_rng().gen())
+    }
+
+    /// Same as `new` but the deck is shuffled from `seed`, so the round can be played again card for card
+    pub fn with_seed(players: Vec<User>, seed: u64) -> BlackJack {
+        let d: Deck = Deck::new(seed);
+        let mut history = History::new();
+        history.record(Event::RoundStart {
+            seed,
+            players: players.len(),
+            bankrolls: players.iter().map(|p| p.money).collect(),
+        });
         BlackJack {
-            players: players,
+            players,
             deck: d,
-            dealer: User::new(-1),
+            dealer: Hand::new(),
+            history,
+            input: Input::Terminal,
+            reveal_dealer: false,
+            turn: None,
         }
     }
 
+    /// Play with keypresses in the full screen table instead of typing numbers
+    pub fn use_tui(&mut self, tui: Tui) {
+        self.input = Input::Tui(Box::new(tui));
+    }
+
+    /// Go back to typing numbers. Dropping the full screen table hands the terminal back.
+    pub fn use_terminal(&mut self) {
+        self.input = Input::Terminal;
+    }
+
+    /// Plays a logged round again from its seed and recorded decisions and checks
+    /// that every card, action and payout comes out the same.
+    pub fn replay(round: &History) -> Result<(), String> {
+        let (seed, _) = round
+            .round_start()
+            .ok_or("Round does not begin with a round_start event")?;
+        let players = round
+            .starting_bankrolls()
+            .into_iter()
+            .enumerate()
+            .map(|(i, bankroll)| {
+                let mut player = User::new(i as isize);
+                player.money = bankroll;
+                player
+            })
+            .collect();
+        let mut blackjack = BlackJack::with_seed(players, seed);
+        blackjack.input = Input::Scripted {
+            bets: round.bets(),
+            actions: round.actions(),
+        };
+        blackjack.start();
+        round.verify(&blackjack.history)
+    }
+
     pub fn start(&mut self) {
         self.get_bets();
         self.deal_hands();
-        self.show_dealer_hand(false);
+        self.show_dealer_hand();
         for id in 0..self.players.len() {
-            if self.players[id].bet > 0 {
+            if self.players[id].total_bet() > 0 {
                 self.player_turn(id);
             }
         }
+        self.turn = None;
+        self.reveal_dealer = true;
         self.dealer_turn();
-        self.show_dealer_hand(true);
+        self.show_dealer_hand();
         self.determine_winners();
-        // self.payouts();
+        self.payouts();
         self.show_results();
+        self.finish();
     }
 
     fn get_bets(&mut self) {
-        println!("getting bets");
-        let mut i = 1;
-        for player in self.players.iter_mut() {
+        self.say(String::from("getting bets"));
+        for id in 0..self.players.len() {
+            if self.players[id].money < MIN_BET {
+                // nothing they could bet would be accepted, so don't keep asking
+                self.say(format!(
+                    "{} is out of chips and sits this round out.",
+                    self.players[id].name
+                ));
+                // no bet event either, replays only script the bets someone actually typed
+                let hand = &mut self.players[id].hands[0];
+                hand.bet = 0;
+                hand.status = -1;
+                continue;
+            }
             loop {
-                let max = player.money;
-                let message = format!(
-                    "Player {}, Place a bet between {} and {}. Enter 0 to fold.",
-                    i, 5, 100
-                );
-                let bet = get_int_input(message);
+                let max = self.players[id].money;
+                let bet = self.read_bet(id);
                 match bet {
                     0 => {
-                        player.bet = 0;
-                        player.status = -1;
+                        let hand = &mut self.players[id].hands[0];
+                        hand.bet = 0;
+                        hand.status = -1;
                         break;
                     }
                     b if b > max => {
-                        println!("Too Hi.");
+                        self.say(String::from("Too Hi."));
                     }
-                    b if b < 5 => {
-                        println!("Too Low.");
+                    b if b < MIN_BET => {
+                        self.say(String::from("Too Low."));
                     }
                     _ => {
-                        player.bet = bet;
+                        self.players[id].hands[0].bet = bet;
                         break;
                     }
                 }
             }
-            i += 1;
+            self.history.record(Event::Bet {
+                player: id,
+                amount: self.players[id].hands[0].bet,
+            });
         }
     }
 
     fn deal_hands(&mut self) {
         for _ in 0..2 {
             // two cards per player
-            for player in self.players.iter_mut() {
-                player.cards.add(self.deck.draw());
+            for id in 0..self.players.len() {
+                self.deal_to(Seat::Player(id), 0);
             }
-            self.dealer.cards.add(self.deck.draw());
+            self.deal_to(Seat::Dealer, 0);
         }
-        println!("Cards dealt to players and dealer.");
+        self.say(String::from("Cards dealt to players and dealer."));
     }
 
-    fn show_dealer_hand(&self, show_all: bool) {
-        println!("\n+++++++++++++++++++++\nDEALER IS SHOWING...");
-        if !show_all {
-            for card_num in 0..self.dealer.cards.num_cards() {
+    /// draws the next card for one of a seat's hands and logs it
+    fn deal_to(&mut self, seat: Seat, hand: usize) {
+        let card = self.deck.draw();
+        match seat {
+            Seat::Player(id) => self.players[id].hands[hand].cards.add(card),
+            Seat::Dealer => self.dealer.add(card),
+        }
+        self.history.record(Event::Deal { seat, hand, card });
+    }
+
+    fn show_dealer_hand(&self) {
+        let mut text = String::from("\n+++++++++++++++++++++\nDEALER IS SHOWING...\n");
+        if !self.reveal_dealer {
+            for card_num in 0..self.dealer.num_cards() {
                 if card_num == 0 {
-                    println!("> {}", self.dealer.cards.get_first_card());
+                    text.push_str(&format!("> {}\n", self.dealer.get_first_card()));
                 } else {
-                    println!("> HIDDEN CARD\n+++++++++++++++++++++");
+                    text.push_str("> HIDDEN CARD\n+++++++++++++++++++++");
                 }
             }
         } else {
-            println!("Dealers Cards\n{}", self.dealer.cards);
-            if self.dealer.cards.is_busted() {
-                println!("/// DEALER BUSTED ///")
+            text.push_str(&format!("Dealers Cards\n{}", self.dealer));
+            if self.dealer.is_busted() {
+                text.push_str("/// DEALER BUSTED ///")
             }
         }
+        self.show(&text);
     }
 
     fn player_turn(&mut self, player_id: usize) {
+        // splitting adds hands while the player is still going, so this can't be a plain for loop
+        let mut hand = 0;
+        while hand < self.players[player_id].hands.len() {
+            self.turn = Some((player_id, hand));
+            self.play_hand(player_id, hand);
+            hand += 1;
+        }
+    }
+
+    fn play_hand(&mut self, player_id: usize, hand: usize) {
         loop {
-            self.show_player_cards(player_id);
-            if self.players[player_id].cards.get_score() == -1 {
-                self.players[player_id].status = -1;
-                thread::sleep(time::Duration::from_secs(1));
+            self.show_player_cards(player_id, hand);
+            let current = &mut self.players[player_id].hands[hand];
+            if current.cards.get_score() == -1 {
+                current.status = -1;
+                self.say(format!("{} BUSTED!", self.players[player_id].name));
+                if let Input::Terminal = self.input {
+                    thread::sleep(time::Duration::from_secs(1));
+                }
+                break;
+            }
+            // a doubled hand gets exactly one more card
+            if current.doubled {
                 break;
             }
-            let decision = get_int_input(String::from("Hit (1) or Hold (2)?"));
-            match decision {
-                1 => {
-                    self.players[player_id].cards.add(self.deck.draw());
+            let action = self.read_action(player_id, hand);
+            if let Err(reason) = self.check_action(player_id, hand, action) {
+                self.say(reason);
+                continue;
+            }
+            self.history.record(Event::Action {
+                player: player_id,
+                hand,
+                action,
+            });
+            match action {
+                Action::Hit => {
+                    self.deal_to(Seat::Player(player_id), hand);
                 }
-                2 => {
+                Action::Stand => {
                     break;
                 }
-                _ => {
-                    println!("Invalid response {}\n Try Again!!!", decision);
+                Action::Double => {
+                    let current = &mut self.players[player_id].hands[hand];
+                    current.bet *= 2;
+                    current.doubled = true;
+                    self.deal_to(Seat::Player(player_id), hand);
+                }
+                Action::Split => {
+                    let player = &mut self.players[player_id];
+                    let card = player.hands[hand]
+                        .cards
+                        .take_last()
+                        .expect("Split a hand without cards");
+                    let mut split = PlayerHand::new(player.hands[hand].bet);
+                    split.cards.add(card);
+                    player.hands.push(split);
+                    let split = player.hands.len() - 1;
+                    self.deal_to(Seat::Player(player_id), hand);
+                    self.deal_to(Seat::Player(player_id), split);
                 }
             }
         }
     }
 
-    fn show_player_cards(&self, player_id: usize) {
-        println!(
-            "\n### Player's {} HAND ###\n{}",
-            player_id + 1,
-            self.players[player_id].cards
-        );
-        if self.players[player_id].cards.get_score() == -1 {
-            println!("BUSTED!");
+    /// double and split are only allowed on the first two cards and when the player can cover another bet
+    fn check_action(&self, player_id: usize, hand: usize, action: Action) -> Result<(), String> {
+        let player = &self.players[player_id];
+        let current = &player.hands[hand];
+        let can_cover = player.money >= player.total_bet() + current.bet;
+        match action {
+            Action::Hit | Action::Stand => Ok(()),
+            Action::Double if current.cards.num_cards() != 2 => Err(String::from(
+                "You can only double down on your first two cards.",
+            )),
+            Action::Split if !current.cards.is_pair() => {
+                Err(String::from("You can only split a pair."))
+            }
+            Action::Split if player.hands.len() >= MAX_HANDS => Err(format!(
+                "You can't split into more than {} hands.",
+                MAX_HANDS
+            )),
+            Action::Double | Action::Split if !can_cover => {
+                Err(String::from("You don't have enough chips for that."))
+            }
+            Action::Double | Action::Split => Ok(()),
         }
     }
 
+    fn show_player_cards(&self, player_id: usize, hand: usize) {
+        let cards = &self.players[player_id].hands[hand].cards;
+        let mut text = format!("\n### Player's {} HAND ###\n{}", player_id + 1, cards);
+        if self.players[player_id].hands.len() > 1 {
+            text = format!(
+                "\n### Player's {} HAND {} ###\n{}",
+                player_id + 1,
+                hand + 1,
+                cards
+            );
+        }
+        self.show(&text);
+    }
+
     fn dealer_turn(&mut self) {
         loop {
-            if self.dealer.cards.is_busted() {
-                self.dealer.status = -1;
+            if self.dealer.is_busted() {
                 break;
             }
-            let total = self.dealer.cards.get_score();
+            let total = self.dealer.get_score();
 
             if total <= 16 {
                 // hit
-                self.dealer.cards.add(self.deck.draw());
-            } else if total == 17 && self.dealer.cards.has_ace() {
+                self.deal_to(Seat::Dealer, 0);
+            } else if total == 17 && self.dealer.has_ace() {
                 // hit on soft 17 because there's an ace
-                self.dealer.cards.add(self.deck.draw());
+                self.deal_to(Seat::Dealer, 0);
             } else if total <= 21 {
                 // between 17 - 21, pass
                 break;
-            } else if total > -1 {
-                self.dealer.status = -1;
-                break;
             } else {
                 println!("total: {}", total);
                 panic!("Unknown matching in dealer_turn().");
             }
         }
+        self.history.record(Event::DealerDone {
+            score: self.dealer.get_score(),
+            busted: self.dealer.is_busted(),
+        });
     }
 
     fn determine_winners(&mut self) {
-        if self.dealer.status == -1 {
-            for player in self.players.iter_mut() {
-                if player.status == 0 {
-                    player.status = 1;
+        let dealer_busted = self.dealer.is_busted();
+        let dealer_total = self.dealer.get_score();
+        for player in self.players.iter_mut() {
+            for hand in player.hands.iter_mut() {
+                if dealer_busted {
+                    if hand.status == 0 {
+                        hand.status = 1;
+                    }
+                } else if hand.cards.get_score() > dealer_total {
+                    hand.status = 1;
+                } else {
+                    hand.status = -1;
                 }
             }
-        } else {
-            let dealer_total = self.dealer.cards.get_score();
-            for player in self.players.iter_mut() {
-                if player.cards.get_score() > dealer_total {
-                    player.status = 1;
-                } else {
-                    player.status = -1;
+        }
+    }
+
+    /// settles every hand against the player's bankroll, counts it in their stats and logs it
+    fn payouts(&mut self) {
+        for (id, player) in self.players.iter_mut().enumerate() {
+            let split = player.hands.len() > 1;
+            for (h, hand) in player.hands.iter().enumerate() {
+                let winnings = match hand.status {
+                    1 => hand.bet,
+                    -1 => -hand.bet,
+                    _ => 0,
+                };
+                player.money += winnings;
+                if hand.bet > 0 {
+                    player.stats.hands += 1;
+                    if hand.status == 1 {
+                        player.stats.wins += 1;
+                    }
+                    if hand.cards.is_busted() {
+                        player.stats.busts += 1;
+                    }
+                    // a natural is 21 on the first two cards, a split hand reaching 21 doesn't count
+                    if !split && hand.cards.num_cards() == 2 && hand.cards.get_score() == 21 {
+                        player.stats.blackjacks += 1;
+                    }
                 }
+                self.history.record(Event::Payout {
+                    player: id,
+                    hand: h,
+                    status: hand.status,
+                    bet: hand.bet,
+                    winnings,
+                });
             }
         }
     }
 
     pub fn show_results(&mut self) {
-        for player in self.players.iter_mut() {
-            if player.status == 1 {
-                println!("Player {} won {} chips!", player.id + 1, player.bet);
-                player.bet = player.bet * 2;
-            } else if player.status == -1 {
-                println!("Player {} lost.", player.id + 1);
-            } else {
-                println!("Player {} status was 0", player.id + 1);
+        let mut results: Vec<String> = Vec::new();
+        for player in self.players.iter() {
+            for hand in player.hands.iter() {
+                if hand.status == 1 {
+                    results.push(format!("{} won {} chips!", player.name, hand.bet));
+                } else if hand.status == -1 {
+                    results.push(format!("{} lost.", player.name));
+                } else {
+                    results.push(format!("{} status was 0", player.name));
+                }
             }
         }
+        for result in results {
+            self.say(result);
+        }
+    }
+
+    pub fn players(&self) -> &[User] {
+        &self.players
+    }
+
+    /// the table as the full screen front end draws it
+    pub fn table(&self) -> String {
+        render::table(&self.dealer, self.reveal_dealer, &self.players, self.turn)
+    }
+
+    /// a message everyone at the table should see
+    fn say(&mut self, message: String) {
+        match &mut self.input {
+            Input::Tui(tui) => tui.say(&message),
+            _ => println!("{}", message),
+        }
+    }
+
+    /// the text version of the table. The full screen front end draws the table itself instead.
+    fn show(&self, text: &str) {
+        if !matches!(self.input, Input::Tui(_)) {
+            println!("{}", text);
+        }
+    }
+
+    /// gives the full screen front end a chance to show the results before the round is over
+    fn finish(&mut self) {
+        let table = self.table();
+        if let Input::Tui(tui) = &mut self.input {
+            tui.wait(&table, "Round over. Press any key to continue.");
+        }
+    }
+
+    /// asks for a bet from `MIN_BET` up to all the player's chips, the limits `get_bets` checks
+    fn read_bet(&mut self, player_id: usize) -> isize {
+        let table = self.table();
+        let player = &self.players[player_id];
+        let (name, max) = (player.name.clone(), player.money);
+        match &mut self.input {
+            Input::Terminal => get_int_input(format!(
+                "{}, Place a bet between {} and {}. Enter 0 to fold.",
+                name,
+                MIN_BET,
+                max
+            )),
+            Input::Tui(tui) => tui.read_bet(&table, &name, max),
+            // a log that runs out of bets folds the rest of the table
+            Input::Scripted { bets, .. } => bets.pop_front().unwrap_or(0),
+        }
+    }
+
+    fn read_action(&mut self, player_id: usize, hand: usize) -> Action {
+        let table = self.table();
+        let name = self.players[player_id].name.clone();
+        match &mut self.input {
+            Input::Terminal => loop {
+                let decision =
+                    get_int_input(String::from("Hit (1), Hold (2), Double (3) or Split (4)?"));
+                match Action::from_input(decision) {
+                    Some(action) => break action,
+                    None => println!("Invalid response {}\n Try Again!!!", decision),
+                }
+            },
+            Input::Tui(tui) => tui.read_action(&table, &name, hand),
+            Input::Scripted { actions, .. } => actions.pop_front().unwrap_or(Action::Stand),
+        }
     }
 }
 
-pub fn get_int_input(message: String) -> isize {
+pub fn get_string_input(message: String) -> String {
     println!("{}", message);
     let mut var = String::new();
     io::stdin()
         .read_line(&mut var)
         .expect("Failed to read line");
+    var.trim().to_string()
+}
+
+/// keeps asking until a whole number is typed in
+pub fn get_int_input(message: String) -> isize {
+    loop {
+        println!("{}", message);
+        let mut var = String::new();
+        io::stdin()
+            .read_line(&mut var)
+            .expect("Failed to read line");
 
-    let var: isize = match var.trim().parse() {
-        Ok(num) => num,
-        Err(_) => {
-            println!("you messed up idiot! Try Again!");
-            get_int_input(message)
+        match var.trim().parse() {
+            Ok(num) => return num,
+            Err(_) => println!("you messed up idiot! Try Again!"),
         }
-    };
-    var
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use card_games::cards::{Card, Rank, Suit};
+
+    fn seats(num_players: isize) -> Vec<User> {
+        (0..num_players).map(User::new).collect()
+    }
+
+    fn scripted_round(seed: u64) -> BlackJack {
+        let mut blackjack = BlackJack::with_seed(seats(2), seed);
+        blackjack.input = Input::Scripted {
+            bets: VecDeque::from(vec![10, 25]),
+            actions: VecDeque::from(vec![Action::Hit, Action::Stand, Action::Stand]),
+        };
+        blackjack.start();
+        blackjack
+    }
+
+    #[test]
+    fn same_seed_same_round() {
+        assert_eq!(scripted_round(7).history, scripted_round(7).history);
+    }
+
+    #[test]
+    fn replay_matches_log() {
+        let blackjack = scripted_round(42);
+        assert_eq!(BlackJack::replay(&blackjack.history), Ok(()));
+    }
+
+    #[test]
+    fn replay_catches_tampered_log() {
+        let mut history = scripted_round(42).history;
+        for event in history.events.iter_mut() {
+            if let Event::Payout { winnings, .. } = event {
+                *winnings += 1000;
+            }
+        }
+        assert!(BlackJack::replay(&history).is_err());
+    }
+
+    #[test]
+    fn double_takes_one_card_and_doubles_the_bet() {
+        let mut blackjack = BlackJack::with_seed(seats(1), 11);
+        blackjack.input = Input::Scripted {
+            bets: VecDeque::from(vec![10]),
+            actions: VecDeque::from(vec![Action::Double, Action::Hit]),
+        };
+        blackjack.start();
+
+        let hand = &blackjack.players[0].hands[0];
+        assert!(hand.doubled);
+        assert_eq!(hand.bet, 20);
+        assert_eq!(hand.cards.num_cards(), 3);
+        assert_eq!(blackjack.players[0].money, 100 + hand.status * 20);
+        assert_eq!(BlackJack::replay(&blackjack.history), Ok(()));
+    }
+
+    #[test]
+    fn split_pair_into_two_hands() {
+        let mut blackjack = BlackJack::with_seed(seats(1), 5);
+        blackjack.input = Input::Scripted {
+            bets: VecDeque::new(),
+            actions: VecDeque::from(vec![Action::Split, Action::Stand, Action::Stand]),
+        };
+        let player = &mut blackjack.players[0];
+        player.hands[0].bet = 10;
+        player.hands[0].cards.add(Card::new(Rank::Eight, Suit::Spades));
+        player.hands[0].cards.add(Card::new(Rank::Eight, Suit::Hearts));
+
+        blackjack.player_turn(0);
+
+        let hands = &blackjack.players[0].hands;
+        assert_eq!(hands.len(), 2);
+        assert_eq!(blackjack.players[0].total_bet(), 20);
+        assert!(hands.iter().all(|h| h.cards.num_cards() == 2));
+        assert_eq!(hands[0].cards.cards()[0].rank(), hands[1].cards.cards()[0].rank());
+    }
+
+    #[test]
+    fn cannot_split_without_a_pair_or_chips() {
+        let mut blackjack = BlackJack::with_seed(seats(1), 5);
+        let player = &mut blackjack.players[0];
+        player.hands[0].bet = 60;
+        player.hands[0].cards.add(Card::new(Rank::Eight, Suit::Spades));
+        player.hands[0].cards.add(Card::new(Rank::Nine, Suit::Hearts));
+        assert!(blackjack.check_action(0, 0, Action::Split).is_err());
+        assert!(blackjack.check_action(0, 0, Action::Double).is_err());
+
+        blackjack.players[0].hands[0].bet = 50;
+        assert_eq!(blackjack.check_action(0, 0, Action::Double), Ok(()));
+    }
+
+    #[test]
+    fn broke_player_sits_out_without_being_asked() {
+        let mut players = seats(2);
+        players[0].money = 3;
+        let mut blackjack = BlackJack::with_seed(players, 21);
+        // only the second player's bet is in the script
+        blackjack.input = Input::Scripted {
+            bets: VecDeque::from(vec![50]),
+            actions: VecDeque::new(),
+        };
+        blackjack.start();
+
+        assert_eq!(blackjack.players[0].money, 3);
+        assert_eq!(blackjack.players[0].stats.hands, 0);
+        assert_eq!(blackjack.players[1].hands[0].bet, 50);
+        assert_eq!(BlackJack::replay(&blackjack.history), Ok(()));
+    }
+
+    #[test]
+    fn stats_count_every_hand_with_chips_on_it() {
+        let blackjack = scripted_round(42);
+        for player in blackjack.players() {
+            let stats = player.stats;
+            assert_eq!(stats.hands, 1);
+            let won = player.hands[0].status == 1;
+            assert_eq!(stats.wins, won as usize);
+            assert_eq!(stats.busts, player.hands[0].cards.is_busted() as usize);
+        }
+    }
 }
@@ -1,12 +1,13 @@
 extern crate rand;
 use crate::deck::Deck;
-use crate::user::User;
+use crate::user::{PlayerHand, User};
+use rand::{thread_rng, Rng};
 use std::io;
 
 pub struct BlackJackSim {
     players: Vec<User>,
     pub deck: Deck,
-    dealer: User,
+    dealer: PlayerHand,
     rounds: i32
 }
 
@@ -16,8 +17,8 @@ impl BlackJackSim {
         for i in 0..4 {
             players.push(User::new(i));
         }
-        let d: Deck = Deck::new();
-        BlackJackSim{players: players, deck: d, dealer: User::new(-1), rounds: rounds}
+        let d: Deck = Deck::new(thread_rng().gen());
+        BlackJackSim{players: players, deck: d, dealer: PlayerHand::new(0), rounds: rounds}
     }
 
     pub fn start(&mut self) {
@@ -26,7 +27,7 @@ impl BlackJackSim {
             self.deal_hands();
             self.show_dealer_hand(false);
             for id in 0..self.players.len() {
-                if self.players[id].bet > 0 {
+                if self.players[id].hands[0].bet > 0 {
                     self.player_turn(id);
                 }
             }
@@ -49,15 +50,15 @@ impl BlackJackSim {
                 let bet = get_int_input(message);
                 match bet {
                     0 => {
-                        player.bet = 0;
-                        player.status = -1;
+                        player.hands[0].bet = 0;
+                        player.hands[0].status = -1;
                         break;
                     }, b if b > max => {
                         println!("Too Hi.");
                     }, b if b < 5 => {
                         println!("Too Low.");
                     }, _ => {
-                        player.bet = bet;
+                        player.hands[0].bet = bet;
                         break;
                     }
                 }
@@ -69,7 +70,7 @@ impl BlackJackSim {
     fn deal_hands(&mut self) {
         for _ in 0..2 { // two cards per player
             for player in self.players.iter_mut() {
-                player.cards.add(self.deck.draw());
+                player.hands[0].cards.add(self.deck.draw());
             }
             self.dealer.cards.add(self.deck.draw());
         }
@@ -97,14 +98,14 @@ impl BlackJackSim {
     fn player_turn(&mut self, player_id: usize) {
         loop {
             self.show_player_cards(player_id);
-            if self.players[player_id].cards.get_score() == -1 {
-                self.players[player_id].status = -1;
+            if self.players[player_id].hands[0].cards.get_score() == -1 {
+                self.players[player_id].hands[0].status = -1;
                 break;
             }
             let decision = get_int_input(String::from("Hit (1) or Hold (2)?"));
             match decision {
                 1 => {
-                    self.players[player_id].cards.add(self.deck.draw());
+                    self.players[player_id].hands[0].cards.add(self.deck.draw());
                 }, 2 => {
                     break;
                 }, _ => {
@@ -116,8 +117,8 @@ impl BlackJackSim {
     }
 
     fn show_player_cards(&self, player_id: usize) {
-        println!("\n### Player's {} HAND ###\n{}", player_id+1, self.players[player_id].cards);
-        if  self.players[player_id].cards.get_score() == -1 {
+        println!("\n### Player's {} HAND ###\n{}", player_id+1, self.players[player_id].hands[0].cards);
+        if  self.players[player_id].hands[0].cards.get_score() == -1 {
             println!("BUSTED!")
         }
     }
@@ -152,17 +153,17 @@ impl BlackJackSim {
     fn determine_winners(&mut self) {
         if self.dealer.status == -1 {
             for player in self.players.iter_mut() {
-                if player.status == 0 {
-                    player.status = 1;
+                if player.hands[0].status == 0 {
+                    player.hands[0].status = 1;
                 }
             }
         } else {
             let dealer_total = self.dealer.cards.get_score();
             for player in self.players.iter_mut() {
-                if player.cards.get_score() > dealer_total {
-                    player.status = 1;
+                if player.hands[0].cards.get_score() > dealer_total {
+                    player.hands[0].status = 1;
                 } else {
-                    player.status = -1;
+                    player.hands[0].status = -1;
                 }
             }
         }
@@ -170,13 +171,13 @@ impl BlackJackSim {
 
     pub fn show_results(&mut self) {
         for player in self.players.iter_mut() {
-            if player.status == 1 {
-                println!("Player {} won {} chips!", player.id +1, player.bet);
-                player.bet = player.bet * 2;
-            } else if player.status == -1 {
-                println!("Player {} lost.", player.id + 1);
+            if player.hands[0].status == 1 {
+                println!("{} won {} chips!", player.name, player.hands[0].bet);
+                player.hands[0].bet = player.hands[0].bet * 2;
+            } else if player.hands[0].status == -1 {
+                println!("{} lost.", player.name);
             } else {
-                println!("Player {} status was 0", player.id +1);
+                println!("{} status was 0", player.name);
             }
         }
     }
@@ -0,0 +1,428 @@
+//! Playing cards that are not tied to any one game. Blackjack scores them one way and
+//! poker another, so nothing in here knows what a card is worth.
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::fmt;
+use std::iter::FromIterator;
+use std::str::FromStr;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Suit {
+    Spades,
+    Hearts,
+    Clubs,
+    Diamonds,
+}
+
+impl Suit {
+    pub const ALL: [Suit; 4] = [Suit::Spades, Suit::Hearts, Suit::Clubs, Suit::Diamonds];
+
+    pub fn symbol(&self) -> char {
+        match self {
+            Suit::Spades => '♠',
+            Suit::Hearts => '♥',
+            Suit::Clubs => '♣',
+            Suit::Diamonds => '♦',
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Suit::Spades => "Spades",
+            Suit::Hearts => "Hearts",
+            Suit::Clubs => "Clubs",
+            Suit::Diamonds => "Diamonds",
+        }
+    }
+
+    /// single letter used in short card codes like "QH"
+    pub fn letter(&self) -> char {
+        match self {
+            Suit::Spades => 'S',
+            Suit::Hearts => 'H',
+            Suit::Clubs => 'C',
+            Suit::Diamonds => 'D',
+        }
+    }
+
+    pub fn from_letter(letter: char) -> Option<Suit> {
+        Suit::ALL
+            .iter()
+            .copied()
+            .find(|s| s.letter() == letter.to_ascii_uppercase())
+    }
+}
+
+/// Ranks sort with the ace high, the way most games compare them.
+/// Use `number` when the ace should count as 1.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Rank {
+    Two = 2,
+    Three,
+    Four,
+    Five,
+    Six,
+    Seven,
+    Eight,
+    Nine,
+    Ten,
+    Jack,
+    Queen,
+    King,
+    Ace,
+}
+
+impl Rank {
+    /// lowest to highest
+    pub const ALL: [Rank; 13] = [
+        Rank::Two,
+        Rank::Three,
+        Rank::Four,
+        Rank::Five,
+        Rank::Six,
+        Rank::Seven,
+        Rank::Eight,
+        Rank::Nine,
+        Rank::Ten,
+        Rank::Jack,
+        Rank::Queen,
+        Rank::King,
+        Rank::Ace,
+    ];
+
+    /// Ace is 1, number cards are their number, then Jack 11, Queen 12 and King 13
+    pub fn number(&self) -> usize {
+        match self {
+            Rank::Ace => 1,
+            rank => *rank as usize,
+        }
+    }
+
+    /// the opposite of `number`. 14 is also taken as an ace
+    pub fn from_number(number: usize) -> Option<Rank> {
+        match number {
+            1 | 14 => Some(Rank::Ace),
+            2..=13 => Some(Rank::ALL[number - 2]),
+            _ => None,
+        }
+    }
+
+    /// "A", "2" through "10", "J", "Q", "K"
+    pub fn symbol(&self) -> String {
+        match self {
+            Rank::Ace => String::from("A"),
+            Rank::Jack => String::from("J"),
+            Rank::Queen => String::from("Q"),
+            Rank::King => String::from("K"),
+            rank => rank.number().to_string(),
+        }
+    }
+
+    /// "Ace", "2" through "10", "Jack", "Queen", "King"
+    pub fn name(&self) -> String {
+        match self {
+            Rank::Ace => String::from("Ace"),
+            Rank::Jack => String::from("Jack"),
+            Rank::Queen => String::from("Queen"),
+            Rank::King => String::from("King"),
+            rank => rank.number().to_string(),
+        }
+    }
+}
+
+/// A card from a standard deck, or a joker. Saved as its short code, see `FromStr`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum Card {
+    Standard { rank: Rank, suit: Suit },
+    Joker,
+}
+
+impl Card {
+    pub fn new(rank: Rank, suit: Suit) -> Card {
+        Card::Standard { rank, suit }
+    }
+
+    /// `None` for a joker
+    pub fn rank(&self) -> Option<Rank> {
+        match self {
+            Card::Standard { rank, .. } => Some(*rank),
+            Card::Joker => None,
+        }
+    }
+
+    /// `None` for a joker
+    pub fn suit(&self) -> Option<Suit> {
+        match self {
+            Card::Standard { suit, .. } => Some(*suit),
+            Card::Joker => None,
+        }
+    }
+
+    pub fn is_joker(&self) -> bool {
+        *self == Card::Joker
+    }
+
+    /// rank then suit letter, like "AS", "10H" or "QD". Jokers are "JK"
+    pub fn code(&self) -> String {
+        match self {
+            Card::Standard { rank, suit } => format!("{}{}", rank.symbol(), suit.letter()),
+            Card::Joker => String::from("JK"),
+        }
+    }
+}
+
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Card::Standard { rank, suit } => write!(f, "{} of {}", rank.name(), suit.name()),
+            Card::Joker => write!(f, "Joker"),
+        }
+    }
+}
+
+/// Reads a short code like "AS", "10H", "TH" or "JK". Letters can be either case.
+impl FromStr for Card {
+    type Err = String;
+
+    fn from_str(code: &str) -> Result<Card, String> {
+        let code = code.trim().to_ascii_uppercase();
+        if code == "JK" {
+            return Ok(Card::Joker);
+        }
+        let mut chars = code.chars();
+        let suit = chars
+            .next_back()
+            .and_then(Suit::from_letter)
+            .ok_or(format!("Bad suit in card {:?}", code))?;
+        let rank = match chars.as_str() {
+            "A" => Rank::Ace,
+            "K" => Rank::King,
+            "Q" => Rank::Queen,
+            "J" => Rank::Jack,
+            "T" => Rank::Ten,
+            number => number
+                .parse()
+                .ok()
+                .filter(|n| (2..=10).contains(n))
+                .and_then(Rank::from_number)
+                .ok_or(format!("Bad rank in card {:?}", code))?,
+        };
+        Ok(Card::new(rank, suit))
+    }
+}
+
+impl From<Card> for String {
+    fn from(card: Card) -> String {
+        card.code()
+    }
+}
+
+impl TryFrom<String> for Card {
+    type Error = String;
+
+    fn try_from(code: String) -> Result<Card, String> {
+        code.parse()
+    }
+}
+
+/// One or more standard decks shuffled together, optionally with jokers.
+/// Cards are dealt from the top until it runs out, then `shuffle` puts them all back.
+pub struct Deck {
+    cards: Vec<Card>,
+    idx: usize,
+}
+
+impl Deck {
+    /// `decks` full decks plus `jokers` jokers in each, in order and not shuffled.
+    /// Every suit runs Ace through King, suits in the order of `Suit::ALL`. Blackjack has
+    /// always built its deck in this order, so seeds from old game logs still shuffle the same.
+    pub fn unshuffled(decks: usize, jokers: usize) -> Deck {
+        let mut ace_low = Rank::ALL;
+        ace_low.rotate_right(1);
+        let mut cards = Vec::with_capacity(decks * (52 + jokers));
+        for _ in 0..decks {
+            for suit in Suit::ALL {
+                for rank in ace_low {
+                    cards.push(Card::new(rank, suit));
+                }
+            }
+            cards.extend(std::iter::repeat_n(Card::Joker, jokers));
+        }
+        Deck { cards, idx: 0 }
+    }
+
+    /// Builds the cards and shuffles them with the given rng.
+    /// Pass a seeded rng (e.g. `StdRng::seed_from_u64`) to get the same deck back every time.
+    pub fn new<R: Rng + ?Sized>(decks: usize, jokers: usize, rng: &mut R) -> Deck {
+        let mut deck = Deck::unshuffled(decks, jokers);
+        deck.shuffle(rng);
+        deck
+    }
+
+    /// a single 52 card deck without jokers
+    pub fn standard<R: Rng + ?Sized>(rng: &mut R) -> Deck {
+        Deck::new(1, 0, rng)
+    }
+
+    /// gathers every card back, including ones already dealt, and shuffles
+    pub fn shuffle<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.cards.shuffle(rng);
+        self.idx = 0;
+    }
+
+    /// the top card, or `None` once every card has been dealt
+    pub fn draw(&mut self) -> Option<Card> {
+        let card = self.cards.get(self.idx).copied();
+        if card.is_some() {
+            self.idx += 1;
+        }
+        card
+    }
+
+    /// cards left to deal
+    pub fn remaining(&self) -> usize {
+        self.cards.len() - self.idx
+    }
+
+    /// every card in the deck, dealt or not
+    pub fn len(&self) -> usize {
+        self.cards.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+}
+
+impl fmt::Display for Deck {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Deck of {} cards. {} undrawn cards.",
+            self.len(),
+            self.remaining()
+        )
+    }
+}
+
+/// The cards a player is holding, in the order they were received
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Hand {
+    cards: Vec<Card>,
+}
+
+impl Hand {
+    pub fn new() -> Hand {
+        Hand { cards: Vec::new() }
+    }
+
+    pub fn add(&mut self, card: Card) {
+        self.cards.push(card);
+    }
+
+    /// takes out the card at `index`, keeping the rest in order
+    pub fn remove(&mut self, index: usize) -> Option<Card> {
+        if index < self.cards.len() {
+            Some(self.cards.remove(index))
+        } else {
+            None
+        }
+    }
+
+    pub fn take_last(&mut self) -> Option<Card> {
+        self.cards.pop()
+    }
+
+    pub fn cards(&self) -> &[Card] {
+        &self.cards
+    }
+
+    pub fn len(&self) -> usize {
+        self.cards.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+
+    /// highest rank first, jokers at the end
+    pub fn sort(&mut self) {
+        self.cards
+            .sort_by(|a, b| b.rank().cmp(&a.rank()).then(a.suit().cmp(&b.suit())));
+    }
+}
+
+impl FromIterator<Card> for Hand {
+    fn from_iter<I: IntoIterator<Item = Card>>(iter: I) -> Hand {
+        Hand {
+            cards: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl fmt::Display for Hand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let codes: Vec<String> = self.cards.iter().map(Card::code).collect();
+        write!(f, "{}", codes.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::collections::HashSet;
+
+    #[test]
+    fn codes_round_trip() {
+        for deck_card in Deck::unshuffled(1, 2).cards {
+            assert_eq!(deck_card.code().parse(), Ok(deck_card));
+        }
+        assert_eq!("th".parse(), Ok(Card::new(Rank::Ten, Suit::Hearts)));
+        assert!("1S".parse::<Card>().is_err());
+        assert!("QX".parse::<Card>().is_err());
+        assert_eq!(
+            serde_json::to_string(&Card::new(Rank::Queen, Suit::Diamonds)).unwrap(),
+            r#""QD""#
+        );
+    }
+
+    #[test]
+    fn multiple_decks_with_jokers() {
+        let mut deck = Deck::new(6, 2, &mut StdRng::seed_from_u64(1));
+        assert_eq!(deck.len(), 6 * 54);
+        let mut jokers = 0;
+        let mut distinct = HashSet::new();
+        while let Some(card) = deck.draw() {
+            jokers += card.is_joker() as usize;
+            distinct.insert(card);
+        }
+        assert_eq!(jokers, 12);
+        assert_eq!(distinct.len(), 53);
+        assert_eq!(deck.remaining(), 0);
+    }
+
+    #[test]
+    fn same_seed_same_shuffle() {
+        let mut a = Deck::standard(&mut StdRng::seed_from_u64(7));
+        let mut b = Deck::standard(&mut StdRng::seed_from_u64(7));
+        for _ in 0..52 {
+            assert_eq!(a.draw(), b.draw());
+        }
+    }
+
+    #[test]
+    fn hand_sorts_high_to_low() {
+        let mut hand: Hand = ["JK", "3C", "AS", "3H"]
+            .iter()
+            .map(|c| c.parse::<Card>().unwrap())
+            .collect();
+        hand.sort();
+        assert_eq!(hand.to_string(), "AS 3H 3C JK");
+    }
+}
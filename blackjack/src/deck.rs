@@ -1,40 +1,36 @@
+use card_games::cards::{self, Card};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 use std::fmt;
-use crate::card::Card;
-
-use rand::seq::SliceRandom;
-use rand::thread_rng;
 
+/// The blackjack shoe, a single shuffled deck. Once every card is dealt the
+/// whole deck is shuffled again and dealing carries on.
 pub struct Deck {
-    cards: [Option<Card>; 52],
-    idx: usize,
+    cards: cards::Deck,
+    rng: StdRng,
 }
 
 impl Deck {
-    pub fn new() -> Deck {
-        let mut cards: [Option<Card>; 52] = [None; 52];
-        let mut i = 0;
-        for suite in 0..4 {
-            for num in 1..14 {
-                cards[i] = Some(Card::new(num, suite));
-                i += 1;
-            }
-        }
-        // shuffle before return deck object
-        let mut rng = thread_rng();  
-        cards.shuffle(&mut rng);
-        Deck{cards: cards, idx: 0}
+    /// Builds the 52 cards and shuffles them from `seed`, so the same seed always gives the same deck.
+    pub fn new(seed: u64) -> Deck {
+        let mut rng = StdRng::seed_from_u64(seed);
+        Deck{cards: cards::Deck::standard(&mut rng), rng}
     }
 
     pub fn draw(&mut self) -> Card {
-        let c = self.cards[self.idx];
-        self.idx = (self.idx + 1) % 52;
-        c.expect("None value found in Deck")
+        match self.cards.draw() {
+            Some(card) => card,
+            None => {
+                self.cards.shuffle(&mut self.rng);
+                self.cards.draw().expect("Deck has no cards")
+            }
+        }
     }
 }
 
 impl fmt::Display for Deck {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let name = format!("Deck Object. {} undrawn cards.", 52 - self.idx);
+        let name = format!("Deck Object. {} undrawn cards.", self.cards.remaining());
         write!(f, "{}", name)
     }
 }
@@ -0,0 +1,359 @@
+//! How often a pair of hole cards wins at hold'em, estimated by dealing out many random boards
+
+use crate::cards::{Card, Deck, Rank, Suit};
+use crate::poker::{card_bit, evaluate_bits};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::str::FromStr;
+
+/// how many times to redraw an opponent's hand before deciding the ranges can't all be dealt
+const MAX_REDRAWS: usize = 1000;
+
+/// Every pair of hole cards a player might be holding
+#[derive(Clone, Debug, PartialEq)]
+pub struct Range {
+    combos: Vec<[Card; 2]>,
+}
+
+impl Range {
+    /// any two cards, all 1326 combinations
+    pub fn any() -> Range {
+        let cards = full_deck();
+        let mut combos = Vec::new();
+        for (i, a) in cards.iter().enumerate() {
+            for b in cards.iter().skip(i + 1) {
+                combos.push([*a, *b]);
+            }
+        }
+        Range { combos }
+    }
+
+    pub fn combos(&self) -> &[[Card; 2]] {
+        &self.combos
+    }
+
+    pub fn len(&self) -> usize {
+        self.combos.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.combos.is_empty()
+    }
+
+    fn add(&mut self, a: Card, b: Card) {
+        if a != b && !self.combos.iter().any(|c| c.contains(&a) && c.contains(&b)) {
+            self.combos.push([a, b]);
+        }
+    }
+
+    /// every suit combination of two ranks. Pairs only get the six with different suits
+    fn add_ranks(&mut self, high: Rank, low: Rank, suited: Option<bool>) {
+        for a in Suit::ALL {
+            for b in Suit::ALL {
+                if suited.is_none_or(|suited| suited == (a == b)) {
+                    self.add(Card::new(high, a), Card::new(low, b));
+                }
+            }
+        }
+    }
+}
+
+/// the 52 cards, no jokers
+fn full_deck() -> Vec<Card> {
+    let mut deck = Deck::unshuffled(1, 0);
+    std::iter::from_fn(|| deck.draw()).collect()
+}
+
+fn rank_from_char(c: char) -> Option<Rank> {
+    match c.to_ascii_uppercase() {
+        'A' => Some(Rank::Ace),
+        'K' => Some(Rank::King),
+        'Q' => Some(Rank::Queen),
+        'J' => Some(Rank::Jack),
+        'T' => Some(Rank::Ten),
+        c => c
+            .to_digit(10)
+            .filter(|d| *d >= 2)
+            .and_then(|d| Rank::from_number(d as usize)),
+    }
+}
+
+/// Reads the usual shorthand, separated by commas:
+/// `QQ` a pair, `QQ+` that pair or better, `AKs` suited, `AKo` offsuit, `AK` either,
+/// `ATs+` raises the second card up to just under the first (ATs, AJs, AQs, AKs),
+/// `AhKh` one exact hand, and `any` for any two cards.
+impl FromStr for Range {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Range, String> {
+        let mut range = Range { combos: Vec::new() };
+        for token in text.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+            if token.eq_ignore_ascii_case("any") || token.eq_ignore_ascii_case("random") {
+                return Ok(Range::any());
+            }
+            let bad = || format!("Can't read {:?} as a range", token);
+            let chars: Vec<char> = token.chars().collect();
+            if chars.len() == 4 && !token.ends_with('+') {
+                // an exact hand like AhKh
+                let a: Card = chars[..2].iter().collect::<String>().parse()?;
+                let b: Card = chars[2..].iter().collect::<String>().parse()?;
+                if a == b {
+                    return Err(bad());
+                }
+                range.add(a, b);
+                continue;
+            }
+            let high = chars
+                .first()
+                .and_then(|c| rank_from_char(*c))
+                .ok_or_else(bad)?;
+            let low = chars
+                .get(1)
+                .and_then(|c| rank_from_char(*c))
+                .ok_or_else(bad)?;
+            let (high, low) = (high.max(low), high.min(low));
+            let mut rest = &token[2..];
+            let plus = rest.ends_with('+');
+            if plus {
+                rest = &rest[..rest.len() - 1];
+            }
+            let suited = match rest {
+                "" => None,
+                "s" | "S" if high != low => Some(true),
+                "o" | "O" => Some(false),
+                _ => return Err(bad()),
+            };
+            let highest_low = if !plus {
+                low
+            } else if high == low {
+                Rank::Ace
+            } else {
+                Rank::ALL[high as usize - 3]
+            };
+            for second in Rank::ALL
+                .iter()
+                .filter(|r| **r >= low && **r <= highest_low)
+            {
+                // pairs climb both cards, QQ+ is QQ, KK, AA
+                let first = if high == low { *second } else { high };
+                range.add_ranks(first, *second, suited);
+            }
+        }
+        if range.is_empty() {
+            return Err(format!("{:?} has no hands in it", text));
+        }
+        Ok(range)
+    }
+}
+
+/// Hero's share of the pot over every simulated board, each between 0 and 1
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Equity {
+    /// boards won outright
+    pub win: f64,
+    /// boards where the pot was split
+    pub tie: f64,
+    /// wins plus each split pot's share, what the hand is worth on average
+    pub equity: f64,
+    pub trials: usize,
+}
+
+/// Deals `trials` random run outs of the board, with each opponent holding a random hand from
+/// their range, and counts how often `hero` wins. `board` can hold 0 to 5 cards already out.
+pub fn equity<R: Rng + ?Sized>(
+    hero: [Card; 2],
+    opponents: &[Range],
+    board: &[Card],
+    trials: usize,
+    rng: &mut R,
+) -> Result<Equity, String> {
+    if opponents.is_empty() {
+        return Err(String::from("Need at least one opponent"));
+    }
+    if board.len() > 5 {
+        return Err(format!("A board is at most 5 cards, not {}", board.len()));
+    }
+    if trials == 0 {
+        return Err(String::from("Need at least one trial"));
+    }
+    let mut dead = 0;
+    for card in hero.iter().chain(board) {
+        let bit = card_bit(card)?;
+        if dead & bit != 0 {
+            return Err(format!("{} is dealt twice", card));
+        }
+        dead |= bit;
+    }
+    let hero_bits = card_bit(&hero[0])? | card_bit(&hero[1])?;
+    let board_bits = dead & !hero_bits;
+
+    // drop hands that need a card already showing
+    let mut ranges: Vec<Vec<u64>> = Vec::new();
+    for (i, range) in opponents.iter().enumerate() {
+        let mut live = Vec::new();
+        for [a, b] in range.combos() {
+            let bits = card_bit(a)? | card_bit(b)?;
+            if bits & dead == 0 {
+                live.push(bits);
+            }
+        }
+        if live.is_empty() {
+            return Err(format!(
+                "Opponent {} has no hands left in their range",
+                i + 1
+            ));
+        }
+        ranges.push(live);
+    }
+
+    let deck: Vec<u64> = full_deck().iter().map(|c| card_bit(c).unwrap()).collect();
+    let to_deal = 5 - board.len();
+    let mut wins = 0.0;
+    let mut ties = 0.0;
+    let mut share = 0.0;
+    let mut stub: Vec<u64> = Vec::with_capacity(52);
+    let mut holdings: Vec<u64> = Vec::with_capacity(opponents.len());
+    for _ in 0..trials {
+        let mut used = dead;
+        holdings.clear();
+        for live in ranges.iter() {
+            let mut redraws = 0;
+            let hand = loop {
+                let hand = *live.choose(rng).expect("range is not empty");
+                if hand & used == 0 {
+                    break hand;
+                }
+                redraws += 1;
+                if redraws > MAX_REDRAWS {
+                    return Err(String::from("The ranges overlap too much to deal them all"));
+                }
+            };
+            used |= hand;
+            holdings.push(hand);
+        }
+
+        stub.clear();
+        stub.extend(deck.iter().filter(|bit| *bit & used == 0));
+        let (run_out, _) = stub.partial_shuffle(rng, to_deal);
+        let full_board = run_out.iter().fold(board_bits, |acc, bit| acc | bit);
+
+        let hero_rank = evaluate_bits(full_board | hero_bits);
+        let mut tied = 1;
+        let mut beaten = false;
+        for hand in holdings.iter() {
+            let rank = evaluate_bits(full_board | hand);
+            if rank > hero_rank {
+                beaten = true;
+                break;
+            } else if rank == hero_rank {
+                tied += 1;
+            }
+        }
+        if !beaten {
+            if tied == 1 {
+                wins += 1.0;
+            } else {
+                ties += 1.0;
+            }
+            share += 1.0 / tied as f64;
+        }
+    }
+    let trials_f = trials as f64;
+    Ok(Equity {
+        win: wins / trials_f,
+        tie: ties / trials_f,
+        equity: share / trials_f,
+        trials,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn cards(codes: &str) -> Vec<Card> {
+        codes
+            .split_whitespace()
+            .map(|c| c.parse().unwrap())
+            .collect()
+    }
+
+    fn hole(codes: &str) -> [Card; 2] {
+        let c = cards(codes);
+        [c[0], c[1]]
+    }
+
+    #[test]
+    fn reads_range_shorthand() {
+        let count = |text: &str| text.parse::<Range>().map(|r| r.len());
+        assert_eq!(count("any"), Ok(1326));
+        assert_eq!(count("AA"), Ok(6));
+        assert_eq!(count("QQ+"), Ok(18));
+        assert_eq!(count("AKs"), Ok(4));
+        assert_eq!(count("AKo"), Ok(12));
+        assert_eq!(count("KA"), Ok(16));
+        assert_eq!(count("ATs+"), Ok(16));
+        assert_eq!(count("AhKh, AKs"), Ok(4));
+        assert_eq!(count("22+, A2+"), Ok(13 * 6 + 12 * 16));
+        assert!("AAs".parse::<Range>().is_err());
+        assert!("A1".parse::<Range>().is_err());
+        assert!("".parse::<Range>().is_err());
+    }
+
+    #[test]
+    fn finished_board_is_exact() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let kings = vec!["KcKd".parse().unwrap()];
+        let sets = equity(
+            hole("AH AD"),
+            &kings,
+            &cards("AS KS 2C 7D 9H"),
+            10,
+            &mut rng,
+        )
+        .unwrap();
+        assert_eq!((sets.win, sets.tie, sets.equity), (1.0, 0.0, 1.0));
+
+        let royal = equity(
+            hole("2H 3D"),
+            &kings,
+            &cards("AS KS QS JS TS"),
+            10,
+            &mut rng,
+        )
+        .unwrap();
+        assert_eq!((royal.win, royal.tie, royal.equity), (0.0, 1.0, 0.5));
+    }
+
+    #[test]
+    fn aces_beat_kings_about_four_times_in_five() {
+        let mut rng = StdRng::seed_from_u64(30);
+        let result = equity(
+            hole("AH AD"),
+            &["KK".parse().unwrap()],
+            &[],
+            20_000,
+            &mut rng,
+        )
+        .unwrap();
+        // the exact answer is about 82%
+        assert!((result.equity - 0.82).abs() < 0.02, "{:?}", result);
+    }
+
+    #[test]
+    fn rejects_impossible_deals() {
+        let mut rng = StdRng::seed_from_u64(2);
+        assert!(equity(
+            hole("AH AD"),
+            &["AA".parse().unwrap()],
+            &cards("AS"),
+            10,
+            &mut rng
+        )
+        .is_err());
+        assert!(equity(hole("AH AH"), &[Range::any()], &[], 10, &mut rng).is_err());
+        assert!(equity(hole("AH KD"), &[], &[], 10, &mut rng).is_err());
+    }
+}
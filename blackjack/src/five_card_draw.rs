@@ -0,0 +1,147 @@
+use crate::blackjack::get_string_input;
+use card_games::cards::{Deck, Hand};
+use card_games::poker::{evaluate, Category};
+use rand::thread_rng;
+use std::cmp::Ordering;
+
+/// the most cards either side may swap in the draw
+const MAX_DISCARDS: usize = 3;
+
+/// One hand of five card draw against the computer. Both get five cards, each can swap up to
+/// three of them, then the best poker hand wins.
+pub fn play() {
+    let mut deck = Deck::standard(&mut thread_rng());
+    let mut player = Hand::new();
+    let mut computer = Hand::new();
+    for _ in 0..5 {
+        player.add(deck.draw().expect("Deck ran out"));
+        computer.add(deck.draw().expect("Deck ran out"));
+    }
+    player.sort();
+
+    println!("Your hand:\n{}", numbered(&player));
+    let discards = read_discards();
+    replace(&mut player, &discards, &mut deck);
+    let computer_discards = computer_discards(&computer);
+    replace(&mut computer, &computer_discards, &mut deck);
+    println!("The computer swapped {} cards.", computer_discards.len());
+
+    let player_rank = evaluate(player.cards()).expect("Player hand is not 5 cards");
+    let computer_rank = evaluate(computer.cards()).expect("Computer hand is not 5 cards");
+    player.sort();
+    computer.sort();
+    println!("You have      {}  {}", player, player_rank);
+    println!("Computer has  {}  {}", computer, computer_rank);
+    match player_rank.cmp(&computer_rank) {
+        Ordering::Greater => println!("You win!"),
+        Ordering::Less => println!("The computer wins."),
+        Ordering::Equal => println!("It's a tie."),
+    }
+}
+
+fn numbered(hand: &Hand) -> String {
+    let mut s = String::new();
+    for (i, card) in hand.cards().iter().enumerate() {
+        s.push_str(&format!("  {}) {}\n", i + 1, card));
+    }
+    s
+}
+
+/// positions (0 based) the player wants to swap, typed like "1 4 5"
+fn read_discards() -> Vec<usize> {
+    loop {
+        let input = get_string_input(format!(
+            "Which cards do you throw away? Up to {}, like \"1 4 5\". Enter keeps them all.",
+            MAX_DISCARDS
+        ));
+        match parse_discards(&input) {
+            Ok(discards) => return discards,
+            Err(message) => println!("{}", message),
+        }
+    }
+}
+
+fn parse_discards(input: &str) -> Result<Vec<usize>, String> {
+    let mut discards = Vec::new();
+    for word in input.split_whitespace() {
+        match word.parse::<usize>() {
+            Ok(num) if (1..=5).contains(&num) => {
+                if !discards.contains(&(num - 1)) {
+                    discards.push(num - 1);
+                }
+            }
+            _ => return Err(format!("{} is not a card from 1 to 5", word)),
+        }
+    }
+    if discards.len() > MAX_DISCARDS {
+        return Err(format!("You can only throw away {} cards", MAX_DISCARDS));
+    }
+    Ok(discards)
+}
+
+/// Keeps anything that pairs up and throws away the rest, highest cards kept first.
+/// A straight or better is already made so it keeps all five.
+fn computer_discards(hand: &Hand) -> Vec<usize> {
+    let rank = evaluate(hand.cards()).expect("Computer hand is not 5 cards");
+    if rank.category() >= Category::Straight {
+        return Vec::new();
+    }
+    let cards = hand.cards();
+    let mut singles: Vec<usize> = (0..cards.len())
+        .filter(|&i| cards.iter().filter(|c| c.rank() == cards[i].rank()).count() == 1)
+        .collect();
+    singles.sort_by_key(|&i| cards[i].rank());
+    singles.truncate(MAX_DISCARDS);
+    singles
+}
+
+fn replace(hand: &mut Hand, discards: &[usize], deck: &mut Deck) {
+    let mut discards = discards.to_vec();
+    // take out the highest positions first so the others don't move
+    discards.sort_unstable_by(|a, b| b.cmp(a));
+    for i in discards {
+        hand.remove(i);
+    }
+    while hand.len() < 5 {
+        hand.add(deck.draw().expect("Deck ran out"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use card_games::cards::Card;
+
+    fn hand(codes: &str) -> Hand {
+        codes
+            .split(' ')
+            .map(|c| c.parse::<Card>().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn reads_discards() {
+        assert_eq!(parse_discards(""), Ok(vec![]));
+        assert_eq!(parse_discards(" 5 1 1"), Ok(vec![4, 0]));
+        assert!(parse_discards("1 2 3 4").is_err());
+        assert!(parse_discards("6").is_err());
+    }
+
+    #[test]
+    fn computer_keeps_pairs_and_made_hands() {
+        assert_eq!(computer_discards(&hand("9S 2H 9C KD 4S")), vec![1, 4, 3]);
+        assert_eq!(
+            computer_discards(&hand("2S 5S 9S JS KS")),
+            Vec::<usize>::new()
+        );
+        assert_eq!(computer_discards(&hand("AS 2H 7C 9D KS")), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn replacing_keeps_five_cards() {
+        let mut deck = Deck::unshuffled(1, 0);
+        let mut h = hand("9S 2H 9C KD 4S");
+        replace(&mut h, &[1, 4], &mut deck);
+        assert_eq!(h.to_string(), "9S 9C KD AS 2S");
+    }
+}
@@ -1,8 +1,31 @@
 use std::fmt;
-use crate::card::Card;
+use card_games::cards::{self, Card, Rank};
 
+/// What a card counts for in blackjack. Aces are 11 here, `calc_score` drops them to 1 when needed.
+pub fn card_value(card: &Card) -> usize {
+    match card.rank() {
+        Some(Rank::Ace) => 11,
+        Some(Rank::Jack) | Some(Rank::Queen) | Some(Rank::King) => 10,
+        Some(rank) => rank.number(),
+        None => 0, // jokers are never dealt in blackjack
+    }
+}
+
+fn busted_value(card: &Card) -> usize { // for checking if busted
+    if is_ace(card) {
+        1
+    } else {
+        card_value(card)
+    }
+}
+
+fn is_ace(card: &Card) -> bool {
+    card.rank() == Some(Rank::Ace)
+}
+
+/// A blackjack hand. The cards are a plain `cards::Hand`, this adds the scoring.
 pub struct Hand {
-    cards: Vec<Card>,
+    cards: cards::Hand,
     best_score: usize,
     busted: bool,
 }
@@ -11,7 +34,7 @@ impl fmt::Display for Hand {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut s = String::new();
         s.push_str("____ Hand: _____\n");
-        for card in self.cards.iter() {
+        for card in self.cards.cards() {
             s.push_str(&format!("  > {}\n", card));
         }
         s.push_str(&format!("___ Score: {} ___\n", self.calc_score()));
@@ -21,7 +44,7 @@ impl fmt::Display for Hand {
 
 impl Hand {
     pub fn new() -> Hand {
-        Hand{cards: Vec::new(), best_score:  0, busted: false}
+        Hand{cards: cards::Hand::new(), best_score:  0, busted: false}
     }
 
     pub fn get_score(&self) -> isize {
@@ -35,7 +58,7 @@ impl Hand {
     pub fn get_first_card(&self) -> Card {
         // used when dealer's first card is shown but the other one is covered
         if self.num_cards() > 0 {
-            self.cards[0]
+            self.cards.cards()[0]
         } else {
             panic!("Dont have a card to give you");
         }
@@ -50,10 +73,10 @@ impl Hand {
     }
 
     pub fn add(&mut self, card: Card) {
-        self.cards.push(card);
+        self.cards.add(card);
         let mut min_total = 0;
-        for card in self.cards.iter() {
-            min_total += card.get_busted_value();
+        for card in self.cards.cards() {
+            min_total += busted_value(card);
         }
         if min_total > 21 {
             self.busted = true;
@@ -66,11 +89,11 @@ impl Hand {
         // Must add all none ace cards, then if there is room for an 11, add that once, then all other aces will be 1
         let mut num_aces = 0;
         let mut score = 0;
-        for card in self.cards.iter() {
-            if card.is_ace() {
+        for card in self.cards.cards() {
+            if is_ace(card) {
                 num_aces += 1;
             }
-            score += card.get_busted_value();
+            score += busted_value(card);
         }
         if score <= 11 && num_aces > 0 {
             score += 10;
@@ -78,9 +101,26 @@ impl Hand {
         score
     }
 
+    pub fn cards(&self) -> &[Card] {
+        self.cards.cards()
+    }
+
+    /// two cards of the same rank, which can be split into two hands
+    pub fn is_pair(&self) -> bool {
+        self.num_cards() == 2 && self.cards()[0].rank() == self.cards()[1].rank()
+    }
+
+    /// takes the last card back out of the hand, used when splitting a pair
+    pub fn take_last(&mut self) -> Option<Card> {
+        let card = self.cards.take_last();
+        self.busted = self.cards.cards().iter().map(busted_value).sum::<usize>() > 21;
+        self.best_score = self.calc_score();
+        card
+    }
+
     pub fn has_ace(&self) -> bool {
-        for card in self.cards.iter() {
-            if card.is_ace() {
+        for card in self.cards.cards() {
+            if is_ace(card) {
                 return true
             }
         }
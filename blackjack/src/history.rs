@@ -0,0 +1,238 @@
+use card_games::cards::Card;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+/// Who a card was dealt to
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Seat {
+    Player(usize),
+    Dealer,
+}
+
+/// A decision a player makes on their turn
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Action {
+    Hit,
+    Stand,
+    /// double the bet, take exactly one more card and stand
+    Double,
+    /// split a pair into two hands with the same bet each
+    Split,
+}
+
+impl Action {
+    /// maps the number typed at the hit/hold prompt to an action
+    pub fn from_input(input: isize) -> Option<Action> {
+        match input {
+            1 => Some(Action::Hit),
+            2 => Some(Action::Stand),
+            3 => Some(Action::Double),
+            4 => Some(Action::Split),
+            _ => None,
+        }
+    }
+}
+
+/// One thing that happened during a round. Each event is written as a single line of json.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// always the first event of a round. The seed is all that is needed to rebuild the shuffled deck
+    RoundStart {
+        seed: u64,
+        players: usize,
+        /// chips each player sat down with, needed to check their bets the same way again
+        bankrolls: Vec<isize>,
+    },
+    Bet {
+        player: usize,
+        amount: isize,
+    },
+    /// `hand` is which of the player's hands got the card once they have split
+    Deal {
+        seat: Seat,
+        hand: usize,
+        card: Card,
+    },
+    Action {
+        player: usize,
+        hand: usize,
+        action: Action,
+    },
+    DealerDone {
+        score: isize,
+        busted: bool,
+    },
+    /// status is 1 won, -1 lost, 0 tie. winnings is negative when chips were lost
+    Payout {
+        player: usize,
+        hand: usize,
+        status: isize,
+        bet: isize,
+        winnings: isize,
+    },
+}
+
+/// The ordered event log of a single round
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct History {
+    pub events: Vec<Event>,
+}
+
+impl History {
+    pub fn new() -> History {
+        History { events: Vec::new() }
+    }
+
+    pub fn record(&mut self, event: Event) {
+        self.events.push(event);
+    }
+
+    /// the seed and number of players from the `RoundStart` event
+    pub fn round_start(&self) -> Option<(u64, usize)> {
+        match self.events.first() {
+            Some(Event::RoundStart { seed, players, .. }) => Some((*seed, *players)),
+            _ => None,
+        }
+    }
+
+    /// chips each player started the round with
+    pub fn starting_bankrolls(&self) -> Vec<isize> {
+        match self.events.first() {
+            Some(Event::RoundStart { bankrolls, .. }) => bankrolls.clone(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// every bet in the order they were placed
+    pub fn bets(&self) -> VecDeque<isize> {
+        self.events
+            .iter()
+            .filter_map(|e| match e {
+                Event::Bet { amount, .. } => Some(*amount),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// every decision in the order they were made
+    pub fn actions(&self) -> VecDeque<Action> {
+        self.events
+            .iter()
+            .filter_map(|e| match e {
+                Event::Action { action, .. } => Some(*action),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Checks that another log of the same round played out exactly like this one
+    pub fn verify(&self, other: &History) -> Result<(), String> {
+        for (i, (logged, replayed)) in self.events.iter().zip(other.events.iter()).enumerate() {
+            if logged != replayed {
+                return Err(format!(
+                    "Event {} does not match. Logged {:?} but replay gave {:?}",
+                    i, logged, replayed
+                ));
+            }
+        }
+        if self.events.len() != other.events.len() {
+            return Err(format!(
+                "Logged round had {} events but replay gave {}",
+                self.events.len(),
+                other.events.len()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Appends the round to a json lines file, creating it if needed
+    pub fn append_to(&self, path: &Path) -> Result<(), String> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| format!("Cannot open {}: {}", path.display(), e))?;
+        for event in self.events.iter() {
+            let line = serde_json::to_string(event).map_err(|e| e.to_string())?;
+            writeln!(file, "{}", line).map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Reads a json lines file back into rounds. A new round begins at every `RoundStart`.
+    pub fn read_rounds(path: &Path) -> Result<Vec<History>, String> {
+        let file =
+            File::open(path).map_err(|e| format!("Cannot open {}: {}", path.display(), e))?;
+        let mut rounds: Vec<History> = Vec::new();
+        for (line_num, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(|e| e.to_string())?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let event: Event = serde_json::from_str(&line)
+                .map_err(|e| format!("Bad event on line {}: {}", line_num + 1, e))?;
+            match (&event, rounds.last_mut()) {
+                (Event::RoundStart { .. }, _) => rounds.push(History {
+                    events: vec![event],
+                }),
+                (_, Some(round)) => round.record(event),
+                (_, None) => {
+                    return Err(format!(
+                        "Line {} comes before any round_start event",
+                        line_num + 1
+                    ))
+                }
+            }
+        }
+        Ok(rounds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use card_games::cards::{Rank, Suit};
+
+    #[test]
+    fn events_round_trip_through_json_lines() {
+        let mut first = History::new();
+        first.record(Event::RoundStart {
+            seed: 3,
+            players: 1,
+            bankrolls: vec![80],
+        });
+        first.record(Event::Bet {
+            player: 0,
+            amount: 20,
+        });
+        first.record(Event::Deal {
+            seat: Seat::Dealer,
+            hand: 0,
+            card: Card::new(Rank::Ace, Suit::Clubs),
+        });
+        let mut second = History::new();
+        second.record(Event::RoundStart {
+            seed: 4,
+            players: 1,
+            bankrolls: vec![120],
+        });
+        second.record(Event::Action {
+            player: 0,
+            hand: 1,
+            action: Action::Split,
+        });
+
+        let path =
+            std::env::temp_dir().join(format!("blackjack_history_{}.jsonl", std::process::id()));
+        first.append_to(&path).unwrap();
+        second.append_to(&path).unwrap();
+        let rounds = History::read_rounds(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(rounds, Ok(vec![first, second]));
+    }
+}
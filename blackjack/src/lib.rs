@@ -0,0 +1,6 @@
+//! Building blocks for card games: a generic deck and hand in `cards`,
+//! poker hand evaluation in `poker` and hold'em odds in `equity`.
+
+pub mod cards;
+pub mod equity;
+pub mod poker;
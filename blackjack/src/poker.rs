@@ -0,0 +1,320 @@
+//! Ranking poker hands
+
+use crate::cards::{Card, Rank};
+use std::fmt;
+
+/// Kinds of poker hand, weakest first
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Category {
+    HighCard,
+    OnePair,
+    TwoPair,
+    ThreeOfAKind,
+    Straight,
+    Flush,
+    FullHouse,
+    FourOfAKind,
+    StraightFlush,
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Category::HighCard => "High Card",
+            Category::OnePair => "One Pair",
+            Category::TwoPair => "Two Pair",
+            Category::ThreeOfAKind => "Three of a Kind",
+            Category::Straight => "Straight",
+            Category::Flush => "Flush",
+            Category::FullHouse => "Full House",
+            Category::FourOfAKind => "Four of a Kind",
+            Category::StraightFlush => "Straight Flush",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl Category {
+    /// weakest first, so a category's position here is what gets packed into a `HandRank`
+    const ALL: [Category; 9] = [
+        Category::HighCard,
+        Category::OnePair,
+        Category::TwoPair,
+        Category::ThreeOfAKind,
+        Category::Straight,
+        Category::Flush,
+        Category::FullHouse,
+        Category::FourOfAKind,
+        Category::StraightFlush,
+    ];
+}
+
+/// How strong a hand is. Compare two of these to see which hand wins, equal means a split pot.
+///
+/// It is packed into one number so comparing is a single integer compare: the category in the
+/// top bits, then up to five ranks of 4 bits each. The ranks are the ones that make the hand
+/// first, then the kickers, e.g. a full house of kings over fours is `K 4`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HandRank(u32);
+
+impl HandRank {
+    fn new(category: Category, ranks: &[u32]) -> HandRank {
+        let mut packed = category as u32;
+        for i in 0..5 {
+            packed = packed << 4 | ranks.get(i).copied().unwrap_or(0);
+        }
+        HandRank(packed)
+    }
+
+    pub fn category(&self) -> Category {
+        Category::ALL[(self.0 >> 20) as usize]
+    }
+
+    /// the ranks that decide ties, most important first
+    pub fn ranks(&self) -> Vec<Rank> {
+        (0..5)
+            .rev()
+            .filter_map(|i| Rank::from_number((self.0 >> (i * 4) & 0xF) as usize))
+            .collect()
+    }
+}
+
+impl fmt::Display for HandRank {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ranks: Vec<String> = self.ranks().iter().map(Rank::symbol).collect();
+        write!(f, "{} ({})", self.category(), ranks.join(" "))
+    }
+}
+
+/// A set of cards as one bit per card: 16 bits per suit, the rank's value (2 to 14) picks the bit.
+/// Lets the evaluator find flushes, pairs and straights with a few bit operations.
+pub(crate) fn card_bit(card: &Card) -> Result<u64, String> {
+    match card {
+        Card::Standard { rank, suit } => Ok(1 << (*suit as u64 * 16 + *rank as u64)),
+        Card::Joker => Err(String::from("Jokers can't be ranked")),
+    }
+}
+
+/// Ranks the best five card hand out of 5, 6 or 7 cards, so it works for both five card
+/// draw and hold'em. Jokers are not wild here, so they are an error, as is the same card twice.
+pub fn evaluate(cards: &[Card]) -> Result<HandRank, String> {
+    if !(5..=7).contains(&cards.len()) {
+        return Err(format!("A poker hand is 5 to 7 cards, not {}", cards.len()));
+    }
+    let mut bits = 0;
+    for card in cards {
+        let bit = card_bit(card)?;
+        if bits & bit != 0 {
+            return Err(format!("{} is in the hand twice", card));
+        }
+        bits |= bit;
+    }
+    Ok(evaluate_bits(bits))
+}
+
+/// highest card of the best straight in a 16 bit rank mask, if there is one
+fn straight_high(ranks: u32) -> Option<u32> {
+    // copy the ace down to bit 1 so it can play low in A-2-3-4-5
+    let ranks = ranks | (ranks >> 14 & 1) << 1;
+    let runs = ranks & ranks >> 1 & ranks >> 2 & ranks >> 3 & ranks >> 4;
+    if runs == 0 {
+        None
+    } else {
+        Some(31 - runs.leading_zeros() + 4)
+    }
+}
+
+/// the `count` highest ranks set in a rank mask
+fn top_ranks(ranks: u32, count: usize) -> Vec<u32> {
+    (2..=14)
+        .rev()
+        .filter(|r| ranks >> r & 1 == 1)
+        .take(count)
+        .collect()
+}
+
+/// `evaluate` for 5 to 7 cards already turned into bits by `card_bit`. Does no checking.
+pub(crate) fn evaluate_bits(bits: u64) -> HandRank {
+    let suits = [0, 16, 32, 48].map(|shift| (bits >> shift & 0xFFFF) as u32);
+
+    // With 7 cards or fewer a flush leaves too few cards for quads or a full house,
+    // so a flush here is already the best the hand can do apart from a straight flush.
+    if let Some(flush) = suits.iter().find(|s| s.count_ones() >= 5) {
+        return match straight_high(*flush) {
+            Some(high) => HandRank::new(Category::StraightFlush, &[high]),
+            None => HandRank::new(Category::Flush, &top_ranks(*flush, 5)),
+        };
+    }
+
+    let any = suits[0] | suits[1] | suits[2] | suits[3];
+    let mut quads = Vec::new();
+    let mut trips = Vec::new();
+    let mut pairs = Vec::new();
+    let mut singles = Vec::new();
+    for rank in (2..=14).rev() {
+        match suits.iter().filter(|s| *s >> rank & 1 == 1).count() {
+            4 => quads.push(rank),
+            3 => trips.push(rank),
+            2 => pairs.push(rank),
+            1 => singles.push(rank),
+            _ => {}
+        }
+    }
+
+    if let Some(&quad) = quads.first() {
+        let kicker = top_ranks(any & !(1 << quad), 1);
+        return HandRank::new(Category::FourOfAKind, &[quad, kicker[0]]);
+    }
+    if let Some(&trip) = trips.first() {
+        // the pair can come from a second set of trips
+        let pair = trips.get(1).copied().max(pairs.first().copied());
+        if let Some(pair) = pair {
+            return HandRank::new(Category::FullHouse, &[trip, pair]);
+        }
+    }
+    if let Some(high) = straight_high(any) {
+        return HandRank::new(Category::Straight, &[high]);
+    }
+    if let Some(&trip) = trips.first() {
+        return HandRank::new(Category::ThreeOfAKind, &[trip, singles[0], singles[1]]);
+    }
+    if pairs.len() >= 2 {
+        // a third pair can still be the kicker
+        let kicker = top_ranks(any & !(1 << pairs[0]) & !(1 << pairs[1]), 1);
+        return HandRank::new(Category::TwoPair, &[pairs[0], pairs[1], kicker[0]]);
+    }
+    if let Some(&pair) = pairs.first() {
+        return HandRank::new(
+            Category::OnePair,
+            &[pair, singles[0], singles[1], singles[2]],
+        );
+    }
+    HandRank::new(Category::HighCard, &singles[..5])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::Deck;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::collections::HashSet;
+
+    fn rank(codes: &str) -> HandRank {
+        evaluate(&cards(codes)).unwrap()
+    }
+
+    #[test]
+    fn finds_every_category() {
+        let hands = [
+            ("2S 5H 9C JD KS", Category::HighCard),
+            ("2S 2H 9C JD KS", Category::OnePair),
+            ("2S 2H 9C 9D KS", Category::TwoPair),
+            ("2S 2H 2C JD KS", Category::ThreeOfAKind),
+            ("AS 2H 3C 4D 5S", Category::Straight),
+            ("KS 2H 3C 4D 5S", Category::HighCard),
+            ("2S 5S 9S JS KS", Category::Flush),
+            ("2S 2H 2C KD KS", Category::FullHouse),
+            ("2S 2H 2C 2D KS", Category::FourOfAKind),
+            ("TH JH QH KH AH", Category::StraightFlush),
+        ];
+        for (codes, category) in hands.iter() {
+            assert_eq!(rank(codes).category(), *category, "{}", codes);
+        }
+    }
+
+    #[test]
+    fn ties_are_broken_by_ranks_then_kickers() {
+        assert!(rank("9S 9H 4C 3D 2S") > rank("8S 8H AC KD QS"));
+        assert!(rank("9S 9H 5C 3D 2S") > rank("9C 9D 4C 3H 2H"));
+        assert!(rank("2S 3H 4C 5D 6S") > rank("AS 2H 3C 4D 5S"));
+        assert!(rank("3S 3H 3C 2D 2S") > rank("2C 2D 2H AD AS"));
+        assert_eq!(rank("KS QS 9S 5S 2S"), rank("KH QH 9H 5H 2H"));
+    }
+
+    #[test]
+    fn rejects_jokers_and_wrong_sizes() {
+        let joker = cards("2S 3S 4S 5S JK");
+        assert!(evaluate(&joker).is_err());
+        assert!(evaluate(&joker[..4]).is_err());
+        assert!(evaluate(&cards("2S 3S 4S 5S 5S"))
+            .unwrap_err()
+            .contains("twice"));
+    }
+
+    fn cards(codes: &str) -> Vec<Card> {
+        codes.split(' ').map(|c| c.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn seven_cards_make_the_best_five() {
+        let best = rank("AS AH KC KD QS");
+        assert_eq!(evaluate(&cards("AS AH KC KD QS QH 2C")), Ok(best));
+        let straight_flush = evaluate(&cards("AH 2H 3H 4H 5H 6H 6S")).unwrap();
+        assert_eq!(straight_flush.category(), Category::StraightFlush);
+        assert_eq!(straight_flush.ranks(), vec![Rank::Six]);
+        let boat = evaluate(&cards("9S 9H 9C 4D 4S 4H 2C")).unwrap();
+        assert_eq!(boat.ranks(), vec![Rank::Nine, Rank::Four]);
+        assert_eq!(boat.to_string(), "Full House (9 4)");
+    }
+
+    /// the best of every 5 card subset, done the slow way
+    fn best_of_subsets(cards: &[Card]) -> HandRank {
+        let mut best = None;
+        for skip_a in 0..cards.len() {
+            for skip_b in skip_a + 1..cards.len() {
+                let five: Vec<Card> = (0..cards.len())
+                    .filter(|i| *i != skip_a && *i != skip_b)
+                    .map(|i| cards[i])
+                    .collect();
+                best = best.max(Some(evaluate(&five).unwrap()));
+            }
+        }
+        best.unwrap()
+    }
+
+    #[test]
+    fn seven_card_rank_matches_the_best_subset() {
+        let mut rng = StdRng::seed_from_u64(30);
+        for _ in 0..2000 {
+            let mut deck = Deck::standard(&mut rng);
+            let seven: Vec<Card> = (0..7).map(|_| deck.draw().unwrap()).collect();
+            assert_eq!(evaluate(&seven), Ok(best_of_subsets(&seven)), "{:?}", seven);
+        }
+    }
+
+    #[test]
+    fn counts_every_five_card_hand() {
+        let deck = Deck::unshuffled(1, 0);
+        let bits: Vec<u64> = deck_cards(deck)
+            .iter()
+            .map(|c| card_bit(c).unwrap())
+            .collect();
+        let mut counts = [0usize; 9];
+        let mut distinct = HashSet::new();
+        for a in 0..52 {
+            for b in a + 1..52 {
+                for c in b + 1..52 {
+                    for d in c + 1..52 {
+                        let four = bits[a] | bits[b] | bits[c] | bits[d];
+                        for e in bits.iter().skip(d + 1) {
+                            let rank = evaluate_bits(four | e);
+                            counts[rank.category() as usize] += 1;
+                            distinct.insert(rank);
+                        }
+                    }
+                }
+            }
+        }
+        assert_eq!(
+            counts,
+            [1_302_540, 1_098_240, 123_552, 54_912, 10_200, 5_108, 3_744, 624, 40]
+        );
+        assert_eq!(counts.iter().sum::<usize>(), 2_598_960);
+        assert_eq!(distinct.len(), 7462);
+    }
+
+    fn deck_cards(mut deck: Deck) -> Vec<Card> {
+        std::iter::from_fn(|| deck.draw()).collect()
+    }
+}
@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// what a new profile, or one that went broke, starts with
+pub const STARTING_BANKROLL: isize = 100;
+
+/// Lifetime counts for a player. Every hand with chips on it counts, split hands included.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Stats {
+    pub hands: usize,
+    pub wins: usize,
+    pub blackjacks: usize,
+    pub busts: usize,
+    /// times the player ran out of chips and started over
+    #[serde(default)]
+    pub rebuys: usize,
+}
+
+impl Stats {
+    pub fn add(&mut self, other: &Stats) {
+        self.hands += other.hands;
+        self.wins += other.wins;
+        self.blackjacks += other.blackjacks;
+        self.busts += other.busts;
+        self.rebuys += other.rebuys;
+    }
+
+    /// percent of hands won, 0 when nothing has been played
+    pub fn win_rate(&self) -> f64 {
+        if self.hands == 0 {
+            0.0
+        } else {
+            self.wins as f64 * 100.0 / self.hands as f64
+        }
+    }
+}
+
+/// A named player whose bankroll and stats carry over between games
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    pub bankroll: isize,
+    pub stats: Stats,
+}
+
+impl Profile {
+    pub fn new(name: &str) -> Profile {
+        Profile {
+            name: name.to_string(),
+            bankroll: STARTING_BANKROLL,
+            stats: Stats::default(),
+        }
+    }
+}
+
+/// Every saved profile. Kept in a small json file next to where the game is run.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Profiles {
+    profiles: Vec<Profile>,
+}
+
+impl Profiles {
+    /// a missing file just means nobody has played yet
+    pub fn load(path: &Path) -> Result<Profiles, String> {
+        if !path.exists() {
+            return Ok(Profiles::default());
+        }
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        serde_json::from_str(&contents)
+            .map_err(|e| format!("Bad profile file {}: {}", path.display(), e))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let contents = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, contents).map_err(|e| format!("Cannot write {}: {}", path.display(), e))
+    }
+
+    /// finds a profile by name, ignoring case
+    pub fn get(&self, name: &str) -> Option<&Profile> {
+        self.profiles
+            .iter()
+            .find(|p| p.name.eq_ignore_ascii_case(name))
+    }
+
+    /// stores the profile, replacing any saved one with the same name
+    pub fn update(&mut self, profile: Profile) {
+        match self
+            .profiles
+            .iter_mut()
+            .find(|p| p.name.eq_ignore_ascii_case(&profile.name))
+        {
+            Some(saved) => *saved = profile,
+            None => self.profiles.push(profile),
+        }
+    }
+
+    /// profiles from richest to poorest, ties broken by most hands won
+    pub fn leaderboard(&self) -> Vec<&Profile> {
+        let mut ranked: Vec<&Profile> = self.profiles.iter().collect();
+        ranked.sort_by(|a, b| {
+            b.bankroll
+                .cmp(&a.bankroll)
+                .then(b.stats.wins.cmp(&a.stats.wins))
+                .then(a.name.cmp(&b.name))
+        });
+        ranked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(name: &str, bankroll: isize, wins: usize) -> Profile {
+        let mut p = Profile::new(name);
+        p.bankroll = bankroll;
+        p.stats.wins = wins;
+        p
+    }
+
+    #[test]
+    fn update_replaces_by_name() {
+        let mut profiles = Profiles::default();
+        profiles.update(profile("Ann", 100, 0));
+        profiles.update(profile("ann", 250, 3));
+        assert_eq!(profiles.get("ANN"), Some(&profile("ann", 250, 3)));
+        assert_eq!(profiles.leaderboard().len(), 1);
+    }
+
+    #[test]
+    fn leaderboard_ranks_by_bankroll_then_wins() {
+        let mut profiles = Profiles::default();
+        profiles.update(profile("Cal", 90, 9));
+        profiles.update(profile("Bea", 300, 1));
+        profiles.update(profile("Dee", 90, 12));
+        let names: Vec<&str> = profiles
+            .leaderboard()
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Bea", "Dee", "Cal"]);
+    }
+
+    #[test]
+    fn saves_and_loads() {
+        let mut profiles = Profiles::default();
+        profiles.update(profile("Ann", 40, 2));
+        let path =
+            std::env::temp_dir().join(format!("blackjack_profiles_{}.json", std::process::id()));
+        profiles.save(&path).unwrap();
+        let loaded = Profiles::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, Ok(profiles));
+    }
+}
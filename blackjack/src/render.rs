@@ -0,0 +1,213 @@
+use crate::hand::{self, Hand};
+use crate::user::User;
+use card_games::cards::Card;
+
+/// rows of text that make up one drawn card
+const CARD_HEIGHT: usize = 5;
+
+/// Draws a single card as ascii art. `None` draws the back of a face down card.
+/// ```text
+/// +-----+
+/// |Q    |
+/// |  ♥  |
+/// |    Q|
+/// +-----+
+/// ```
+pub fn card_art(card: Option<&Card>) -> [String; CARD_HEIGHT] {
+    let edge = String::from("+-----+");
+    match card {
+        Some(card) => {
+            let rank = card
+                .rank()
+                .map(|r| r.symbol())
+                .unwrap_or_else(|| String::from("JK"));
+            [
+                edge.clone(),
+                format!("|{:<5}|", rank),
+                format!("|  {}  |", card.suit().map(|s| s.symbol()).unwrap_or('*')),
+                format!("|{:>5}|", rank),
+                edge,
+            ]
+        }
+        None => [
+            edge.clone(),
+            String::from("|/////|"),
+            String::from("|/////|"),
+            String::from("|/////|"),
+            edge,
+        ],
+    }
+}
+
+/// Lays cards out side by side. Every card after the first `face_up` cards is drawn face down.
+pub fn cards_row(cards: &[Card], face_up: usize, indent: &str) -> String {
+    let arts: Vec<[String; CARD_HEIGHT]> = cards
+        .iter()
+        .enumerate()
+        .map(|(i, card)| card_art(if i < face_up { Some(card) } else { None }))
+        .collect();
+    let mut out = String::new();
+    for row in 0..CARD_HEIGHT {
+        let line: Vec<&str> = arts.iter().map(|art| art[row].as_str()).collect();
+        out.push_str(indent);
+        out.push_str(&line.join(" "));
+        out.push('\n');
+    }
+    out
+}
+
+fn score_text(hand: &Hand) -> String {
+    match hand.get_score() {
+        -1 => String::from("BUST"),
+        score => score.to_string(),
+    }
+}
+
+/// The whole table as text: the dealer, then every player with their bankroll and hands.
+/// The dealer's second card stays face down until `reveal_dealer`, and the hand whose turn
+/// it is gets marked with `>>`.
+pub fn table(
+    dealer: &Hand,
+    reveal_dealer: bool,
+    players: &[User],
+    turn: Option<(usize, usize)>,
+) -> String {
+    let mut out = String::new();
+    if reveal_dealer {
+        out.push_str(&format!("Dealer  Score: {}\n", score_text(dealer)));
+        out.push_str(&cards_row(dealer.cards(), dealer.num_cards(), ""));
+    } else if dealer.num_cards() > 0 {
+        out.push_str(&format!(
+            "Dealer  Showing: {}\n",
+            hand::card_value(&dealer.get_first_card())
+        ));
+        out.push_str(&cards_row(dealer.cards(), 1, ""));
+    } else {
+        out.push_str("Dealer\n");
+    }
+
+    for (id, player) in players.iter().enumerate() {
+        out.push_str(&format!(
+            "\n{}  Bankroll: {}  Bet: {}\n",
+            player.name,
+            player.money,
+            player.total_bet()
+        ));
+        for (h, hand) in player.hands.iter().enumerate() {
+            let marker = if turn == Some((id, h)) { ">> " } else { "   " };
+            if hand.bet == 0 {
+                out.push_str(&format!("{}Sitting out\n", marker));
+                continue;
+            }
+            let doubled = if hand.doubled { "  (doubled)" } else { "" };
+            out.push_str(&format!(
+                "{}Hand {}  Bet: {}  Score: {}{}\n",
+                marker,
+                h + 1,
+                hand.bet,
+                score_text(&hand.cards),
+                doubled
+            ));
+            out.push_str(&cards_row(
+                hand.cards.cards(),
+                hand.cards.num_cards(),
+                "   ",
+            ));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::user::PlayerHand;
+    use card_games::cards::{Rank, Suit};
+
+    #[test]
+    fn draws_face_up_and_face_down_cards() {
+        let row = cards_row(
+            &[
+                Card::new(Rank::Ten, Suit::Diamonds),
+                Card::new(Rank::Queen, Suit::Hearts),
+            ],
+            1,
+            "",
+        );
+        assert_eq!(
+            row,
+            "\
++-----+ +-----+
+|10   | |/////|
+|  ♦  | |/////|
+|   10| |/////|
++-----+ +-----+
+"
+        );
+    }
+
+    #[test]
+    fn table_snapshot() {
+        let mut dealer = Hand::new();
+        dealer.add(Card::new(Rank::Ace, Suit::Spades));
+        dealer.add(Card::new(Rank::King, Suit::Hearts));
+
+        let mut player = User::new(0);
+        player.money = 90;
+        player.hands = vec![PlayerHand::new(10)];
+        player.hands[0]
+            .cards
+            .add(Card::new(Rank::Nine, Suit::Clubs));
+        player.hands[0]
+            .cards
+            .add(Card::new(Rank::Seven, Suit::Spades));
+
+        let mut sitting_out = User::new(1);
+        sitting_out.hands[0]
+            .cards
+            .add(Card::new(Rank::Two, Suit::Clubs));
+
+        assert_eq!(
+            table(&dealer, false, &[player, sitting_out], Some((0, 0))),
+            "\
+Dealer  Showing: 11
++-----+ +-----+
+|A    | |/////|
+|  ♠  | |/////|
+|    A| |/////|
++-----+ +-----+
+
+Player 1  Bankroll: 90  Bet: 10
+>> Hand 1  Bet: 10  Score: 16
+   +-----+ +-----+
+   |9    | |7    |
+   |  ♣  | |  ♠  |
+   |    9| |    7|
+   +-----+ +-----+
+
+Player 2  Bankroll: 100  Bet: 0
+   Sitting out
+"
+        );
+    }
+
+    #[test]
+    fn revealed_dealer_shows_every_card() {
+        let mut dealer = Hand::new();
+        dealer.add(Card::new(Rank::Ten, Suit::Spades));
+        dealer.add(Card::new(Rank::Six, Suit::Hearts));
+        dealer.add(Card::new(Rank::Nine, Suit::Diamonds));
+
+        assert_eq!(
+            table(&dealer, true, &[], None),
+            "\
+Dealer  Score: BUST
++-----+ +-----+ +-----+
+|10   | |6    | |9    |
+|  ♠  | |  ♥  | |  ♦  |
+|   10| |    6| |    9|
++-----+ +-----+ +-----+
+"
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use crate::history::Action;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::widgets::{Block, Paragraph};
+use ratatui::DefaultTerminal;
+use std::io;
+
+/// how many lines of game messages are kept on screen
+const MAX_MESSAGES: usize = 8;
+
+/// Full screen terminal front end. The table itself is drawn by `render::table`,
+/// this only lays it out next to the message log and reads keypresses.
+pub struct Tui {
+    terminal: DefaultTerminal,
+    messages: Vec<String>,
+    /// set once `q` or Esc is pressed, see `next_key`
+    quit: bool,
+}
+
+impl Tui {
+    /// switches the terminal into raw mode on the alternate screen. It is put back when the `Tui` is dropped.
+    pub fn new() -> io::Result<Tui> {
+        Ok(Tui {
+            terminal: ratatui::try_init()?,
+            messages: Vec::new(),
+            quit: false,
+        })
+    }
+
+    pub fn say(&mut self, message: &str) {
+        self.messages.extend(message.lines().map(String::from));
+        if self.messages.len() > MAX_MESSAGES {
+            self.messages.drain(..self.messages.len() - MAX_MESSAGES);
+        }
+    }
+
+    pub fn draw(&mut self, table: &str, prompt: &str) {
+        let messages = self.messages.join("\n");
+        self.terminal
+            .draw(|frame| {
+                let [table_area, message_area, prompt_area] = Layout::vertical([
+                    Constraint::Min(0),
+                    Constraint::Length(MAX_MESSAGES as u16 + 2),
+                    Constraint::Length(3),
+                ])
+                .areas(frame.area());
+                frame.render_widget(
+                    Paragraph::new(table).block(Block::bordered().title(" Blackjack ")),
+                    table_area,
+                );
+                frame.render_widget(
+                    Paragraph::new(messages).block(Block::bordered().title(" Messages ")),
+                    message_area,
+                );
+                frame.render_widget(Paragraph::new(prompt).block(Block::bordered()), prompt_area);
+            })
+            .expect("Failed to draw the table");
+    }
+
+    /// Blocks until a key is pressed. `q` or Esc quits: this and every later call give
+    /// `None` without waiting, so the rest of the round is folded or stood and gets
+    /// settled and saved like any other before the game ends.
+    fn next_key(&mut self) -> Option<KeyCode> {
+        while !self.quit {
+            if let Event::Key(key) = event::read().expect("Failed to read key") {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                if let KeyCode::Esc | KeyCode::Char('q') = key.code {
+                    self.quit = true;
+                    break;
+                }
+                return Some(key.code);
+            }
+        }
+        None
+    }
+
+    /// arrow keys move the bet up and down by 5 between 5 and `max`. Quitting folds.
+    pub fn read_bet(&mut self, table: &str, name: &str, max: isize) -> isize {
+        let mut bet = 10.min(max).max(5);
+        loop {
+            let prompt = format!(
+                "{}, bet {} chips   [↑/↓] change  [Enter] place bet  [f] fold  [q] quit",
+                name, bet
+            );
+            self.draw(table, &prompt);
+            match self.next_key() {
+                Some(KeyCode::Up | KeyCode::Right | KeyCode::Char('+')) => {
+                    bet = (bet + 5).min(max).max(5)
+                }
+                Some(KeyCode::Down | KeyCode::Left | KeyCode::Char('-')) => bet = (bet - 5).max(5),
+                Some(KeyCode::Enter) => return bet,
+                Some(KeyCode::Char('f') | KeyCode::Char('0')) | None => return 0,
+                _ => {}
+            }
+        }
+    }
+
+    /// quitting stands, so the bet on the hand is still settled
+    pub fn read_action(&mut self, table: &str, name: &str, hand: usize) -> Action {
+        let prompt = format!(
+            "{}, hand {}   [h]it  [s]tand  [d]ouble  s[p]lit  [q] quit",
+            name,
+            hand + 1
+        );
+        loop {
+            self.draw(table, &prompt);
+            match self.next_key() {
+                Some(KeyCode::Char('h')) => return Action::Hit,
+                Some(KeyCode::Char('s')) | None => return Action::Stand,
+                Some(KeyCode::Char('d')) => return Action::Double,
+                Some(KeyCode::Char('p')) => return Action::Split,
+                _ => {}
+            }
+        }
+    }
+
+    pub fn wait(&mut self, table: &str, prompt: &str) {
+        self.draw(table, prompt);
+        self.next_key();
+    }
+}
+
+impl Drop for Tui {
+    fn drop(&mut self) {
+        ratatui::restore();
+    }
+}
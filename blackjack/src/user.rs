@@ -1,15 +1,44 @@
 use crate::hand::Hand;
+use crate::profile::{Profile, Stats, STARTING_BANKROLL};
 
-pub struct User {
-    pub id: isize,
+/// One hand a player is playing. Splitting a pair gives the player another one with its own bet.
+pub struct PlayerHand {
+    pub cards: Hand,
     pub status: isize, // 0 tie, -1 busted, 1 won
     pub bet: isize,
+    pub doubled: bool,
+}
+
+impl PlayerHand {
+    pub fn new(bet: isize) -> PlayerHand {
+        PlayerHand{cards: Hand::new(), status: 0, bet, doubled: false}
+    }
+}
+
+pub struct User {
+    pub name: String,
     pub money: isize,
-    pub cards: Hand
+    pub hands: Vec<PlayerHand>,
+    /// how this player's hands went this game, added to their profile afterwards
+    pub stats: Stats,
 }
 
 impl User {
+    /// a new player named after their seat, `id` 0 being "Player 1"
     pub fn new(id: isize) -> User {
-        User{id: id, status: 0, bet: 0, money: 100, cards: Hand::new()}
+        User{name: format!("Player {}", id + 1), money: STARTING_BANKROLL, hands: vec![PlayerHand::new(0)], stats: Stats::default()}
+    }
+
+    /// sits a saved player down with the bankroll they left with last time
+    pub fn from_profile(id: isize, profile: &Profile) -> User {
+        let mut user = User::new(id);
+        user.name = profile.name.clone();
+        user.money = profile.bankroll;
+        user
     }
-}
\ No newline at end of file
+
+    /// chips riding on all of this player's hands
+    pub fn total_bet(&self) -> isize {
+        self.hands.iter().map(|h| h.bet).sum()
+    }
+}
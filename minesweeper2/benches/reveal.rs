@@ -0,0 +1,23 @@
+//! How long one click takes to open up a large, nearly empty board. Run with `cargo bench`.
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use minesweeper2::minesweeper2::MineSweeper;
+
+fn reveal(c: &mut Criterion) {
+    let mut group = c.benchmark_group("reveal");
+    group.sample_size(10);
+    for (size, mines) in [(100, 10), (1000, 100)].iter() {
+        group.bench_function(format!("{}x{} with {} mines", size, size, mines), |b| {
+            b.iter_batched(
+                || MineSweeper::with_seed(*size, *size, *mines, 1),
+                // the middle is almost certainly in the big open area
+                |mut game| game.click(size / 2, size / 2),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, reveal);
+criterion_main!(benches);
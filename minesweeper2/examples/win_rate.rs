@@ -0,0 +1,40 @@
+//! How often the solver wins on the classic board sizes. Run with
+//! `cargo run --release --example win_rate [games]`.
+
+use minesweeper2::minesweeper2::{Difficulty, GameStatus, Generation, MineSweeper};
+use minesweeper2::solver::autoplay;
+use std::time::Instant;
+
+fn main() {
+    let games: u64 = std::env::args()
+        .nth(1)
+        .and_then(|arg| arg.parse().ok())
+        .unwrap_or(1000);
+    for difficulty in Difficulty::ALL.iter() {
+        let (width, height, mines) = difficulty.size();
+        let start = Instant::now();
+        let mut won = 0;
+        for seed in 0..games {
+            let mut game = MineSweeper::with_seed_and_generation(
+                width,
+                height,
+                mines,
+                seed,
+                Generation::FirstClickSafe,
+            );
+            if autoplay(&mut game) == GameStatus::Won {
+                won += 1;
+            }
+        }
+        println!(
+            "{:<12} {:>3}x{:<3} {:>3} mines  won {:>5.1}% of {} games in {:.1?}",
+            difficulty.name(),
+            width,
+            height,
+            mines,
+            won as f64 * 100.0 / games as f64,
+            games,
+            start.elapsed()
+        );
+    }
+}
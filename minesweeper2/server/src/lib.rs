@@ -0,0 +1,13 @@
+//! A websocket server for playing minesweeper2 with other people, on the same engine as the
+//! web page and the terminal.
+//!
+//! Players meet in named rooms. A race gives everyone their own copy of one board, and co-op
+//! puts everyone on a single board. The server plays every move itself and only sends out
+//! what changed, so nobody's client ever knows where the mines are. See `protocol` for the
+//! messages.
+
+pub mod protocol;
+pub mod room;
+pub mod server;
+
+pub use server::serve;
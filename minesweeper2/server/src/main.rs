@@ -0,0 +1,24 @@
+//! `cargo run -- [address]` serves games on `address`, 127.0.0.1:9001 if left out.
+
+use tokio::net::TcpListener;
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:9001";
+
+#[tokio::main]
+async fn main() {
+    let address = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_ADDRESS.to_string());
+    let listener = match TcpListener::bind(&address).await {
+        Ok(listener) => listener,
+        Err(error) => {
+            eprintln!("Couldn't listen on {}: {}", address, error);
+            std::process::exit(1);
+        }
+    };
+    println!("Serving minesweeper on ws://{}", address);
+    if let Err(error) = minesweeper2_server::serve(listener).await {
+        eprintln!("Server error: {}", error);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,208 @@
+//! The messages sent over the websocket, one JSON object per text frame, tagged with `type`.
+//!
+//! A client starts with `create` or `join`, then sends moves. The server answers a join with
+//! `welcome` and the board as it looks so far, then sends an `update` with the spots that
+//! changed after each move. In co-op every player gets every update.
+//!
+//! Spots go over the wire as the state codes in `minesweeper2::cell`, except that covered
+//! spots never say whether they hold a mine. The mines are only sent in `game_over`.
+//!
+//! Racers all play the same mines, so a racer's spots and mines would give the others away
+//! on their own copy. A racer is only sent the spots and mines of their own board, and
+//! only a `progress` for everyone else's.
+
+use minesweeper2::cell::Cell;
+use minesweeper2::minesweeper2::GameStatus;
+use serde::{Deserialize, Serialize};
+
+/// How the players in a room share the game
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Mode {
+    /// every player gets their own copy of the same board, first to clear it wins
+    Race,
+    /// everyone plays on one board
+    Coop,
+}
+
+/// the size of a room's board
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Settings {
+    pub width: usize,
+    pub height: usize,
+    pub mines: usize,
+}
+
+/// biggest board a room can be made with, either way
+pub const MAX_SIDE: usize = 100;
+
+impl Settings {
+    /// checked before a room is made, since the engine panics on impossible boards
+    pub fn check(&self) -> Result<(), String> {
+        if self.width == 0 || self.height == 0 {
+            return Err(String::from("Cannot have a height or width of zero"));
+        }
+        if self.width > MAX_SIDE || self.height > MAX_SIDE {
+            return Err(format!("Boards are at most {0}x{0}", MAX_SIDE));
+        }
+        if self.mines >= self.width * self.height {
+            return Err(String::from("Cannot have more mines than spaces"));
+        }
+        Ok(())
+    }
+}
+
+/// where a game in the room is at
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Status {
+    Playing,
+    Won,
+    Lost,
+}
+
+impl From<GameStatus> for Status {
+    fn from(status: GameStatus) -> Status {
+        match status {
+            GameStatus::Playing => Status::Playing,
+            GameStatus::Won => Status::Won,
+            GameStatus::Lost => Status::Lost,
+        }
+    }
+}
+
+/// one spot that changed, by index into the board and its state code
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Spot {
+    pub idx: usize,
+    pub code: u8,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum ClientMessage {
+    /// makes a new room and joins it. `seed` picks the board, a random one if left out
+    Create {
+        room: String,
+        name: String,
+        mode: Mode,
+        settings: Settings,
+        #[serde(default)]
+        seed: Option<u64>,
+    },
+    Join {
+        room: String,
+        name: String,
+    },
+    Click {
+        col: usize,
+        row: usize,
+    },
+    Flag {
+        col: usize,
+        row: usize,
+    },
+    Chord {
+        col: usize,
+        row: usize,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum ServerMessage {
+    /// Sent to a player that just joined. `boards` holds every board in the room as state
+    /// codes, one per player in a race or just the one in co-op, and `players` their names.
+    /// In a race the other racers' boards are shown as they all started.
+    Welcome {
+        room: String,
+        player: usize,
+        mode: Mode,
+        settings: Settings,
+        players: Vec<String>,
+        boards: Vec<Vec<u8>>,
+    },
+    PlayerJoined {
+        player: usize,
+        name: String,
+    },
+    PlayerLeft {
+        player: usize,
+    },
+    /// `player` made a move that changed `spots` on `board`, the player's own board in a
+    /// race and always 0 in co-op
+    Update {
+        player: usize,
+        board: usize,
+        spots: Vec<Spot>,
+        status: Status,
+    },
+    /// another racer's move changed `board`: how many spots without a mine they have
+    /// uncovered, and where their game is at
+    Progress {
+        player: usize,
+        board: usize,
+        uncovered: usize,
+        status: Status,
+    },
+    /// `board` is finished, with where its mines were and how long it took from the room
+    /// being made
+    GameOver {
+        board: usize,
+        status: Status,
+        mines: Vec<usize>,
+        time_ms: u64,
+    },
+    Error {
+        message: String,
+    },
+}
+
+impl ServerMessage {
+    pub fn error(message: impl Into<String>) -> ServerMessage {
+        ServerMessage::Error {
+            message: message.into(),
+        }
+    }
+}
+
+/// The state code a player is allowed to see for a spot, covered spots all look alike
+pub fn visible_code(cell: Cell) -> u8 {
+    let seen = match cell {
+        Cell::MineCovered => Cell::NoMineCovered,
+        Cell::MineFlagged => Cell::NoMineFlagged,
+        Cell::MineQuestioned => Cell::NoMineQuestioned,
+        other => other,
+    };
+    seen.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_are_tagged_json() {
+        let message: ClientMessage =
+            serde_json::from_str(r#"{"type":"click","col":3,"row":4}"#).unwrap();
+        assert_eq!(message, ClientMessage::Click { col: 3, row: 4 });
+        let create: ClientMessage = serde_json::from_str(
+            r#"{"type":"create","room":"a","name":"b","mode":"coop","settings":{"width":9,"height":9,"mines":10}}"#,
+        )
+        .unwrap();
+        assert!(matches!(create, ClientMessage::Create { seed: None, .. }));
+        let json = serde_json::to_string(&ServerMessage::error("no")).unwrap();
+        assert_eq!(json, r#"{"type":"error","message":"no"}"#);
+    }
+
+    #[test]
+    fn covered_mines_stay_hidden() {
+        assert_eq!(
+            visible_code(Cell::MineCovered),
+            visible_code(Cell::NoMineCovered)
+        );
+        assert_eq!(visible_code(Cell::MineFlagged), 11);
+        assert_eq!(visible_code(Cell::MineExploded), 13);
+        assert_eq!(visible_code(Cell::Three), 3);
+    }
+}
@@ -0,0 +1,397 @@
+//! One game being played by the players in a room, without anything about the network.
+//!
+//! A race deals one board and opens the same spot in the middle for everyone, then every
+//! player plays a copy of it, so all of them start from the same opening on the same mines.
+//! Co-op puts everyone on one board with the mines placed on whoever clicks first.
+
+use crate::protocol::{visible_code, Mode, ServerMessage, Settings, Spot, Status};
+use minesweeper2::cell::Cell;
+use minesweeper2::minesweeper2::{GameStatus, Generation, MineSweeper};
+use std::time::Instant;
+
+/// who in the room a message from `Room::play` is for
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum To {
+    Everyone,
+    Player(usize),
+    /// everyone but this player
+    Others(usize),
+}
+
+/// a move a player can make, at a spot
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Action {
+    Click,
+    Flag,
+    Chord,
+}
+
+pub struct Room {
+    mode: Mode,
+    settings: Settings,
+    /// what every new racer starts from
+    start: MineSweeper,
+    /// one per player in a race, in the order they joined, or the shared one in co-op
+    boards: Vec<MineSweeper>,
+    /// `None` once the player has left, so the others keep their numbers
+    players: Vec<Option<String>>,
+    started: Instant,
+}
+
+impl Room {
+    /// A room with nobody in it yet. The same `seed` and settings give the same boards.
+    pub fn new(mode: Mode, settings: Settings, seed: Option<u64>) -> Result<Room, String> {
+        settings.check()?;
+        let Settings {
+            width,
+            height,
+            mines,
+        } = settings;
+        let mut start = match seed {
+            Some(seed) => MineSweeper::with_seed_and_generation(
+                width,
+                height,
+                mines,
+                seed,
+                Generation::FirstClickSafe,
+            ),
+            None => MineSweeper::with_generation(width, height, mines, Generation::FirstClickSafe),
+        };
+        let boards = match mode {
+            Mode::Race => {
+                start.click(width / 2, height / 2);
+                Vec::new()
+            }
+            Mode::Coop => vec![start.clone()],
+        };
+        Ok(Room {
+            mode,
+            settings,
+            start,
+            boards,
+            players: Vec::new(),
+            started: Instant::now(),
+        })
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// the seed the boards were dealt from
+    pub fn seed(&self) -> u64 {
+        self.start.seed()
+    }
+
+    /// Adds a player and returns their number. Racers joining late still start from the
+    /// beginning of the same board.
+    pub fn join(&mut self, name: &str) -> usize {
+        self.players.push(Some(name.to_string()));
+        if self.mode == Mode::Race {
+            self.boards.push(self.start.clone());
+        }
+        self.players.len() - 1
+    }
+
+    /// what `player` needs to draw the room when they join
+    pub fn welcome(&self, room: &str, player: usize) -> ServerMessage {
+        ServerMessage::Welcome {
+            room: room.to_string(),
+            player,
+            mode: self.mode,
+            settings: self.settings,
+            players: self
+                .players
+                .iter()
+                .map(|name| name.clone().unwrap_or_default())
+                .collect(),
+            boards: self
+                .boards
+                .iter()
+                .enumerate()
+                .map(|(board, game)| match self.mode {
+                    Mode::Race if board != player => codes(&self.start),
+                    _ => codes(game),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn leave(&mut self, player: usize) -> ServerMessage {
+        if let Some(name) = self.players.get_mut(player) {
+            *name = None;
+        }
+        ServerMessage::PlayerLeft { player }
+    }
+
+    /// true once everyone has left
+    pub fn is_empty(&self) -> bool {
+        self.players.iter().all(Option::is_none)
+    }
+
+    /// which board `player` plays on
+    pub fn board_of(&self, player: usize) -> usize {
+        match self.mode {
+            Mode::Race => player,
+            Mode::Coop => 0,
+        }
+    }
+
+    /// Plays a move for `player` and returns what the room should be sent, and who to: the
+    /// spots that changed, if any did, and the mines if that finished the board. In a race
+    /// those only go to `player`, and the other racers are sent their progress instead.
+    pub fn play(
+        &mut self,
+        player: usize,
+        action: Action,
+        col: usize,
+        row: usize,
+    ) -> Result<Vec<(To, ServerMessage)>, String> {
+        if !matches!(self.players.get(player), Some(Some(_))) {
+            return Err(format!("There is no player {} in the room", player));
+        }
+        let board = self.board_of(player);
+        let game = &mut self.boards[board];
+        if game.game_status() != GameStatus::Playing {
+            return Err(String::from("That game is already over"));
+        }
+        if col >= game.width() || row >= game.height() {
+            return Err(format!("({}, {}) is off the board", col, row));
+        }
+        let changed = match action {
+            Action::Click => game.click(col, row),
+            Action::Chord => game.chord(col, row),
+            Action::Flag if game.toggle_flag(col, row) => vec![row * game.width() + col],
+            Action::Flag => Vec::new(),
+        };
+        if changed.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let status = Status::from(game.game_status());
+        let width = game.width();
+        let spots = changed
+            .iter()
+            .filter_map(|&idx| {
+                let cell = game.cell(idx % width, idx / width)?;
+                Some(Spot {
+                    idx,
+                    code: visible_code(cell),
+                })
+            })
+            .collect();
+        let update = ServerMessage::Update {
+            player,
+            board,
+            spots,
+            status,
+        };
+        let (to, mut messages) = match self.mode {
+            Mode::Race => {
+                let uncovered = (0..width * game.height())
+                    .filter(|idx| {
+                        game.cell(idx % width, idx / width)
+                            .is_some_and(|cell| cell.number().is_some())
+                    })
+                    .count();
+                let progress = ServerMessage::Progress {
+                    player,
+                    board,
+                    uncovered,
+                    status,
+                };
+                (
+                    To::Player(player),
+                    vec![(To::Player(player), update), (To::Others(player), progress)],
+                )
+            }
+            Mode::Coop => (To::Everyone, vec![(To::Everyone, update)]),
+        };
+        if status != Status::Playing {
+            let mines = (0..width * game.height())
+                .filter(|idx| {
+                    game.cell(idx % width, idx / width)
+                        .is_some_and(Cell::is_mine)
+                })
+                .collect();
+            messages.push((
+                to,
+                ServerMessage::GameOver {
+                    board,
+                    status,
+                    mines,
+                    time_ms: self.started.elapsed().as_millis() as u64,
+                },
+            ));
+        }
+        Ok(messages)
+    }
+}
+
+/// the board as a player may see it, row by row
+fn codes(game: &MineSweeper) -> Vec<u8> {
+    (0..game.height())
+        .flat_map(|row| (0..game.width()).map(move |col| (col, row)))
+        .filter_map(|(col, row)| game.cell(col, row))
+        .map(visible_code)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SETTINGS: Settings = Settings {
+        width: 9,
+        height: 9,
+        mines: 10,
+    };
+
+    /// a spot the first click didn't open
+    fn covered(game: &MineSweeper) -> (usize, usize) {
+        let idx = (0..81)
+            .find(|idx| game.cell(idx % 9, idx / 9).unwrap().is_covered())
+            .unwrap();
+        (idx % 9, idx / 9)
+    }
+
+    fn spots(messages: &[(To, ServerMessage)]) -> Vec<Spot> {
+        match &messages[0].1 {
+            ServerMessage::Update { spots, .. } => spots.clone(),
+            other => panic!("expected an update, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn racers_get_the_same_board() {
+        let mut room = Room::new(Mode::Race, SETTINGS, Some(7)).unwrap();
+        let (a, b) = (room.join("a"), room.join("b"));
+        assert_eq!(room.boards[a].board_code(), room.boards[b].board_code());
+        assert_eq!(codes(&room.boards[a]), codes(&room.boards[b]));
+        // the middle is already open
+        assert!(room.boards[a].cell(4, 4).unwrap().number().is_some());
+
+        let again = Room::new(Mode::Race, SETTINGS, Some(7)).unwrap();
+        assert_eq!(again.start.board_code(), room.start.board_code());
+
+        let (col, row) = covered(&room.boards[a]);
+        room.play(a, Action::Flag, col, row).unwrap();
+        assert!(room.boards[a].cell(col, row).unwrap().is_flagged());
+        assert!(!room.boards[b].cell(col, row).unwrap().is_flagged());
+    }
+
+    #[test]
+    fn racers_never_see_each_others_spots_or_mines() {
+        let mut room = Room::new(Mode::Race, SETTINGS, Some(7)).unwrap();
+        let (a, b) = (room.join("a"), room.join("b"));
+        let game = &room.boards[a];
+        let mine = (0..81)
+            .find(|idx| game.cell(idx % 9, idx / 9).unwrap().is_mine())
+            .unwrap();
+        let mut safe = (0..81).filter(|idx| {
+            let cell = game.cell(idx % 9, idx / 9).unwrap();
+            cell.is_covered() && !cell.is_mine()
+        });
+        let (flag, safe) = (safe.next().unwrap(), safe.next().unwrap());
+
+        let mut messages = room.play(a, Action::Flag, flag % 9, flag / 9).unwrap();
+        messages.extend(room.play(a, Action::Click, safe % 9, safe / 9).unwrap());
+        messages.extend(room.play(a, Action::Click, mine % 9, mine / 9).unwrap());
+        let for_b: Vec<&ServerMessage> = messages
+            .iter()
+            .filter(|(to, _)| match *to {
+                To::Everyone => true,
+                To::Player(only) => only == b,
+                To::Others(but) => but != b,
+            })
+            .map(|(_, message)| message)
+            .collect();
+        assert_eq!(for_b.len(), 3);
+        for message in for_b {
+            match message {
+                ServerMessage::Progress { player, .. } => assert_eq!(*player, a),
+                other => panic!("a racer was sent {:?}", other),
+            }
+        }
+        assert!(messages.iter().any(|message| matches!(
+            message,
+            (To::Player(only), ServerMessage::GameOver { mines, .. }) if *only == a && mines.len() == 10
+        )));
+        assert_eq!(room.boards[b].game_status(), GameStatus::Playing);
+
+        // joining later doesn't show a's board either
+        let c = room.join("c");
+        match room.welcome("r", c) {
+            ServerMessage::Welcome { boards, .. } => {
+                assert_eq!(boards[a], codes(&room.start));
+                assert_ne!(codes(&room.boards[a]), codes(&room.start));
+            }
+            other => panic!("expected a welcome, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn coop_players_share_one_board() {
+        let mut room = Room::new(Mode::Coop, SETTINGS, Some(7)).unwrap();
+        let (a, b) = (room.join("a"), room.join("b"));
+        let opened = room.play(a, Action::Click, 4, 4).unwrap();
+        assert!(!spots(&opened).is_empty());
+        let (col, row) = covered(&room.boards[0]);
+        let flagged = room.play(b, Action::Flag, col, row).unwrap();
+        assert_eq!(spots(&flagged)[0].idx, row * 9 + col);
+        assert_eq!(room.boards.len(), 1);
+        match room.welcome("r", b) {
+            ServerMessage::Welcome {
+                boards, players, ..
+            } => {
+                assert_eq!(boards.len(), 1);
+                assert_eq!(boards[0][row * 9 + col], 11);
+                assert_eq!(players, vec!["a", "b"]);
+            }
+            other => panic!("expected a welcome, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn hitting_a_mine_ends_the_board() {
+        let mut room = Room::new(Mode::Coop, SETTINGS, Some(7)).unwrap();
+        let player = room.join("a");
+        room.play(player, Action::Click, 4, 4).unwrap();
+        let game = &room.boards[0];
+        let mine = (0..81)
+            .find(|idx| game.cell(idx % 9, idx / 9).unwrap().is_mine())
+            .unwrap();
+
+        let messages = room
+            .play(player, Action::Click, mine % 9, mine / 9)
+            .unwrap();
+        match &messages[1] {
+            (To::Everyone, ServerMessage::GameOver { status, mines, .. }) => {
+                assert_eq!(*status, Status::Lost);
+                assert_eq!(mines.len(), 10);
+            }
+            other => panic!("expected the game to end, got {:?}", other),
+        }
+        assert!(room.play(player, Action::Click, 0, 0).is_err());
+    }
+
+    #[test]
+    fn bad_moves_and_rooms() {
+        assert!(Room::new(
+            Mode::Race,
+            Settings {
+                mines: 81,
+                ..SETTINGS
+            },
+            None
+        )
+        .is_err());
+        let mut room = Room::new(Mode::Race, SETTINGS, None).unwrap();
+        assert!(room.play(0, Action::Click, 0, 0).is_err());
+        let player = room.join("a");
+        assert!(room.play(player, Action::Click, 9, 0).is_err());
+        room.leave(player);
+        assert!(room.is_empty());
+        assert!(room.play(player, Action::Click, 0, 0).is_err());
+    }
+}
@@ -0,0 +1,189 @@
+//! The websocket side: accepts connections, keeps the rooms, and passes moves to them.
+//!
+//! Every connection gets a channel that the room's messages are sent down, and a task that
+//! writes whatever comes out of it to the socket, so a slow player never holds up the rooms.
+
+use crate::protocol::{ClientMessage, ServerMessage};
+use crate::room::{Action, Room, To};
+use futures_util::{SinkExt, StreamExt};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use tokio_tungstenite::tungstenite::Message;
+
+type Sender = UnboundedSender<ServerMessage>;
+
+struct Entry {
+    room: Room,
+    /// by player number, `None` once they've left
+    senders: Vec<Option<Sender>>,
+}
+
+impl Entry {
+    fn broadcast(&self, message: &ServerMessage) {
+        self.send(To::Everyone, message);
+    }
+
+    fn send(&self, to: To, message: &ServerMessage) {
+        for (player, sender) in self.senders.iter().enumerate() {
+            let wanted = match to {
+                To::Everyone => true,
+                To::Player(only) => player == only,
+                To::Others(but) => player != but,
+            };
+            if let (true, Some(sender)) = (wanted, sender) {
+                // a closed channel means that player is on the way out
+                let _ = sender.send(message.clone());
+            }
+        }
+    }
+}
+
+/// Every room on the server, by name
+#[derive(Default)]
+struct Hub {
+    rooms: HashMap<String, Entry>,
+}
+
+/// the room and player number a connection plays as, once it has joined
+type Seat = Option<(String, usize)>;
+
+impl Hub {
+    /// Handles one message from a connection, sending the answers down the channels.
+    /// An error goes back to the connection that sent the message only.
+    fn handle(
+        &mut self,
+        seat: &mut Seat,
+        message: ClientMessage,
+        sender: &Sender,
+    ) -> Result<(), String> {
+        let (action, col, row) = match message {
+            ClientMessage::Create {
+                room,
+                name,
+                mode,
+                settings,
+                seed,
+            } => {
+                if seat.is_some() {
+                    return Err(String::from("Already in a room"));
+                }
+                if self.rooms.contains_key(&room) {
+                    return Err(format!("There is already a room called {}", room));
+                }
+                let entry = Entry {
+                    room: Room::new(mode, settings, seed)?,
+                    senders: Vec::new(),
+                };
+                self.rooms.insert(room.clone(), entry);
+                return self.join(seat, room, &name, sender);
+            }
+            ClientMessage::Join { room, name } => {
+                if seat.is_some() {
+                    return Err(String::from("Already in a room"));
+                }
+                return self.join(seat, room, &name, sender);
+            }
+            ClientMessage::Click { col, row } => (Action::Click, col, row),
+            ClientMessage::Flag { col, row } => (Action::Flag, col, row),
+            ClientMessage::Chord { col, row } => (Action::Chord, col, row),
+        };
+        let (room, player) = seat.as_ref().ok_or("Join a room first")?;
+        let entry = self
+            .rooms
+            .get_mut(room)
+            .ok_or_else(|| format!("There is no room called {}", room))?;
+        for (to, message) in entry.room.play(*player, action, col, row)? {
+            entry.send(to, &message);
+        }
+        Ok(())
+    }
+
+    fn join(
+        &mut self,
+        seat: &mut Seat,
+        room: String,
+        name: &str,
+        sender: &Sender,
+    ) -> Result<(), String> {
+        let entry = self
+            .rooms
+            .get_mut(&room)
+            .ok_or_else(|| format!("There is no room called {}", room))?;
+        let player = entry.room.join(name);
+        entry.broadcast(&ServerMessage::PlayerJoined {
+            player,
+            name: name.to_string(),
+        });
+        entry.senders.push(Some(sender.clone()));
+        let _ = sender.send(entry.room.welcome(&room, player));
+        *seat = Some((room, player));
+        Ok(())
+    }
+
+    /// takes the player out of their room, and the room off the server once it's empty
+    fn leave(&mut self, seat: Seat) {
+        let (room, player) = match seat {
+            Some(seat) => seat,
+            None => return,
+        };
+        if let Some(entry) = self.rooms.get_mut(&room) {
+            entry.senders[player] = None;
+            let left = entry.room.leave(player);
+            entry.broadcast(&left);
+            if entry.room.is_empty() {
+                self.rooms.remove(&room);
+            }
+        }
+    }
+}
+
+/// Serves games to every connection on `listener` until accepting fails
+pub async fn serve(listener: TcpListener) -> std::io::Result<()> {
+    let hub = Arc::new(Mutex::new(Hub::default()));
+    loop {
+        let (stream, _) = listener.accept().await?;
+        tokio::spawn(connection(stream, hub.clone()));
+    }
+}
+
+async fn connection(stream: TcpStream, hub: Arc<Mutex<Hub>>) {
+    let socket = match tokio_tungstenite::accept_async(stream).await {
+        Ok(socket) => socket,
+        // not a websocket handshake, nothing to play with
+        Err(_) => return,
+    };
+    let (mut outgoing, mut incoming) = socket.split();
+    let (sender, mut receiver) = unbounded_channel::<ServerMessage>();
+    let writer = tokio::spawn(async move {
+        while let Some(message) = receiver.recv().await {
+            let text = serde_json::to_string(&message).expect("messages always serialize");
+            if outgoing.send(Message::Text(text)).await.is_err() {
+                break;
+            }
+        }
+        let _ = outgoing.close().await;
+    });
+
+    let mut seat: Seat = None;
+    while let Some(Ok(frame)) = incoming.next().await {
+        let text = match frame {
+            Message::Text(text) => text,
+            Message::Close(_) => break,
+            _ => continue,
+        };
+        let handled = match serde_json::from_str::<ClientMessage>(&text) {
+            Ok(message) => hub.lock().unwrap().handle(&mut seat, message, &sender),
+            Err(error) => Err(format!("Not a message the server knows: {}", error)),
+        };
+        if let Err(message) = handled {
+            let _ = sender.send(ServerMessage::error(message));
+        }
+    }
+
+    hub.lock().unwrap().leave(seat);
+    // the writer stops once the last sender for this connection is gone
+    drop(sender);
+    let _ = writer.await;
+}
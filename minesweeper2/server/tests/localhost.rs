@@ -0,0 +1,209 @@
+//! Whole games against a real server on localhost, with clients speaking the protocol over
+//! websockets the way a browser would.
+
+use futures_util::{SinkExt, StreamExt};
+use minesweeper2_server::protocol::{ClientMessage, Mode, ServerMessage, Settings, Status};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+const SETTINGS: Settings = Settings {
+    width: 9,
+    height: 9,
+    mines: 10,
+};
+
+/// a server on a free port, and its address
+async fn start() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = format!("ws://{}", listener.local_addr().unwrap());
+    tokio::spawn(minesweeper2_server::serve(listener));
+    address
+}
+
+async fn connect(address: &str) -> Socket {
+    tokio_tungstenite::connect_async(address).await.unwrap().0
+}
+
+async fn send(socket: &mut Socket, message: ClientMessage) {
+    let text = serde_json::to_string(&message).unwrap();
+    socket.send(Message::Text(text)).await.unwrap();
+}
+
+async fn receive(socket: &mut Socket) -> ServerMessage {
+    loop {
+        let frame = tokio::time::timeout(std::time::Duration::from_secs(5), socket.next())
+            .await
+            .expect("the server went quiet")
+            .unwrap()
+            .unwrap();
+        if let Message::Text(text) = frame {
+            return serde_json::from_str(&text).unwrap();
+        }
+    }
+}
+
+fn create(mode: Mode) -> ClientMessage {
+    ClientMessage::Create {
+        room: String::from("room"),
+        name: String::from("host"),
+        mode,
+        settings: SETTINGS,
+        seed: Some(42),
+    }
+}
+
+fn join() -> ClientMessage {
+    ClientMessage::Join {
+        room: String::from("room"),
+        name: String::from("guest"),
+    }
+}
+
+/// the boards in a welcome
+fn boards(message: ServerMessage) -> Vec<Vec<u8>> {
+    match message {
+        ServerMessage::Welcome { boards, .. } => boards,
+        other => panic!("expected a welcome, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn racers_play_their_own_copy_of_one_board() {
+    let address = start().await;
+    let mut host = connect(&address).await;
+    let mut guest = connect(&address).await;
+
+    send(&mut host, create(Mode::Race)).await;
+    let hosted = boards(receive(&mut host).await);
+    send(&mut guest, join()).await;
+    let joined = boards(receive(&mut guest).await);
+    assert_eq!(joined.len(), 2);
+    assert_eq!(joined[0], hosted[0]);
+    assert_eq!(joined[1], hosted[0]);
+    assert_eq!(
+        receive(&mut host).await,
+        ServerMessage::PlayerJoined {
+            player: 1,
+            name: String::from("guest")
+        }
+    );
+
+    // covered spots never give the mines away
+    let board = &joined[1];
+    let covered = board.iter().position(|code| *code == 9).unwrap();
+    assert!(board.iter().all(|code| *code != 10));
+    send(
+        &mut guest,
+        ClientMessage::Flag {
+            col: covered % 9,
+            row: covered / 9,
+        },
+    )
+    .await;
+    match receive(&mut guest).await {
+        ServerMessage::Update {
+            player,
+            board,
+            spots,
+            status,
+        } => {
+            assert_eq!((player, board), (1, 1));
+            assert_eq!(spots.len(), 1);
+            assert_eq!(spots[0].idx, covered);
+            assert_eq!(spots[0].code, 11);
+            assert_eq!(status, Status::Playing);
+        }
+        other => panic!("expected an update, got {:?}", other),
+    }
+    // the other racer only hears how far along the guest is, not which spots
+    let opened = board.iter().filter(|code| **code <= 8).count();
+    assert_eq!(
+        receive(&mut host).await,
+        ServerMessage::Progress {
+            player: 1,
+            board: 1,
+            uncovered: opened,
+            status: Status::Playing,
+        }
+    );
+}
+
+#[tokio::test]
+async fn coop_players_see_each_others_moves() {
+    let address = start().await;
+    let mut host = connect(&address).await;
+    let mut guest = connect(&address).await;
+
+    send(&mut host, create(Mode::Coop)).await;
+    receive(&mut host).await;
+    send(&mut guest, join()).await;
+    assert_eq!(boards(receive(&mut guest).await).len(), 1);
+    receive(&mut host).await;
+
+    send(&mut guest, ClientMessage::Click { col: 4, row: 4 }).await;
+    let mut seen = Vec::new();
+    for socket in [&mut host, &mut guest] {
+        match receive(socket).await {
+            ServerMessage::Update {
+                player,
+                board,
+                spots,
+                ..
+            } => {
+                assert_eq!((player, board), (1, 0));
+                assert!(!spots.is_empty());
+                seen.push(spots);
+            }
+            other => panic!("expected an update, got {:?}", other),
+        }
+    }
+    assert_eq!(seen[0], seen[1]);
+
+    drop(guest);
+    assert_eq!(
+        receive(&mut host).await,
+        ServerMessage::PlayerLeft { player: 1 }
+    );
+}
+
+#[tokio::test]
+async fn mistakes_only_go_back_to_the_sender() {
+    let address = start().await;
+    let mut host = connect(&address).await;
+    let mut stranger = connect(&address).await;
+
+    send(&mut stranger, ClientMessage::Click { col: 0, row: 0 }).await;
+    assert!(matches!(
+        receive(&mut stranger).await,
+        ServerMessage::Error { .. }
+    ));
+    stranger
+        .send(Message::Text(String::from("{\"type\":\"dance\"}")))
+        .await
+        .unwrap();
+    assert!(matches!(
+        receive(&mut stranger).await,
+        ServerMessage::Error { .. }
+    ));
+    send(&mut stranger, join()).await;
+    assert!(matches!(
+        receive(&mut stranger).await,
+        ServerMessage::Error { .. }
+    ));
+
+    send(&mut host, create(Mode::Race)).await;
+    receive(&mut host).await;
+    send(&mut stranger, create(Mode::Coop)).await;
+    assert!(matches!(
+        receive(&mut stranger).await,
+        ServerMessage::Error { .. }
+    ));
+    send(&mut host, ClientMessage::Click { col: 40, row: 0 }).await;
+    assert!(matches!(
+        receive(&mut host).await,
+        ServerMessage::Error { .. }
+    ));
+}
@@ -0,0 +1,75 @@
+//! Minesweeper in the terminal, on the same engine as the web page.
+//!
+//! `cargo run --bin minesweeper -- [beginner|intermediate|expert] [--ascii]`
+//!
+//! `--ascii` draws with plain characters for terminals that can't show emoji.
+//! `cargo run --bin minesweeper -- stats` lists the win rates and best times so far.
+
+#[cfg(not(target_arch = "wasm32"))]
+mod terminal;
+
+use minesweeper2::minesweeper2::Difficulty;
+
+/// the difficulty and whether to draw in ASCII, from the command line arguments
+fn parse_args<I: Iterator<Item = String>>(args: I) -> Result<(Difficulty, bool), String> {
+    let mut difficulty = Difficulty::Beginner;
+    let mut ascii = false;
+    for arg in args {
+        if arg == "--ascii" {
+            ascii = true;
+        } else {
+            difficulty = arg.parse()?;
+        }
+    }
+    Ok((difficulty, ascii))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    if std::env::args().nth(1).as_deref() == Some("stats") {
+        let path = std::path::Path::new(terminal::STATS_FILE);
+        match minesweeper2::stats::Stats::load_from_file(path) {
+            Ok(stats) => print!("{}", terminal::report(&stats)),
+            Err(message) => {
+                eprintln!("{}", message);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    let (difficulty, ascii) = match parse_args(std::env::args().skip(1)) {
+        Ok(parsed) => parsed,
+        Err(message) => {
+            eprintln!("{}", message);
+            eprintln!("usage: minesweeper [beginner|intermediate|expert] [--ascii] | stats");
+            std::process::exit(2);
+        }
+    };
+    if let Err(error) = terminal::run(difficulty, ascii) {
+        eprintln!("Terminal error: {}", error);
+        std::process::exit(1);
+    }
+}
+
+// the browser has its own front end in www/
+#[cfg(target_arch = "wasm32")]
+fn main() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<(Difficulty, bool), String> {
+        parse_args(args.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn reads_arguments() {
+        assert_eq!(parse(&[]), Ok((Difficulty::Beginner, false)));
+        assert_eq!(
+            parse(&["Expert", "--ascii"]),
+            Ok((Difficulty::Expert, true))
+        );
+        assert!(parse(&["impossible"]).is_err());
+    }
+}
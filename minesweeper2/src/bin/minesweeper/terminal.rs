@@ -0,0 +1,442 @@
+//! Drawing the board and reading keys with crossterm
+
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use minesweeper2::cell::Cell;
+use minesweeper2::minesweeper2::{Difficulty, GameStatus, MineSweeper};
+use minesweeper2::save::SavedGame;
+use minesweeper2::stats::{three_bv, GameRecord, Stats};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// how often the timer redraws while waiting for a key
+const TICK: Duration = Duration::from_millis(250);
+
+const HELP: &str = "arrows/wasd move  space open  f flag  c chord  u undo  n new  1-3 difficulty  \
+                    S save  L load  q quit";
+
+/// where S and L keep the saved game, in the directory the game was started from
+const SAVE_FILE: &str = "minesweeper-save.json";
+
+/// where finished games are recorded, next to the saved game
+pub const STATS_FILE: &str = "minesweeper-stats.json";
+
+/// One game plus everything the terminal shows around it
+struct Game {
+    board: MineSweeper,
+    difficulty: Difficulty,
+    ascii: bool,
+    col: usize,
+    row: usize,
+    /// set on the first move that changes the board
+    started: Option<Instant>,
+    /// how long the game took, once it is over
+    finished: Option<Duration>,
+    /// the outcome of saving or loading, shown until the next key
+    message: Option<String>,
+    /// every finished game, kept across new games
+    stats: Stats,
+    /// where to write `stats` after each game, if anywhere
+    stats_path: Option<PathBuf>,
+    /// the game is in `stats`, so undoing a loss doesn't get a second go at the best times
+    recorded: bool,
+}
+
+impl Game {
+    fn new(difficulty: Difficulty, ascii: bool) -> Game {
+        let board = MineSweeper::with_difficulty(difficulty);
+        Game {
+            col: board.width() / 2,
+            row: board.height() / 2,
+            board,
+            difficulty,
+            ascii,
+            started: None,
+            finished: None,
+            message: None,
+            stats: Stats::new(),
+            stats_path: None,
+            recorded: false,
+        }
+    }
+
+    /// A fresh game, keeping the stats
+    fn restart(&mut self, difficulty: Difficulty) {
+        *self = Game {
+            stats: std::mem::take(&mut self.stats),
+            stats_path: self.stats_path.take(),
+            ..Game::new(difficulty, self.ascii)
+        };
+    }
+
+    /// Picks a saved game back up, with the clock where it was left
+    fn from_saved(saved: &SavedGame, ascii: bool) -> Game {
+        let board = saved.game();
+        let size = (board.width(), board.height(), board.get_total_bombs());
+        let difficulty = Difficulty::ALL
+            .iter()
+            .copied()
+            .find(|d| d.size() == size)
+            .unwrap_or(Difficulty::Beginner);
+        let elapsed = Duration::from_millis(saved.elapsed_ms());
+        let mut game = Game::new(difficulty, ascii);
+        game.board = board;
+        if elapsed > Duration::from_secs(0) {
+            game.started = Instant::now().checked_sub(elapsed);
+        }
+        game.after_move(false);
+        game
+    }
+
+    fn save(&mut self, path: &Path) {
+        let saved = SavedGame::new(&self.board, self.elapsed().as_millis() as u64);
+        self.message = Some(match saved.save_to_file(path) {
+            Ok(()) => format!("Saved to {}", path.display()),
+            Err(message) => message,
+        });
+    }
+
+    fn load(&mut self, path: &Path) {
+        match SavedGame::load_from_file(path) {
+            Ok(saved) => {
+                *self = Game {
+                    stats: std::mem::take(&mut self.stats),
+                    stats_path: self.stats_path.take(),
+                    ..Game::from_saved(&saved, self.ascii)
+                };
+                self.message = Some(format!("Loaded {}", path.display()));
+            }
+            Err(message) => self.message = Some(message),
+        }
+    }
+
+    fn elapsed(&self) -> Duration {
+        match (self.finished, self.started) {
+            (Some(took), _) => took,
+            (None, Some(start)) => start.elapsed(),
+            (None, None) => Duration::from_secs(0),
+        }
+    }
+
+    /// mines minus flags, negative when there are too many flags
+    fn mines_left(&self) -> isize {
+        self.board.get_total_bombs() as isize - self.board.get_flag_count() as isize
+    }
+
+    fn move_cursor(&mut self, cols: isize, rows: isize) {
+        let col = self.col as isize + cols;
+        let row = self.row as isize + rows;
+        self.col = col.clamp(0, self.board.width() as isize - 1) as usize;
+        self.row = row.clamp(0, self.board.height() as isize - 1) as usize;
+    }
+
+    /// starts the timer on the first move and stops it when the game ends
+    fn after_move(&mut self, changed: bool) {
+        if changed && self.started.is_none() {
+            self.started = Some(Instant::now());
+        }
+        if self.finished.is_none() && self.board.game_status() != GameStatus::Playing {
+            self.finished = Some(self.elapsed());
+            self.record();
+        }
+    }
+
+    /// Adds the game that just ended to the stats and writes them out
+    fn record(&mut self) {
+        if self.recorded {
+            return;
+        }
+        let took = self.elapsed().as_millis() as u64;
+        if let Ok(record) = GameRecord::from_game(&self.board, took) {
+            self.stats.record(record);
+            self.recorded = true;
+        }
+        if let Some(path) = &self.stats_path {
+            if let Err(message) = self.stats.save_to_file(path) {
+                self.message = Some(message);
+            }
+        }
+    }
+
+    /// the fastest win on this board, in seconds
+    fn best_time(&self) -> Option<f64> {
+        let (width, height) = (self.board.width(), self.board.height());
+        self.stats
+            .best_time_ms(width, height, self.board.get_total_bombs())
+            .map(|ms| ms as f64 / 1000.0)
+    }
+
+    /// Acts on one key press. Returns false when the player quits.
+    fn handle(&mut self, key: KeyCode) -> bool {
+        let (col, row) = (self.col, self.row);
+        self.message = None;
+        match key {
+            KeyCode::Left | KeyCode::Char('a') => self.move_cursor(-1, 0),
+            KeyCode::Right | KeyCode::Char('d') => self.move_cursor(1, 0),
+            KeyCode::Up | KeyCode::Char('w') => self.move_cursor(0, -1),
+            KeyCode::Down | KeyCode::Char('s') => self.move_cursor(0, 1),
+            KeyCode::Char(' ') | KeyCode::Enter => {
+                let changed = !self.board.click(col, row).is_empty();
+                self.after_move(changed);
+            }
+            KeyCode::Char('f') => {
+                let changed = self.board.toggle_flag(col, row);
+                self.after_move(changed);
+            }
+            KeyCode::Char('c') => {
+                let changed = !self.board.chord(col, row).is_empty();
+                self.after_move(changed);
+            }
+            KeyCode::Char('u') => {
+                let undone = !self.board.undo().is_empty();
+                // playing on after an undo keeps the clock going
+                if undone {
+                    self.finished = None;
+                }
+            }
+            KeyCode::Char('n') => self.restart(self.difficulty),
+            KeyCode::Char(digit @ '1'..='3') => {
+                self.restart(Difficulty::ALL[digit as usize - '1' as usize]);
+            }
+            KeyCode::Char('S') => self.save(Path::new(SAVE_FILE)),
+            KeyCode::Char('L') => self.load(Path::new(SAVE_FILE)),
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            _ => {}
+        }
+        true
+    }
+
+    fn status_line(&self) -> String {
+        let face = match self.board.game_status() {
+            GameStatus::Playing => "",
+            GameStatus::Won => "  You won!",
+            GameStatus::Lost => "  Boom! u to undo, n for a new game",
+        };
+        let mut line = format!(
+            "{}  Mines: {:>3}  Time: {:>3}",
+            self.difficulty.name(),
+            self.mines_left(),
+            self.elapsed().as_secs(),
+        );
+        if let Some(best) = self.best_time() {
+            line.push_str(&format!("  Best: {:.1}", best));
+        }
+        if let (Some(took), true) = (self.finished, self.recorded) {
+            let bv = three_bv(&self.board);
+            let rate = bv as f64 / took.as_secs_f64().max(0.001);
+            line.push_str(&format!("  3BV: {} ({:.2}/s)", bv, rate));
+        }
+        line.push_str(face);
+        match &self.message {
+            Some(message) => format!("{}  {}", line, message),
+            None => line,
+        }
+    }
+
+    fn draw<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        queue!(out, MoveTo(0, 0), Clear(ClearType::All))?;
+        queue!(out, Print(self.status_line()))?;
+        for row in 0..self.board.height() {
+            for col in 0..self.board.width() {
+                let cell = match self.board.cell(col, row) {
+                    Some(cell) => cell,
+                    None => continue,
+                };
+                // every spot is two columns wide so emoji and digits line up
+                queue!(out, MoveTo(col as u16 * 2, row as u16 + 2))?;
+                if (col, row) == (self.col, self.row) {
+                    queue!(out, SetBackgroundColor(Color::DarkGrey))?;
+                }
+                if let Some(color) = number_color(cell) {
+                    queue!(out, SetForegroundColor(color))?;
+                }
+                let symbol = cell.symbol(self.ascii);
+                if self.ascii || number_color(cell).is_some() {
+                    queue!(out, Print(symbol), Print(' '))?;
+                } else {
+                    queue!(out, Print(symbol))?;
+                }
+                queue!(out, ResetColor)?;
+            }
+        }
+        queue!(out, MoveTo(0, self.board.height() as u16 + 3), Print(HELP))?;
+        out.flush()
+    }
+}
+
+/// the classic colors for each number
+fn number_color(cell: Cell) -> Option<Color> {
+    match cell {
+        Cell::One => Some(Color::Blue),
+        Cell::Two => Some(Color::Green),
+        Cell::Three => Some(Color::Red),
+        Cell::Four => Some(Color::DarkBlue),
+        Cell::Five => Some(Color::DarkRed),
+        Cell::Six => Some(Color::Cyan),
+        Cell::Seven => Some(Color::Magenta),
+        Cell::Eight => Some(Color::Grey),
+        _ => None,
+    }
+}
+
+/// Every board played with games won, best time, best 3BV per second and win rate, one per line
+pub fn report(stats: &Stats) -> String {
+    let mut report = String::new();
+    for (width, height, mines) in stats.boards() {
+        let name = Difficulty::ALL
+            .iter()
+            .find(|d| d.size() == (width, height, mines))
+            .map(|d| d.name().to_string())
+            .unwrap_or_else(|| format!("{}x{} {} mines", width, height, mines));
+        let best = match stats.best_time_ms(width, height, mines) {
+            Some(ms) => format!("{:.1}s", ms as f64 / 1000.0),
+            None => String::from("-"),
+        };
+        report.push_str(&format!(
+            "{:<16} won {:>3} of {:>3} ({:>3.0}%)  best {:>7}  best 3BV/s {:.2}\n",
+            name,
+            stats.won(width, height, mines),
+            stats.played(width, height, mines),
+            stats.win_rate(width, height, mines) * 100.0,
+            best,
+            stats.best_three_bv_per_second(width, height, mines),
+        ));
+    }
+    if report.is_empty() {
+        report.push_str("No games finished yet\n");
+    }
+    report
+}
+
+/// Plays until the player quits, putting the terminal back how it was even on errors.
+/// Finished games are added to the stats in `STATS_FILE`.
+pub fn run(difficulty: Difficulty, ascii: bool) -> io::Result<()> {
+    let stats_path = PathBuf::from(STATS_FILE);
+    let mut game = Game::new(difficulty, ascii);
+    match Stats::load_from_file(&stats_path) {
+        Ok(stats) => {
+            game.stats = stats;
+            game.stats_path = Some(stats_path);
+        }
+        // don't write over stats that couldn't be read
+        Err(message) => game.message = Some(message),
+    }
+    let mut out = io::stdout();
+    terminal::enable_raw_mode()?;
+    execute!(out, EnterAlternateScreen, Hide)?;
+    let result = play(&mut game, &mut out);
+    execute!(out, Show, LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+    result
+}
+
+fn play<W: Write>(game: &mut Game, out: &mut W) -> io::Result<()> {
+    loop {
+        game.draw(out)?;
+        // wake up now and then so the timer keeps counting
+        if !event::poll(TICK)? {
+            continue;
+        }
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press && !game.handle(key.code) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_stays_on_the_board() {
+        let mut game = Game::new(Difficulty::Beginner, true);
+        for _ in 0..20 {
+            game.handle(KeyCode::Left);
+            game.handle(KeyCode::Char('w'));
+        }
+        assert_eq!((game.col, game.row), (0, 0));
+        for _ in 0..20 {
+            game.handle(KeyCode::Char('d'));
+        }
+        assert_eq!(game.col, 8);
+    }
+
+    #[test]
+    fn counter_and_timer_follow_the_game() {
+        let mut game = Game::new(Difficulty::Beginner, true);
+        assert_eq!(game.elapsed(), Duration::from_secs(0));
+        game.handle(KeyCode::Char(' '));
+        assert!(game.started.is_some());
+        game.handle(KeyCode::Char('3'));
+        assert_eq!(game.difficulty, Difficulty::Expert);
+        assert_eq!(game.mines_left(), 99);
+        assert!(game.started.is_none());
+    }
+
+    #[test]
+    fn quits_on_q() {
+        let mut game = Game::new(Difficulty::Beginner, false);
+        assert!(game.handle(KeyCode::Char('f')));
+        assert_eq!(game.mines_left(), 9);
+        assert!(!game.handle(KeyCode::Char('q')));
+    }
+
+    #[test]
+    fn saves_and_loads_from_a_file() {
+        let path =
+            std::env::temp_dir().join(format!("minesweeper-test-{}.json", std::process::id()));
+        let mut game = Game::new(Difficulty::Intermediate, true);
+        game.handle(KeyCode::Char(' '));
+        game.save(&path);
+        let mut loaded = Game::new(Difficulty::Beginner, true);
+        loaded.load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.difficulty, Difficulty::Intermediate);
+        assert_eq!(loaded.board.to_string(), game.board.to_string());
+        assert!(loaded.message.unwrap().starts_with("Loaded"));
+
+        loaded = Game::new(Difficulty::Beginner, true);
+        loaded.load(&path);
+        assert!(!loaded.message.unwrap().starts_with("Loaded"));
+    }
+
+    #[test]
+    fn finished_games_go_in_the_stats() {
+        let mut game = Game::new(Difficulty::Beginner, true);
+        game.board = MineSweeper::from_board_code("4x1-I").unwrap();
+        (game.col, game.row) = (0, 0);
+        game.handle(KeyCode::Char(' '));
+        game.col = 3;
+        game.handle(KeyCode::Char(' '));
+        assert_eq!(game.stats.won(4, 1, 1), 1);
+        assert!(game.status_line().contains("Best:"));
+        assert!(game.status_line().contains("3BV: 2"));
+        // undoing and winning again doesn't count twice
+        game.handle(KeyCode::Char('u'));
+        game.handle(KeyCode::Char(' '));
+        assert_eq!(game.stats.played(4, 1, 1), 1);
+
+        game.handle(KeyCode::Char('n'));
+        assert_eq!(game.stats.played(4, 1, 1), 1);
+        let report_line = report(&game.stats);
+        assert!(report_line.starts_with("4x1 1 mines"));
+        assert!(report_line.contains("won   1 of   1 (100%)"));
+        assert_eq!(report(&Stats::new()), "No games finished yet\n");
+    }
+
+    #[test]
+    fn draws_in_ascii() {
+        let game = Game::new(Difficulty::Beginner, true);
+        let mut screen = Vec::new();
+        game.draw(&mut screen).unwrap();
+        let screen = String::from_utf8(screen).unwrap();
+        assert!(screen.contains("Mines:  10"));
+        assert!(screen.contains("# "));
+    }
+}
@@ -0,0 +1,69 @@
+//! Short codes for sharing a board, so everyone can play the same daily challenge.
+//!
+//! A code looks like `9x9-AIAAIQAAgIAIAA`: the width and height, then where the mines are as
+//! one bit per spot (row by row, first spot in the highest bit) written in url safe base64.
+//! Only the mines are kept, not the progress of a game.
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+pub fn encode(width: usize, height: usize, mines: &[bool]) -> String {
+    let mut code = format!("{}x{}-", width, height);
+    for chunk in mines.chunks(6) {
+        let mut value = 0;
+        for (i, mine) in chunk.iter().enumerate() {
+            if *mine {
+                value |= 1 << (5 - i);
+            }
+        }
+        code.push(BASE64[value] as char);
+    }
+    code
+}
+
+/// the width, height and mine layout from a code made by `encode`
+pub fn decode(code: &str) -> Result<(usize, usize, Vec<bool>), String> {
+    let bad = || format!("{:?} is not a board code", code);
+    let (size, bits) = code.trim().split_once('-').ok_or_else(bad)?;
+    let (width, height) = size.split_once('x').ok_or_else(bad)?;
+    let width: usize = width.parse().map_err(|_| bad())?;
+    let height: usize = height.parse().map_err(|_| bad())?;
+    let spots = width.checked_mul(height).ok_or_else(bad)?;
+    if spots == 0 || bits.len() != spots.div_ceil(6) {
+        return Err(bad());
+    }
+
+    let mut mines = Vec::with_capacity(bits.len() * 6);
+    for c in bits.bytes() {
+        let value = BASE64.iter().position(|b| *b == c).ok_or_else(bad)?;
+        mines.extend((0..6).rev().map(|i| value >> i & 1 == 1));
+    }
+    // the last character can have unused bits, which have to be zero
+    if mines.drain(spots..).any(|mine| mine) {
+        return Err(bad());
+    }
+    Ok((width, height, mines))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips() {
+        let mines: Vec<bool> = (0..81).map(|i| i % 7 == 0).collect();
+        let code = encode(9, 9, &mines);
+        assert_eq!(code.len(), "9x9-".len() + 14);
+        assert_eq!(decode(&code), Ok((9, 9, mines)));
+    }
+
+    #[test]
+    fn rejects_bad_codes() {
+        assert!(decode("9x9").is_err());
+        assert!(decode("9x9-AAA").is_err());
+        assert!(decode("0x9-").is_err());
+        assert!(decode("2x2-*").is_err());
+        // 4 spots only use the top 4 bits of the character
+        assert!(decode("2x2-B").is_err());
+        assert_eq!(decode("2x2-I"), Ok((2, 2, vec![false, false, true, false])));
+    }
+}
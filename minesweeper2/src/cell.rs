@@ -0,0 +1,225 @@
+//! What each spot on the board is, as the game sees it and as the front end reads it.
+//!
+//! The game keeps the board as one `Cell` per spot, row by row, so spot `(col, row)` is at
+//! `row * width + col`. `Cell` is one byte with the wire codes below as its values, which lets
+//! `MineSweeper::state` hand the front end a pointer straight into the board to read
+//! `width * height` bytes from, the same codes a saved game stores.
+//!
+//! ## State Codes
+//! - 0 - Uncovered with no neighbors
+//! - 1 - Uncovered with 1 mine neighbor
+//! - 2 - Uncovered with 2 mine neighbors
+//! - 3 - Uncovered with 3 mine neighbors
+//! - 4 - Uncovered with 4 mine neighbors
+//! - 5 - Uncovered with 5 mine neighbors
+//! - 6 - Uncovered with 6 mine neighbors
+//! - 7 - Uncovered with 7 mine neighbors
+//! - 8 - Uncovered with 8 mine neighbors
+//! - 9 - Covered without a mine
+//! - 10 - Covered with a mine
+//! - 11 - Flagged without a mine
+//! - 12 - Flagged with a mine
+//! - 13 - An uncovered bomb aka Game Over
+//! - 14 - Question mark without a mine
+//! - 15 - Question mark with a mine
+//!
+//! ## Layout versions
+//! Front ends can check `MineSweeper::state_layout_version` before reading the buffer.
+//! Codes are only ever added, so a front end built for an older version can still read
+//! boards that don't use the newer codes.
+//! - 1 - codes 0 to 13
+//! - 2 - adds the question marks, 14 and 15
+
+use std::convert::TryFrom;
+use std::fmt;
+
+/// the layout of the state buffer described above
+pub const STATE_LAYOUT_VERSION: u32 = 2;
+
+/// One spot on the board, stored as its state code
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Cell {
+    Empty = 0,
+    One = 1,
+    Two = 2,
+    Three = 3,
+    Four = 4,
+    Five = 5,
+    Six = 6,
+    Seven = 7,
+    Eight = 8,
+    NoMineCovered = 9,
+    MineCovered = 10,
+    NoMineFlagged = 11,
+    MineFlagged = 12,
+    MineExploded = 13,
+    NoMineQuestioned = 14,
+    MineQuestioned = 15,
+}
+
+/// the uncovered spots, indexed by how many mines are around them
+const NUMBERS: [Cell; 9] = [
+    Cell::Empty,
+    Cell::One,
+    Cell::Two,
+    Cell::Three,
+    Cell::Four,
+    Cell::Five,
+    Cell::Six,
+    Cell::Seven,
+    Cell::Eight,
+];
+
+impl Cell {
+    /// An uncovered spot with `mines` around it. No topology gives a spot more than
+    /// `MAX_NEIGHBORS` neighbors, so anything above 8 can't happen and shows as 8.
+    pub fn uncovered(mines: usize) -> Cell {
+        NUMBERS[mines.min(NUMBERS.len() - 1)]
+    }
+
+    /// the number showing on an uncovered spot, `None` for anything else
+    pub fn number(self) -> Option<u8> {
+        match self {
+            Cell::Empty
+            | Cell::One
+            | Cell::Two
+            | Cell::Three
+            | Cell::Four
+            | Cell::Five
+            | Cell::Six
+            | Cell::Seven
+            | Cell::Eight => Some(self as u8),
+            Cell::NoMineCovered
+            | Cell::MineCovered
+            | Cell::NoMineFlagged
+            | Cell::MineFlagged
+            | Cell::MineExploded
+            | Cell::NoMineQuestioned
+            | Cell::MineQuestioned => None,
+        }
+    }
+
+    /// covered, flagged, question marked or exploded, any spot that holds a mine
+    pub fn is_mine(self) -> bool {
+        matches!(
+            self,
+            Cell::MineCovered | Cell::MineFlagged | Cell::MineExploded | Cell::MineQuestioned
+        )
+    }
+
+    pub fn is_flagged(self) -> bool {
+        matches!(self, Cell::NoMineFlagged | Cell::MineFlagged)
+    }
+
+    /// anything the player can't see under yet, flags and question marks included
+    pub fn is_covered(self) -> bool {
+        matches!(
+            self,
+            Cell::NoMineCovered
+                | Cell::MineCovered
+                | Cell::NoMineFlagged
+                | Cell::MineFlagged
+                | Cell::NoMineQuestioned
+                | Cell::MineQuestioned
+        )
+    }
+
+    /// The same covered spot with a mine put under it, `None` if it already has one or
+    /// isn't covered
+    pub fn with_mine(self) -> Option<Cell> {
+        match self {
+            Cell::NoMineCovered => Some(Cell::MineCovered),
+            Cell::NoMineFlagged => Some(Cell::MineFlagged),
+            Cell::NoMineQuestioned => Some(Cell::MineQuestioned),
+            _ => None,
+        }
+    }
+
+    /// What the spot looks like as text: the emoji `Display` uses, or plain ASCII for
+    /// terminals that can't show them. ASCII symbols are one character wide, emoji two.
+    pub fn symbol(self, ascii: bool) -> char {
+        match (self, ascii) {
+            (Cell::MineCovered | Cell::NoMineCovered, false) => '🟩',
+            (Cell::MineCovered | Cell::NoMineCovered, true) => '#',
+            (Cell::MineFlagged | Cell::NoMineFlagged, false) => '🚩',
+            (Cell::MineFlagged | Cell::NoMineFlagged, true) => 'F',
+            (Cell::MineQuestioned | Cell::NoMineQuestioned, false) => '❓',
+            (Cell::MineQuestioned | Cell::NoMineQuestioned, true) => '?',
+            (Cell::Empty, false) => '⬜',
+            (Cell::Empty, true) => '.',
+            (Cell::MineExploded, false) => '💣',
+            (Cell::MineExploded, true) => '*',
+            (
+                Cell::One
+                | Cell::Two
+                | Cell::Three
+                | Cell::Four
+                | Cell::Five
+                | Cell::Six
+                | Cell::Seven
+                | Cell::Eight,
+                _,
+            ) => (b'0' + self as u8) as char,
+        }
+    }
+}
+
+impl TryFrom<u8> for Cell {
+    type Error = String;
+
+    fn try_from(code: u8) -> Result<Cell, String> {
+        Ok(match code {
+            0..=8 => NUMBERS[code as usize],
+            9 => Cell::NoMineCovered,
+            10 => Cell::MineCovered,
+            11 => Cell::NoMineFlagged,
+            12 => Cell::MineFlagged,
+            13 => Cell::MineExploded,
+            14 => Cell::NoMineQuestioned,
+            15 => Cell::MineQuestioned,
+            _ => return Err(format!("{} is not a state code", code)),
+        })
+    }
+}
+
+impl From<Cell> for u8 {
+    fn from(cell: Cell) -> u8 {
+        cell as u8
+    }
+}
+
+impl fmt::Display for Cell {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.symbol(false))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_round_trip() {
+        for code in 0..=15u8 {
+            let cell = Cell::try_from(code).unwrap();
+            assert_eq!(u8::from(cell), code);
+        }
+        assert!(Cell::try_from(16).is_err());
+        assert!(Cell::try_from(255).is_err());
+        assert_eq!(std::mem::size_of::<Cell>(), 1);
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(Cell::uncovered(0), Cell::Empty);
+        assert_eq!(Cell::uncovered(3).number(), Some(3));
+        assert_eq!(Cell::MineFlagged.number(), None);
+        assert_eq!(Cell::Seven.symbol(true), '7');
+        assert_eq!(
+            Cell::NoMineQuestioned.with_mine(),
+            Some(Cell::MineQuestioned)
+        );
+        assert_eq!(Cell::Two.with_mine(), None);
+    }
+}
@@ -0,0 +1,279 @@
+//! Every move made in a game, so moves can be undone and redone, and a finished game can be
+//! shared as a short replay and stepped through one move at a time.
+//!
+//! A replay looks like `3x3-gA? c2.2 f0.0 h1.1`: the board code of the mines (see
+//! `MineSweeper::board_code`), with a `?` on the end when flags cycle through question marks,
+//! then one word per move. `c` is a click, `f` a flag toggle and `h` a chord, followed by the
+//! column and row of the spot.
+
+use crate::cell::Cell;
+use crate::minesweeper2::MineSweeper;
+use wasm_bindgen::prelude::*;
+
+/// What the player did on a move
+#[wasm_bindgen]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MoveKind {
+    Click = 0,
+    Flag = 1,
+    Chord = 2,
+}
+
+impl MoveKind {
+    fn letter(self) -> char {
+        match self {
+            MoveKind::Click => 'c',
+            MoveKind::Flag => 'f',
+            MoveKind::Chord => 'h',
+        }
+    }
+
+    fn from_letter(letter: char) -> Option<MoveKind> {
+        match letter {
+            'c' => Some(MoveKind::Click),
+            'f' => Some(MoveKind::Flag),
+            'h' => Some(MoveKind::Chord),
+            _ => None,
+        }
+    }
+}
+
+/// a move as a replay writes it: what was done, then the column and row
+type Step = (MoveKind, usize, usize);
+
+/// one spot before and after a move
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Change {
+    pub idx: usize,
+    pub before: Cell,
+    pub after: Cell,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Move {
+    pub kind: MoveKind,
+    pub col: usize,
+    pub row: usize,
+    pub changes: Vec<Change>,
+}
+
+/// The moves played so far. Undone moves stay after `position` so they can be redone,
+/// until a new move replaces them.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct History {
+    moves: Vec<Move>,
+    position: usize,
+}
+
+impl History {
+    pub fn record(&mut self, played: Move) {
+        self.moves.truncate(self.position);
+        self.moves.push(played);
+        self.position += 1;
+    }
+
+    /// the last move played, now taken back
+    pub fn undo(&mut self) -> Option<&Move> {
+        self.position = self.position.checked_sub(1)?;
+        self.moves.get(self.position)
+    }
+
+    /// the last move taken back, now played again
+    pub fn redo(&mut self) -> Option<&Move> {
+        let redone = self.moves.get(self.position)?;
+        self.position += 1;
+        Some(redone)
+    }
+
+    /// moves played and not undone
+    pub fn played(&self) -> &[Move] {
+        &self.moves[..self.position]
+    }
+
+    /// Puts the mines placed on the first click under the spots flagged before it, so undoing
+    /// or redoing those flags doesn't take the mines away again
+    pub fn place_mines(&mut self, mines: &[bool]) {
+        for change in self.moves.iter_mut().flat_map(|m| m.changes.iter_mut()) {
+            if mines[change.idx] {
+                change.before = change.before.with_mine().unwrap_or(change.before);
+                change.after = change.after.with_mine().unwrap_or(change.after);
+            }
+        }
+    }
+}
+
+/// Writes the moves played on a board as a replay. `code` is the board's `board_code`.
+pub(crate) fn encode(code: &str, question_marks: bool, moves: &[Move]) -> String {
+    let mut replay = String::from(code);
+    if question_marks {
+        replay.push('?');
+    }
+    for played in moves {
+        replay.push_str(&format!(
+            " {}{}.{}",
+            played.kind.letter(),
+            played.col,
+            played.row
+        ));
+    }
+    replay
+}
+
+/// the board code, question mark setting and moves of a replay made by `encode`
+pub(crate) fn decode(replay: &str) -> Result<(String, bool, Vec<Step>), String> {
+    let mut words = replay.split_whitespace();
+    let code = words
+        .next()
+        .ok_or_else(|| String::from("A replay needs a board code"))?;
+    let (code, question_marks) = match code.strip_suffix('?') {
+        Some(code) => (code, true),
+        None => (code, false),
+    };
+    let mut moves = Vec::new();
+    for word in words {
+        let bad = || format!("{:?} is not a move", word);
+        let mut letters = word.chars();
+        let kind = letters
+            .next()
+            .and_then(MoveKind::from_letter)
+            .ok_or_else(bad)?;
+        let (col, row) = letters.as_str().split_once('.').ok_or_else(bad)?;
+        let col = col.parse().map_err(|_| bad())?;
+        let row = row.parse().map_err(|_| bad())?;
+        moves.push((kind, col, row));
+    }
+    Ok((String::from(code), question_marks, moves))
+}
+
+/// A shared game played back one move at a time
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct Replay {
+    game: MineSweeper,
+    moves: Vec<Step>,
+    /// whether each move stepped through so far changed anything, so stepping back
+    /// only undoes the ones that did
+    stepped: Vec<bool>,
+}
+
+#[wasm_bindgen]
+impl Replay {
+    /// Reads a replay from `MineSweeper::replay`, starting at the covered board
+    pub fn from_text(replay: &str) -> Result<Replay, String> {
+        let (code, question_marks, moves) = decode(replay)?;
+        let mut game = MineSweeper::from_board_code(&code)?;
+        game.set_question_marks(question_marks);
+        if let Some((_, col, row)) = moves
+            .iter()
+            .find(|(_, col, row)| *col >= game.width() || *row >= game.height())
+        {
+            return Err(format!("Move at {}, {} is off the board", col, row));
+        }
+        Ok(Replay {
+            game,
+            moves,
+            stepped: Vec::new(),
+        })
+    }
+
+    /// Plays the next move and returns the indexes of the spots it changed.
+    /// Empty at the end of the replay.
+    pub fn step(&mut self) -> Vec<usize> {
+        let (kind, col, row) = match self.moves.get(self.stepped.len()) {
+            Some(next) => *next,
+            None => return Vec::new(),
+        };
+        let changed = self.game.play(kind, col, row);
+        self.stepped.push(!changed.is_empty());
+        changed
+    }
+
+    /// Takes back the last move stepped through, returning the spots it changed
+    pub fn step_back(&mut self) -> Vec<usize> {
+        match self.stepped.pop() {
+            Some(true) => self.game.undo(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// how many moves have been stepped through
+    pub fn position(&self) -> usize {
+        self.stepped.len()
+    }
+
+    /// how many moves the replay has
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    /// the board as it is at the current step
+    pub fn game(&self) -> MineSweeper {
+        self.game.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replay_text_round_trips() {
+        let moves = vec![
+            Move {
+                kind: MoveKind::Click,
+                col: 2,
+                row: 2,
+                changes: Vec::new(),
+            },
+            Move {
+                kind: MoveKind::Flag,
+                col: 0,
+                row: 10,
+                changes: Vec::new(),
+            },
+        ];
+        let text = encode("3x3-gA", true, &moves);
+        assert_eq!(text, "3x3-gA? c2.2 f0.10");
+        let (code, question_marks, decoded) = decode(&text).unwrap();
+        assert_eq!((code.as_str(), question_marks), ("3x3-gA", true));
+        assert_eq!(
+            decoded,
+            vec![(MoveKind::Click, 2, 2), (MoveKind::Flag, 0, 10)]
+        );
+    }
+
+    #[test]
+    fn rejects_bad_replays() {
+        assert!(decode("").is_err());
+        assert!(decode("3x3-gA x1.1").is_err());
+        assert!(decode("3x3-gA c1").is_err());
+        assert!(Replay::from_text("3x3-gA c3.0").is_err());
+        assert!(Replay::from_text("nonsense c0.0").is_err());
+    }
+
+    #[test]
+    fn undone_moves_are_dropped_by_a_new_move() {
+        let played = |col| Move {
+            kind: MoveKind::Click,
+            col,
+            row: 0,
+            changes: Vec::new(),
+        };
+        let mut history = History::default();
+        history.record(played(0));
+        history.record(played(1));
+        assert_eq!(history.undo().map(|m| m.col), Some(1));
+        history.record(played(2));
+        assert_eq!(history.redo(), None);
+        let cols: Vec<usize> = history.played().iter().map(|m| m.col).collect();
+        assert_eq!(cols, vec![0, 2]);
+        history.undo();
+        history.undo();
+        assert_eq!(history.undo(), None);
+        assert_eq!(history.redo().map(|m| m.col), Some(0));
+    }
+}
@@ -1,4 +1,12 @@
+mod board_code;
+pub mod cell;
+pub mod history;
 pub mod minesweeper2;
+mod rng;
+pub mod save;
+pub mod solver;
+pub mod stats;
+pub mod topology;
 mod utils;
 use wasm_bindgen::prelude::*;
 
@@ -7,78 +7,199 @@
 /// will be wasm friendly. This makes the data more rigid since each number will refer to any of the possible states
 /// a spot can be.
 ///
-/// ## State Codes
-/// - 0 - Uncovered with no neighbors
-/// - 1 - Uncovered with 1 mine neighbor
-/// - 2 - Uncovered with 2 mine neighbors
-/// - 3 - Uncovered with 3 mine neighbors
-/// - 4 - Uncovered with 4 mine neighbors
-/// - 5 - Uncovered with 5 mine neighbors
-/// - 6 - Uncovered with 6 mine neighbors
-/// - 7 - Uncovered with 7 mine neighbors
-/// - 8 - Uncovered with 8 mine neighbors
-/// - 9 - Covered without a mine
-/// - 10 - Covered with a mine
-/// - 11 - Flagged without a mine
-/// - 12 - Flagged with a mine
-/// - 13 - An uncovered bomb aka Game Over
+/// The spots are `Cell`s, one byte each holding its state code, see `cell` for the codes
+/// and the versions of the buffer layout.
 ///
 use wasm_bindgen::prelude::*;
 extern crate web_sys;
+use crate::board_code;
+use crate::cell::{Cell, STATE_LAYOUT_VERSION};
+use crate::history::{self, Change, History, Move, MoveKind};
+use crate::rng::{random_seed, Rng};
+use crate::solver;
+use crate::topology::{Neighbors, Shape, Topology};
 use crate::utils::set_panic_hook;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 
 // A macro to provide `println!(..)`-style syntax for `console.log` logging.
+#[cfg(target_arch = "wasm32")]
 macro_rules! log {
     ( $( $t:tt )* ) => {
         web_sys::console::log_1(&format!( $( $t )* ).into());
     }
 }
 
-const POSSIBLE_NEIGHBORS: [[isize; 2]; 8] = [
-    [-1, -1],
-    [-1, 0],
-    [-1, 1],
-    [0, -1],
-    [0, 1],
-    [1, -1],
-    [1, 0],
-    [1, 1],
-];
-
-const NO_MINE_COVERED: u8 = 9;
-const MINE_COVERED: u8 = 10;
-const NO_MINE_FLAGGED: u8 = 11;
-const MINE_FLAGGED: u8 = 12;
-const MINE_EXPLODED: u8 = 13;
-
-/// this compiles only if compiling on the web and gets the js-sys crate for random number generation on the browswer
-#[cfg(target_arch = "wasm32")]
-fn random_number_from_range(bottom: usize, top: usize) -> usize {
-    use js_sys::Math::random;
-    (random() * (top - bottom) as f64) as usize + bottom
+// there is no browser console natively, and calling into web_sys there panics
+#[cfg(not(target_arch = "wasm32"))]
+macro_rules! log {
+    ( $( $t:tt )* ) => {
+        let _ = format_args!( $( $t )* );
+    };
 }
 
-/// this compiles when not compiling to the web. This makes the game still playable on the cli
-#[cfg(not(target_arch = "wasm32"))]
-fn random_number_from_range(bottom: usize, top: usize) -> usize {
-    use rand::Rng;
-    let mut rng = rand::thread_rng();
-    rng.gen_range(bottom..top)
+/// how many boards a no-guess game deals before settling for the last one
+const MAX_NO_GUESS_ATTEMPTS: usize = 1000;
+
+/// When and how the mines get placed
+#[wasm_bindgen]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Generation {
+    /// mines are placed as soon as the board is made, so the first click can hit one
+    Immediate = 0,
+    /// mines are placed on the first click, never on or next to the clicked spot
+    FirstClickSafe = 1,
+    /// like `FirstClickSafe`, but only keeps boards the solver can clear without guessing
+    NoGuess = 2,
+}
+
+/// The classic board sizes
+#[wasm_bindgen]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Difficulty {
+    /// 9x9 with 10 mines
+    Beginner = 0,
+    /// 16x16 with 40 mines
+    Intermediate = 1,
+    /// 30x16 with 99 mines
+    Expert = 2,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [
+        Difficulty::Beginner,
+        Difficulty::Intermediate,
+        Difficulty::Expert,
+    ];
+
+    /// width, height and mine count
+    pub fn size(self) -> (usize, usize, usize) {
+        match self {
+            Difficulty::Beginner => (9, 9, 10),
+            Difficulty::Intermediate => (16, 16, 40),
+            Difficulty::Expert => (30, 16, 99),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Beginner => "beginner",
+            Difficulty::Intermediate => "intermediate",
+            Difficulty::Expert => "expert",
+        }
+    }
+}
+
+impl std::str::FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Difficulty, String> {
+        Difficulty::ALL
+            .iter()
+            .find(|d| d.name().eq_ignore_ascii_case(text))
+            .copied()
+            .ok_or_else(|| format!("{:?} is not beginner, intermediate or expert", text))
+    }
+}
+
+/// Where the game is at, for the UI to know when to stop taking clicks
+#[wasm_bindgen]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GameStatus {
+    Playing = 0,
+    /// every spot without a mine is uncovered
+    Won = 1,
+    /// a mine was uncovered
+    Lost = 2,
 }
 
 /// Minesweeper struct that holds the data for the game
 #[wasm_bindgen]
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct MineSweeper {
     /// map of bombs or empty spaces. Map is a one dimensional array that is calculated to index as if it was a 2d array
-    game_state: Vec<u8>,
+    game_state: Vec<Cell>,
     width: usize,
     height: usize,
+    mine_count: usize,
+    generation: Generation,
+    /// false until the first click when the mines are placed late
+    mines_placed: bool,
+    /// the board is shuffled from this, so the same seed gives the same board
+    seed: u64,
+    rng: Rng,
+    /// whether `toggle_flag` goes through a question mark after the flag
+    question_marks: bool,
+    /// moves played, for undo, redo and replays
+    history: History,
+    /// which spots are neighbors
+    topology: Topology,
 }
 
 #[wasm_bindgen]
 impl MineSweeper {
     pub fn new(width: usize, height: usize, mine_count: usize) -> MineSweeper {
+        MineSweeper::with_generation(width, height, mine_count, Generation::Immediate)
+    }
+
+    /// Same as `new`, but `generation` picks when the mines get placed.
+    /// With `FirstClickSafe` or `NoGuess` the board stays empty until the first `click`.
+    pub fn with_generation(
+        width: usize,
+        height: usize,
+        mine_count: usize,
+        generation: Generation,
+    ) -> MineSweeper {
+        MineSweeper::with_seed_and_generation(width, height, mine_count, random_seed(), generation)
+    }
+
+    /// A classic sized board, with the mines placed on the first click
+    pub fn with_difficulty(difficulty: Difficulty) -> MineSweeper {
+        let (width, height, mines) = difficulty.size();
+        MineSweeper::with_generation(width, height, mines, Generation::FirstClickSafe)
+    }
+
+    /// Same as `new` but the mines are placed from `seed`. The same seed gives the
+    /// same board in the browser and natively.
+    pub fn with_seed(width: usize, height: usize, mine_count: usize, seed: u64) -> MineSweeper {
+        MineSweeper::with_seed_and_generation(
+            width,
+            height,
+            mine_count,
+            seed,
+            Generation::Immediate,
+        )
+    }
+
+    /// A seeded board with late mine placement. The mines then also depend on where the first click is.
+    pub fn with_seed_and_generation(
+        width: usize,
+        height: usize,
+        mine_count: usize,
+        seed: u64,
+        generation: Generation,
+    ) -> MineSweeper {
+        MineSweeper::with_topology(
+            width,
+            height,
+            mine_count,
+            seed,
+            generation,
+            &Topology::square(),
+        )
+    }
+
+    /// The most general constructor, for boards where the neighbors aren't the usual 8
+    /// around a square, see `Topology`.
+    pub fn with_topology(
+        width: usize,
+        height: usize,
+        mine_count: usize,
+        seed: u64,
+        generation: Generation,
+        topology: &Topology,
+    ) -> MineSweeper {
         set_panic_hook();
         assert!(width * height != 0, "Cannot have a height or width of zero");
         assert!(
@@ -86,118 +207,354 @@ impl MineSweeper {
             "Cannot have more mines than spaces"
         );
 
-        let mut game_state = vec![NO_MINE_COVERED; width * height];
-
-        let mut mines_inserted = 0;
+        let mut ms = MineSweeper {
+            game_state: vec![Cell::NoMineCovered; width * height],
+            width,
+            height,
+            mine_count,
+            generation,
+            mines_placed: false,
+            seed,
+            rng: Rng::new(seed),
+            question_marks: true,
+            history: History::default(),
+            topology: topology.clone(),
+        };
+        if generation == Generation::Immediate {
+            ms.place_mines(&[]);
+        }
+        ms
+    }
 
-        while mines_inserted < mine_count {
-            let random_idx = random_number_from_range(0, width * height);
-            if let NO_MINE_COVERED = game_state[random_idx] {
-                game_state[random_idx] = MINE_COVERED;
-                mines_inserted += 1;
+    /// Rebuilds a board shared with `board_code`, with every spot covered again
+    pub fn from_board_code(code: &str) -> Result<MineSweeper, String> {
+        let (code, topology) = match code.trim().split_once('.') {
+            Some((code, name)) => (code, name.parse()?),
+            None => (code, Topology::square()),
+        };
+        let (width, height, mines) = board_code::decode(code)?;
+        let mine_count = mines.iter().filter(|m| **m).count();
+        if mine_count >= width * height {
+            return Err(String::from("Cannot have more mines than spaces"));
+        }
+        let mut ms =
+            MineSweeper::with_topology(width, height, 0, 0, Generation::Immediate, &topology);
+        for (spot, mine) in ms.game_state.iter_mut().zip(mines) {
+            if mine {
+                *spot = Cell::MineCovered;
             }
         }
+        ms.mine_count = mine_count;
+        Ok(ms)
+    }
 
-        MineSweeper {
-            game_state,
-            width,
-            height,
+    /// A short code for this board's mines that `from_board_code` turns back into the same board.
+    /// Boards that aren't a plain square grid get their topology's name on the end after a `.`.
+    /// Empty until the mines are placed.
+    pub fn board_code(&self) -> String {
+        if !self.mines_placed {
+            return String::new();
+        }
+        let mines: Vec<bool> = self.game_state.iter().map(|s| s.is_mine()).collect();
+        let code = board_code::encode(self.width, self.height, &mines);
+        if self.topology == Topology::square() {
+            code
+        } else {
+            format!("{}.{}", code, self.topology)
         }
     }
 
-    /// Gets a pointer to the game state for the javascript
+    /// which spots are neighbors, for the front end to know how to draw the board
+    pub fn topology(&self) -> Topology {
+        self.topology.clone()
+    }
+
+    /// the seed the board was made from
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// the state code of one spot, see `cell`, or nothing off the board
+    pub fn spot(&self, col: usize, row: usize) -> Option<u8> {
+        self.cell(col, row).map(u8::from)
+    }
+
+    /// Gets a pointer to the game state for the javascript: `width * height` state codes,
+    /// row by row, laid out as `state_layout_version` says
     pub fn state(&self) -> *const u8 {
-        self.game_state.as_ptr()
+        // a `Cell` is one byte holding its state code
+        self.game_state.as_ptr() as *const u8
+    }
+
+    /// which layout `state` points at, see `cell`
+    pub fn state_layout_version() -> u32 {
+        STATE_LAYOUT_VERSION
     }
 
     /// the front end will give the col and row position of the tile clicked.
     /// This is calculated into the 1-d vector and the state of the game is then updated
-    /// True = Game is still going. False = game ended because of the update
-    pub fn click(&mut self, col: usize, row: usize) -> bool {
+    /// Clicking a revealed number chords, see `chord`.
+    /// Returns the indexes of every spot that was uncovered, so the front end only has to
+    /// redraw those. Nothing changes, and the list is empty, off the board or once the game
+    /// is over; check `game_status` after a click to see if it just ended.
+    /// Placing the mines on the first click isn't counted since covered spots look the same
+    /// with or without a mine.
+    pub fn click(&mut self, col: usize, row: usize) -> Vec<usize> {
+        self.play(MoveKind::Click, col, row)
+    }
+
+    /// Clicking a revealed number that has as many flags around it as its number uncovers
+    /// every other covered neighbor at once. A wrong flag means one of those is a mine.
+    /// Returns the same as `click`.
+    pub fn chord(&mut self, col: usize, row: usize) -> Vec<usize> {
+        self.play(MoveKind::Chord, col, row)
+    }
+
+    /// The user placed a flag, usually with the right click button.
+    /// Returns true if successful in placing a flag
+    pub fn place_flag(&mut self, col: usize, row: usize) -> bool {
+        if !self.on_board(col, row) {
+            return false;
+        }
         let idx = self.get_idx(col, row);
-        log!(
-            ">> Rust >> User clicked on col {}, row {}, of state {}",
+        let flagged = match self.game_state[idx] {
+            Cell::MineCovered => Cell::MineFlagged,
+            Cell::NoMineCovered => Cell::NoMineFlagged,
+            _ => return false,
+        };
+        let mut changes = Vec::new();
+        self.set(idx, flagged, &mut changes);
+        self.history.record(Move {
+            kind: MoveKind::Flag,
             col,
             row,
-            self.game_state[idx]
-        );
-        match self.game_state[idx] {
-            // player clicked on an uncovered spot, empty, or flagged spot. Do nothing.
-            0..=8 | MINE_FLAGGED | NO_MINE_FLAGGED => true,
+            changes,
+        });
+        true
+    }
 
-            // player clicked on bomb, and this shouldn't happen but is controlled by the ui
-            MINE_EXPLODED => false,
+    /// Cycles a covered spot through flagged, question mark (if turned on) and back to covered.
+    /// Returns true if the spot changed
+    pub fn toggle_flag(&mut self, col: usize, row: usize) -> bool {
+        !self.play(MoveKind::Flag, col, row).is_empty()
+    }
 
-            // uncovering a tile
-            MINE_COVERED | NO_MINE_COVERED => {
-                if let MINE_COVERED = self.game_state[idx] {
-                    self.game_state[idx] = MINE_EXPLODED;
-                    return false;
+    /// Takes back the last move, click, flag or chord, even one that ended the game.
+    /// Returns the indexes of the spots it changed back, empty if there is nothing to undo.
+    /// The mines stay where they are if the first click is undone.
+    pub fn undo(&mut self) -> Vec<usize> {
+        match self.history.undo() {
+            Some(undone) => {
+                for change in undone.changes.iter().rev() {
+                    self.game_state[change.idx] = change.before;
                 }
+                undone.changes.iter().map(|c| c.idx).collect()
+            }
+            None => Vec::new(),
+        }
+    }
 
-                let neighbor_count = self.get_mine_neighbor_count(col, row);
-                if neighbor_count == 0 {
-                    // allowing `uncover_empty_neighbors()` take charge of changing state to empty
-                    self.uncover_empty_neighbors(col, row);
-                } else {
-                    self.game_state[idx] = neighbor_count;
+    /// Plays the last undone move again. Returns the spots it changed, like `undo`.
+    /// Nothing can be redone once a new move is played.
+    pub fn redo(&mut self) -> Vec<usize> {
+        match self.history.redo() {
+            Some(redone) => {
+                for change in redone.changes.iter() {
+                    self.game_state[change.idx] = change.after;
                 }
-                true
+                redone.changes.iter().map(|c| c.idx).collect()
             }
-            _ => unreachable!(),
+            None => Vec::new(),
         }
     }
 
-    /// The user placed a flag, usually with the right click button.
-    /// Returns true if successful in placing a flag
-    pub fn place_flag(&mut self, col: usize, row: usize) -> bool {
-        let idx = self.get_idx(col, row);
-        match self.game_state[idx] {
-            MINE_COVERED => {
-                self.game_state[idx] = MINE_FLAGGED;
-                true
-            }
-            NO_MINE_COVERED => {
-                self.game_state[idx] = NO_MINE_FLAGGED;
-                true
-            }
-            _ => false,
+    /// how many moves have been played, not counting undone ones
+    pub fn history_len(&self) -> usize {
+        self.history.played().len()
+    }
+
+    /// The moves played so far as short text that `Replay::from_text` can step through.
+    /// Empty until the mines are placed.
+    pub fn replay(&self) -> String {
+        if !self.mines_placed {
+            return String::new();
+        }
+        history::encode(
+            &self.board_code(),
+            self.question_marks,
+            self.history.played(),
+        )
+    }
+
+    /// turns the question mark step of `toggle_flag` on or off. On by default
+    pub fn set_question_marks(&mut self, enabled: bool) {
+        self.question_marks = enabled;
+    }
+
+    /// how many flags are on the board, for the mine counter
+    pub fn get_flag_count(&self) -> usize {
+        self.game_state.iter().filter(|s| s.is_flagged()).count()
+    }
+
+    /// A spot proven safe, or if there is none the spot least likely to be a mine, with
+    /// that chance. `undefined` once the game is over.
+    pub fn hint(&self) -> Option<solver::Hint> {
+        solver::hint(self)
+    }
+
+    pub fn game_status(&self) -> GameStatus {
+        if self.game_state.contains(&Cell::MineExploded) {
+            GameStatus::Lost
+        } else if self.mines_placed
+            && !self
+                .game_state
+                .iter()
+                .any(|s| s.is_covered() && !s.is_mine())
+        {
+            GameStatus::Won
+        } else {
+            GameStatus::Playing
         }
     }
 
     // gets the total number of bombs in the map
     pub fn get_total_bombs(&self) -> usize {
-        self.game_state
-            .iter()
-            .filter(|&&x| x == MINE_COVERED || x == MINE_FLAGGED || x == MINE_EXPLODED)
-            .count()
+        if !self.mines_placed {
+            return self.mine_count;
+        }
+        self.game_state.iter().filter(|s| s.is_mine()).count()
     }
 
     // finds the bombs that are hidden and not flagged
     pub fn get_hidden_bombs(&self) -> usize {
+        if !self.mines_placed {
+            return self.mine_count;
+        }
         self.game_state
             .iter()
-            .filter(|&&spot| spot == MINE_COVERED)
+            .filter(|&&spot| spot == Cell::MineCovered || spot == Cell::MineQuestioned)
             .count()
     }
 }
 
 impl MineSweeper {
-    /// getting the total amount of bombs that are immediate neighbors to the spot passed in by col and row.
-    /// max is 8, meaning all its sides and diagonals are mines
+    pub(crate) fn game_state(&self) -> &[Cell] {
+        &self.game_state
+    }
+
+    /// one spot on the board, or nothing off the board
+    pub fn cell(&self, col: usize, row: usize) -> Option<Cell> {
+        if !self.on_board(col, row) {
+            return None;
+        }
+        Some(self.game_state[self.get_idx(col, row)])
+    }
+
+    pub(crate) fn mines_placed(&self) -> bool {
+        self.mines_placed
+    }
+
+    pub(crate) fn generation(&self) -> Generation {
+        self.generation
+    }
+
+    pub(crate) fn question_marks(&self) -> bool {
+        self.question_marks
+    }
+
+    /// Builds a game from saved parts, see `save`. Nothing is checked here.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn restore(
+        width: usize,
+        height: usize,
+        mine_count: usize,
+        seed: u64,
+        generation: Generation,
+        topology: &Topology,
+        question_marks: bool,
+        game_state: Vec<Cell>,
+    ) -> MineSweeper {
+        let mut ms = MineSweeper::with_topology(
+            width,
+            height,
+            mine_count,
+            seed,
+            Generation::FirstClickSafe,
+            topology,
+        );
+        ms.mines_placed = mine_count == 0 || game_state.iter().any(|s| s.is_mine());
+        ms.generation = generation;
+        ms.question_marks = question_marks;
+        ms.game_state = game_state;
+        ms
+    }
+
+    /// indexes of every spot touching `idx`
+    pub(crate) fn neighbors(&self, idx: usize) -> Neighbors {
+        self.topology.neighbors(self.width, self.height, idx)
+    }
+
+    /// Randomly places every mine, keeping them off the spots in `safe`
+    fn place_mines(&mut self, safe: &[usize]) {
+        let mut mines_inserted = 0;
+
+        while mines_inserted < self.mine_count {
+            let random_idx = self.rng.range(0, self.width * self.height);
+            if safe.contains(&random_idx) {
+                continue;
+            }
+            // flags and question marks may already be down when the mines are placed late
+            let with_mine = match self.game_state[random_idx].with_mine() {
+                Some(with_mine) => with_mine,
+                None => continue,
+            };
+            self.game_state[random_idx] = with_mine;
+            mines_inserted += 1;
+        }
+        self.mines_placed = true;
+    }
+
+    /// Places the mines late, on the first click. The clicked spot and its neighbors stay clear
+    /// so the click opens an area, unless the board is too full for that, then only the clicked
+    /// spot is kept clear. `NoGuess` keeps dealing until the solver can clear the board.
+    fn place_mines_around(&mut self, idx: usize) {
+        let mut safe = self.neighbors(idx).to_vec();
+        safe.push(idx);
+        if self.width * self.height - safe.len() < self.mine_count {
+            safe = vec![idx];
+        }
+        let empty = self.game_state.clone();
+        for attempt in 1..=MAX_NO_GUESS_ATTEMPTS {
+            self.game_state.clone_from(&empty);
+            self.place_mines(&safe);
+            if self.generation != Generation::NoGuess || solver::clears_without_guessing(self, idx)
+            {
+                break;
+            }
+            if attempt == MAX_NO_GUESS_ATTEMPTS {
+                log!(">> Rust >> No board without guessing found, keeping the last one");
+            }
+        }
+        let mines: Vec<bool> = self.game_state.iter().map(|s| s.is_mine()).collect();
+        self.history.place_mines(&mines);
+    }
+
+    /// getting the total amount of bombs that are neighbors to the spot passed in by col and row.
+    /// max is 8, the most neighbors any topology allows
     fn get_mine_neighbor_count(&self, col: usize, row: usize) -> u8 {
         assert!(self.check_bounds(&(col as isize), &(row as isize)));
-        let col = col as isize;
-        let row = row as isize;
-
-        let mine_neighbors = POSSIBLE_NEIGHBORS
+        let mine_neighbors = self
+            .neighbors(self.get_idx(col, row))
             .iter()
-            .filter_map(|[x, y]| self.get_idx_bounds_checked(col + x, row + y))
-            .filter(|idx| {
-                self.game_state[idx.to_owned()] == MINE_COVERED
-                    || self.game_state[idx.to_owned()] == MINE_FLAGGED
-                    || self.game_state[idx.to_owned()] == MINE_EXPLODED
-            })
+            .filter(|idx| self.game_state[**idx].is_mine())
             .count();
 
         mine_neighbors as u8
@@ -211,39 +568,155 @@ impl MineSweeper {
         self.width * row + col
     }
 
-    fn get_idx_bounds_checked(&self, col: isize, row: isize) -> Option<usize> {
-        if !self.check_bounds(&col, &row) {
-            return None;
+    fn on_board(&self, col: usize, row: usize) -> bool {
+        col < self.width && row < self.height
+    }
+
+    /// Plays one move and records it in the history if it changed anything.
+    /// Returns the indexes of the changed spots. Moves off the board change nothing.
+    pub(crate) fn play(&mut self, kind: MoveKind, col: usize, row: usize) -> Vec<usize> {
+        if self.game_status() != GameStatus::Playing || !self.on_board(col, row) {
+            return Vec::new();
+        }
+        let idx = self.get_idx(col, row);
+        let mut changes = Vec::new();
+        match kind {
+            MoveKind::Click => self.click_idx(idx, &mut changes),
+            MoveKind::Chord => self.chord_idx(idx, &mut changes),
+            MoveKind::Flag => self.toggle_flag_idx(idx, &mut changes),
+        }
+        let changed = changes.iter().map(|c| c.idx).collect();
+        if !changes.is_empty() {
+            self.history.record(Move {
+                kind,
+                col,
+                row,
+                changes,
+            });
         }
+        changed
+    }
 
-        Some(self.get_idx(col as usize, row as usize))
+    /// changes one spot's state, noting it down for the history
+    fn set(&mut self, idx: usize, state: Cell, changes: &mut Vec<Change>) {
+        changes.push(Change {
+            idx,
+            before: self.game_state[idx],
+            after: state,
+        });
+        self.game_state[idx] = state;
     }
 
-    /// for uncovering empty neighbors and recursively uncovering their empty neighbors
-    fn uncover_empty_neighbors(&mut self, col: usize, row: usize) {
-        // break case for recursion
+    fn toggle_flag_idx(&mut self, idx: usize, changes: &mut Vec<Change>) {
+        let (after_flag, after_flag_mine) = if self.question_marks {
+            (Cell::NoMineQuestioned, Cell::MineQuestioned)
+        } else {
+            (Cell::NoMineCovered, Cell::MineCovered)
+        };
+        let toggled = match self.game_state[idx] {
+            Cell::NoMineCovered => Cell::NoMineFlagged,
+            Cell::MineCovered => Cell::MineFlagged,
+            Cell::NoMineFlagged => after_flag,
+            Cell::MineFlagged => after_flag_mine,
+            Cell::NoMineQuestioned => Cell::NoMineCovered,
+            Cell::MineQuestioned => Cell::MineCovered,
+            Cell::Empty
+            | Cell::One
+            | Cell::Two
+            | Cell::Three
+            | Cell::Four
+            | Cell::Five
+            | Cell::Six
+            | Cell::Seven
+            | Cell::Eight
+            | Cell::MineExploded => return,
+        };
+        self.set(idx, toggled, changes);
+    }
 
-        let idx = self.get_idx(col, row);
-        let state = self.game_state[idx];
-        // if spot is a mine or the spot is not covered, then return early.
-        if state == MINE_COVERED || state != NO_MINE_COVERED || state == MINE_EXPLODED {
-            return;
+    fn click_idx(&mut self, idx: usize, changed: &mut Vec<Change>) {
+        if !self.mines_placed {
+            self.place_mines_around(idx);
         }
+        log!(
+            ">> Rust >> User clicked on col {}, row {}, of state {:?}",
+            idx % self.width,
+            idx / self.width,
+            self.game_state[idx]
+        );
+        match self.game_state[idx] {
+            // player clicked on an empty or flagged spot. Do nothing.
+            Cell::Empty | Cell::MineFlagged | Cell::NoMineFlagged => {}
 
-        let state = self.get_mine_neighbor_count(col, row);
-        self.game_state[idx] = state;
-        // you found a cell neighboring a bomb, so stop uncovering
-        if 1 <= state && state <= 8 {
-            return;
+            Cell::One
+            | Cell::Two
+            | Cell::Three
+            | Cell::Four
+            | Cell::Five
+            | Cell::Six
+            | Cell::Seven
+            | Cell::Eight => self.chord_idx(idx, changed),
+
+            // player clicked on bomb, and this shouldn't happen but is controlled by the ui
+            Cell::MineExploded => {}
+
+            // uncovering a tile. Question marks don't protect a spot like flags do
+            Cell::MineCovered | Cell::MineQuestioned => self.set(idx, Cell::MineExploded, changed),
+            Cell::NoMineCovered | Cell::NoMineQuestioned => {
+                self.uncover_empty_neighbors(idx, changed)
+            }
         }
+    }
 
-        // copying height and width so that for_each can borrow self in the closure.
-        let (width, height) = (self.width, self.height);
-        let _inbound_neighbors = POSSIBLE_NEIGHBORS
+    fn chord_idx(&mut self, idx: usize, changed: &mut Vec<Change>) {
+        let number = match self.game_state[idx].number() {
+            Some(number) if number > 0 => number,
+            _ => return,
+        };
+        let neighbors = self.neighbors(idx);
+        let flags = neighbors
             .iter()
-            .map(|[x, y]| [x + col as isize, y + row as isize])
-            .filter(|[x, y]| check_bound_usize(*x, *y, width, height))
-            .for_each(|[x, y]| self.uncover_empty_neighbors(x as usize, y as usize));
+            .filter(|n| self.game_state[**n].is_flagged())
+            .count();
+        if flags != number as usize {
+            return;
+        }
+        for &neighbor in neighbors.iter() {
+            let state = self.game_state[neighbor];
+            if state.is_covered() && !state.is_flagged() {
+                self.click_idx(neighbor, changed);
+            }
+        }
+    }
+
+    /// Uncovers the spot at `idx`, and if it has no mine neighbors keeps uncovering outward
+    /// until the whole empty area and its numbered edge are showing. Uses a queue of spots
+    /// still to spread from instead of recursion, so a huge empty board can't overflow the
+    /// stack. Each spot is uncovered the moment it is queued so it is only visited once.
+    fn uncover_empty_neighbors(&mut self, idx: usize, changed: &mut Vec<Change>) {
+        let mut queue = VecDeque::new();
+        if self.uncover(idx, changed) == 0 {
+            queue.push_back(idx);
+        }
+        while let Some(idx) = queue.pop_front() {
+            for &neighbor in self.neighbors(idx).iter() {
+                let state = self.game_state[neighbor];
+                // mines, flags and uncovered spots stop the spreading
+                if state != Cell::NoMineCovered && state != Cell::NoMineQuestioned {
+                    continue;
+                }
+                if self.uncover(neighbor, changed) == 0 {
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+    }
+
+    /// shows the number on one spot and returns it
+    fn uncover(&mut self, idx: usize, changed: &mut Vec<Change>) -> u8 {
+        let count = self.get_mine_neighbor_count(idx % self.width, idx / self.width);
+        self.set(idx, Cell::uncovered(count as usize), changed);
+        count
     }
 
     /// for checking the bounds of a given row and col since neighbors are blindly checked.
@@ -255,29 +728,17 @@ impl MineSweeper {
     }
 }
 
-// checks if the given row and col are in bounds with usizes.
-fn check_bound_usize(col: isize, row: isize, width: usize, height: usize) -> bool {
-    if col < 0 || row < 0 || col >= (width as isize) || row >= (height as isize) {
-        return false;
-    }
-    true
-}
-
 impl std::fmt::Display for MineSweeper {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let mut output = String::new();
         output.push('\n');
         for row in 0..self.height {
+            // hex grids lean every odd row to the right
+            if self.topology.shape() == Shape::Hex && row % 2 == 1 {
+                output.push(' ');
+            }
             for col in 0..self.width {
-                let state = self.game_state[self.get_idx(col, row)];
-                match state {
-                    MINE_COVERED | NO_MINE_COVERED => output.push('🟩'),
-                    MINE_FLAGGED | NO_MINE_FLAGGED => output.push('🚩'),
-                    0 => output.push('⬜'),
-                    MINE_EXPLODED => output.push('💣'),
-                    1..=8 => output.push_str(&state.to_string()),
-                    _ => unreachable!(),
-                }
+                output.push(self.game_state[self.get_idx(col, row)].symbol(false));
             }
             output.push('\n')
         }
@@ -288,7 +749,8 @@ impl std::fmt::Display for MineSweeper {
 #[cfg(test)]
 mod tests {
     use super::*;
-    const EMPTY: u8 = 0;
+    use crate::history::Replay;
+    const EMPTY: Cell = Cell::Empty;
 
     #[test]
     #[should_panic]
@@ -302,7 +764,7 @@ mod tests {
         assert_eq!(
             ms.game_state
                 .iter()
-                .filter(|&&item| item == MINE_COVERED)
+                .filter(|&&item| item == Cell::MineCovered)
                 .count(),
             2
         )
@@ -311,7 +773,7 @@ mod tests {
     #[test]
     fn test_getting_neighbors_1() {
         let mut ms = MineSweeper::new(5, 5, 0);
-        ms.game_state[0] = MINE_COVERED;
+        ms.game_state[0] = Cell::MineCovered;
         assert_eq!(1, ms.get_mine_neighbor_count(0, 1));
         assert_eq!(1, ms.get_mine_neighbor_count(1, 1));
         assert_eq!(1, ms.get_mine_neighbor_count(1, 0));
@@ -320,7 +782,7 @@ mod tests {
     #[test]
     fn test_getting_neighbors_2() {
         let mut ms = MineSweeper::new(5, 5, 0);
-        ms.game_state[12] = MINE_COVERED;
+        ms.game_state[12] = Cell::MineCovered;
         assert_eq!(0, ms.get_mine_neighbor_count(0, 1));
         assert_eq!(1, ms.get_mine_neighbor_count(2, 3));
         assert_eq!(0, ms.get_mine_neighbor_count(2, 2));
@@ -329,8 +791,8 @@ mod tests {
     #[test]
     fn test_getting_neighbors_3() {
         let mut ms = MineSweeper::new(5, 5, 0);
-        ms.game_state[0] = MINE_COVERED;
-        ms.game_state[1] = MINE_COVERED;
+        ms.game_state[0] = Cell::MineCovered;
+        ms.game_state[1] = Cell::MineCovered;
         assert_eq!(2, ms.get_mine_neighbor_count(0, 1));
         assert_eq!(2, ms.get_mine_neighbor_count(1, 1));
         assert_eq!(1, ms.get_mine_neighbor_count(2, 1));
@@ -340,7 +802,7 @@ mod tests {
     #[should_panic]
     fn test_getting_neighbors_bad_input() {
         let mut ms = MineSweeper::new(5, 5, 0);
-        ms.game_state[4] = MINE_COVERED;
+        ms.game_state[4] = Cell::MineCovered;
         assert_eq!(0, ms.get_mine_neighbor_count(15, 98));
     }
 
@@ -348,7 +810,7 @@ mod tests {
     #[should_panic]
     fn test_getting_neighbors_bad_input_2() {
         let mut ms = MineSweeper::new(5, 5, 0);
-        ms.game_state[4] = MINE_COVERED;
+        ms.game_state[4] = Cell::MineCovered;
         assert_eq!(1, ms.get_mine_neighbor_count(5, 0));
     }
 
@@ -361,16 +823,16 @@ mod tests {
     #[test]
     fn test_get_hidden_bombs() {
         let mut ms = MineSweeper::new(5, 5, 0);
-        ms.game_state[1] = MINE_COVERED;
+        ms.game_state[1] = Cell::MineCovered;
         assert_eq!(1, ms.get_hidden_bombs());
     }
 
     #[test]
     fn test_get_hidden_bombs_2() {
         let mut ms = MineSweeper::new(5, 5, 0);
-        ms.game_state[1] = MINE_COVERED;
-        ms.game_state[2] = MINE_COVERED;
-        ms.game_state[0] = MINE_COVERED;
+        ms.game_state[1] = Cell::MineCovered;
+        ms.game_state[2] = Cell::MineCovered;
+        ms.game_state[0] = Cell::MineCovered;
         ms.click(0, 0);
         assert_eq!(2, ms.get_hidden_bombs());
         assert_eq!(3, ms.get_total_bombs());
@@ -379,7 +841,7 @@ mod tests {
     #[test]
     fn test_uncover_neighbors_1() {
         let mut ms = MineSweeper::new(5, 5, 0);
-        ms.game_state[0] = MINE_COVERED;
+        ms.game_state[0] = Cell::MineCovered;
         ms.click(4, 4);
 
         assert_eq!(ms.game_state[ms.get_idx(4, 4)], EMPTY);
@@ -400,20 +862,20 @@ mod tests {
         assert_eq!(ms.game_state[ms.get_idx(4, 1)], EMPTY);
         assert_eq!(ms.game_state[ms.get_idx(3, 1)], EMPTY);
         assert_eq!(ms.game_state[ms.get_idx(2, 1)], EMPTY);
-        assert_eq!(ms.game_state[ms.get_idx(1, 1)], 1);
-        assert_eq!(ms.game_state[ms.get_idx(0, 1)], 1);
+        assert_eq!(ms.game_state[ms.get_idx(1, 1)], Cell::uncovered(1));
+        assert_eq!(ms.game_state[ms.get_idx(0, 1)], Cell::uncovered(1));
         assert_eq!(ms.game_state[ms.get_idx(4, 0)], EMPTY);
         assert_eq!(ms.game_state[ms.get_idx(3, 0)], EMPTY);
         assert_eq!(ms.game_state[ms.get_idx(2, 0)], EMPTY);
-        assert_eq!(ms.game_state[ms.get_idx(1, 0)], 1);
-        assert_eq!(ms.game_state[ms.get_idx(0, 0)], MINE_COVERED);
+        assert_eq!(ms.game_state[ms.get_idx(1, 0)], Cell::uncovered(1));
+        assert_eq!(ms.game_state[ms.get_idx(0, 0)], Cell::MineCovered);
     }
 
     #[test]
     fn test_uncover_neighbors_2() {
         let mut ms = MineSweeper::new(5, 5, 0);
-        ms.game_state[0] = MINE_COVERED;
-        ms.game_state[1] = MINE_COVERED;
+        ms.game_state[0] = Cell::MineCovered;
+        ms.game_state[1] = Cell::MineCovered;
         ms.click(4, 4);
 
         assert_eq!(ms.game_state[ms.get_idx(4, 4)], EMPTY);
@@ -433,13 +895,313 @@ mod tests {
         assert_eq!(ms.game_state[ms.get_idx(0, 2)], EMPTY);
         assert_eq!(ms.game_state[ms.get_idx(4, 1)], EMPTY);
         assert_eq!(ms.game_state[ms.get_idx(3, 1)], EMPTY);
-        assert_eq!(ms.game_state[ms.get_idx(2, 1)], 1);
-        assert_eq!(ms.game_state[ms.get_idx(1, 1)], 2);
-        assert_eq!(ms.game_state[ms.get_idx(0, 1)], 2);
+        assert_eq!(ms.game_state[ms.get_idx(2, 1)], Cell::uncovered(1));
+        assert_eq!(ms.game_state[ms.get_idx(1, 1)], Cell::uncovered(2));
+        assert_eq!(ms.game_state[ms.get_idx(0, 1)], Cell::uncovered(2));
         assert_eq!(ms.game_state[ms.get_idx(4, 0)], EMPTY);
         assert_eq!(ms.game_state[ms.get_idx(3, 0)], EMPTY);
-        assert_eq!(ms.game_state[ms.get_idx(2, 0)], 1);
-        assert_eq!(ms.game_state[ms.get_idx(1, 0)], MINE_COVERED);
-        assert_eq!(ms.game_state[ms.get_idx(0, 0)], MINE_COVERED);
+        assert_eq!(ms.game_state[ms.get_idx(2, 0)], Cell::uncovered(1));
+        assert_eq!(ms.game_state[ms.get_idx(1, 0)], Cell::MineCovered);
+        assert_eq!(ms.game_state[ms.get_idx(0, 0)], Cell::MineCovered);
+    }
+
+    #[test]
+    fn first_click_is_always_safe() {
+        for _ in 0..50 {
+            let mut ms = MineSweeper::with_generation(9, 9, 10, Generation::FirstClickSafe);
+            assert_eq!(ms.get_total_bombs(), 10);
+            assert!(!ms.click(0, 0).is_empty());
+            assert_eq!(ms.game_status(), GameStatus::Playing);
+            assert_eq!(ms.game_state[0], EMPTY);
+            assert_eq!(ms.get_total_bombs(), 10);
+        }
+    }
+
+    #[test]
+    fn crowded_board_still_keeps_the_first_click_safe() {
+        let mut ms = MineSweeper::with_generation(3, 3, 8, Generation::FirstClickSafe);
+        // the only safe spot, so this click also wins
+        assert_eq!(ms.click(1, 1), vec![ms.get_idx(1, 1)]);
+        assert_eq!(ms.game_state[ms.get_idx(1, 1)], Cell::uncovered(8));
+        assert_eq!(ms.game_status(), GameStatus::Won);
+    }
+
+    #[test]
+    fn no_guess_boards_are_solvable() {
+        for _ in 0..20 {
+            let mut ms = MineSweeper::with_generation(9, 9, 10, Generation::NoGuess);
+            ms.click(4, 4);
+            // the same mines with everything covered back up
+            let mut fresh = ms.clone();
+            for spot in fresh.game_state.iter_mut() {
+                if spot.number().is_some() {
+                    *spot = Cell::NoMineCovered;
+                }
+            }
+            assert!(solver::clears_without_guessing(&fresh, 4 * 9 + 4), "{}", ms);
+        }
+    }
+
+    #[test]
+    fn same_seed_same_board() {
+        let a = MineSweeper::with_seed(30, 16, 99, 2024);
+        let b = MineSweeper::with_seed(30, 16, 99, 2024);
+        assert_eq!(a.game_state, b.game_state);
+        assert_ne!(
+            a.game_state,
+            MineSweeper::with_seed(30, 16, 99, 2025).game_state
+        );
+        assert_eq!(a.seed(), 2024);
+    }
+
+    #[test]
+    fn seeded_first_click_boards_repeat() {
+        let mut a = MineSweeper::with_seed_and_generation(9, 9, 10, 5, Generation::NoGuess);
+        let mut b = MineSweeper::with_seed_and_generation(9, 9, 10, 5, Generation::NoGuess);
+        a.click(3, 3);
+        b.click(3, 3);
+        assert_eq!(a.game_state, b.game_state);
+    }
+
+    #[test]
+    fn board_code_round_trips() {
+        let ms = MineSweeper::with_seed(16, 16, 40, 11);
+        let code = ms.board_code();
+        let shared = MineSweeper::from_board_code(&code).unwrap();
+        assert_eq!(shared.game_state, ms.game_state);
+        assert_eq!(shared.get_total_bombs(), 40);
+        assert_eq!(shared.board_code(), code);
+
+        let unplaced = MineSweeper::with_generation(9, 9, 10, Generation::FirstClickSafe);
+        assert_eq!(unplaced.board_code(), "");
+        assert!(MineSweeper::from_board_code("1x1-g").is_err());
+    }
+
+    /// a 3x3 board with one mine in the top left corner
+    fn corner_mine() -> MineSweeper {
+        let mut ms = MineSweeper::new(3, 3, 0);
+        ms.game_state[0] = Cell::MineCovered;
+        ms.mine_count = 1;
+        ms
+    }
+
+    #[test]
+    fn flags_cycle_through_question_marks() {
+        let mut ms = corner_mine();
+        assert!(ms.toggle_flag(0, 0));
+        assert_eq!(ms.game_state[0], Cell::MineFlagged);
+        assert_eq!(ms.get_flag_count(), 1);
+        assert!(ms.toggle_flag(0, 0));
+        assert_eq!(ms.game_state[0], Cell::MineQuestioned);
+        assert_eq!(ms.get_flag_count(), 0);
+        assert!(ms.toggle_flag(0, 0));
+        assert_eq!(ms.game_state[0], Cell::MineCovered);
+
+        ms.set_question_marks(false);
+        ms.toggle_flag(1, 0);
+        ms.toggle_flag(1, 0);
+        assert_eq!(ms.game_state[1], Cell::NoMineCovered);
+    }
+
+    #[test]
+    fn cannot_flag_uncovered_spots() {
+        let mut ms = corner_mine();
+        ms.click(1, 1);
+        assert!(!ms.toggle_flag(1, 1));
+        assert_eq!(ms.game_state[ms.get_idx(1, 1)], Cell::uncovered(1));
+    }
+
+    #[test]
+    fn question_marks_do_not_stop_clicks() {
+        let mut ms = corner_mine();
+        ms.toggle_flag(0, 0);
+        ms.toggle_flag(0, 0);
+        assert_eq!(ms.click(0, 0), vec![0]);
+        assert_eq!(ms.game_status(), GameStatus::Lost);
+    }
+
+    #[test]
+    fn chording_uncovers_the_rest() {
+        let mut ms = corner_mine();
+        ms.click(1, 1);
+        // no flag yet, so nothing happens
+        assert!(ms.click(1, 1).is_empty());
+        assert_eq!(ms.game_state[1], Cell::NoMineCovered);
+
+        ms.toggle_flag(0, 0);
+        let mut changed = ms.click(1, 1);
+        changed.sort_unstable();
+        assert_eq!(changed, vec![1, 2, 3, 5, 6, 7, 8]);
+        assert_eq!(ms.game_status(), GameStatus::Won);
+        assert_eq!(ms.game_state[ms.get_idx(2, 2)], EMPTY);
+        assert_eq!(ms.game_state[0], Cell::MineFlagged);
+    }
+
+    #[test]
+    fn chording_a_wrong_flag_explodes() {
+        let mut ms = corner_mine();
+        ms.click(1, 1);
+        ms.toggle_flag(1, 0);
+        assert!(ms.chord(1, 1).contains(&0));
+        assert_eq!(ms.game_status(), GameStatus::Lost);
+    }
+
+    #[test]
+    fn nothing_happens_after_the_game_ends() {
+        let mut ms = corner_mine();
+        ms.click(0, 0);
+        assert_eq!(ms.game_status(), GameStatus::Lost);
+        let before = ms.game_state.clone();
+        assert!(ms.click(2, 2).is_empty());
+        assert!(!ms.toggle_flag(2, 2));
+        assert_eq!(ms.game_state, before);
+    }
+
+    #[test]
+    fn click_reports_every_uncovered_spot() {
+        let mut ms = corner_mine();
+        let mut changed = ms.click(2, 2);
+        changed.sort_unstable();
+        // everything but the mine, spreading out from the empty corner
+        assert_eq!(changed, (1..9).collect::<Vec<usize>>());
+        assert_eq!(ms.game_status(), GameStatus::Won);
+    }
+
+    #[test]
+    fn huge_empty_boards_do_not_overflow_the_stack() {
+        let mut ms = MineSweeper::with_seed(1000, 1000, 1, 3);
+        let mine = ms.game_state.iter().position(|s| s.is_mine()).unwrap();
+        let far = if mine < 500_000 { 999_999 } else { 0 };
+        let changed = ms.click(far % 1000, far / 1000);
+        assert_eq!(changed.len(), 999_999);
+        assert_eq!(ms.game_status(), GameStatus::Won);
+    }
+
+    #[test]
+    fn undo_and_redo_walk_the_history() {
+        let mut ms = corner_mine();
+        let covered = ms.game_state.clone();
+        ms.toggle_flag(0, 0);
+        let flagged = ms.game_state.clone();
+        ms.click(2, 2);
+        assert_eq!(ms.history_len(), 2);
+        assert_eq!(ms.game_status(), GameStatus::Won);
+        let won = ms.game_state.clone();
+
+        assert_eq!(ms.undo().len(), 8);
+        assert_eq!(ms.game_state, flagged);
+        assert_eq!(ms.undo(), vec![0]);
+        assert_eq!(ms.game_state, covered);
+        assert!(ms.undo().is_empty());
+        assert_eq!(ms.history_len(), 0);
+
+        ms.redo();
+        ms.redo();
+        assert_eq!(ms.game_state, won);
+        assert!(ms.redo().is_empty());
+    }
+
+    #[test]
+    fn undo_takes_back_a_lost_game() {
+        let mut ms = corner_mine();
+        ms.click(0, 0);
+        assert_eq!(ms.game_status(), GameStatus::Lost);
+        ms.undo();
+        assert_eq!(ms.game_status(), GameStatus::Playing);
+        // a new move drops the undone one
+        ms.click(1, 1);
+        assert!(ms.redo().is_empty());
+        assert_eq!(ms.history_len(), 1);
+    }
+
+    #[test]
+    fn replays_step_through_the_same_game() {
+        let mut ms = MineSweeper::with_seed_and_generation(9, 9, 10, 7, Generation::FirstClickSafe);
+        assert_eq!(ms.replay(), "");
+        ms.click(4, 4);
+        ms.toggle_flag(0, 0);
+        ms.toggle_flag(0, 0);
+        solver::autoplay(&mut ms);
+
+        let mut replay = Replay::from_text(&ms.replay()).unwrap();
+        assert_eq!(replay.len(), ms.history_len());
+        while replay.position() < replay.len() {
+            replay.step();
+        }
+        assert_eq!(replay.game().game_state, ms.game_state);
+        replay.step_back();
+        replay.step_back();
+        assert_eq!(replay.position(), replay.len() - 2);
+        assert_eq!(replay.game().history_len(), ms.history_len() - 2);
+    }
+
+    #[test]
+    fn numbers_follow_the_topology() {
+        let mut torus =
+            MineSweeper::with_topology(4, 4, 0, 0, Generation::Immediate, &Topology::torus());
+        torus.game_state[15] = Cell::MineCovered;
+        // the bottom right corner wraps around to touch the top left
+        assert_eq!(torus.get_mine_neighbor_count(0, 0), 1);
+
+        let mut knight =
+            MineSweeper::with_topology(3, 3, 0, 0, Generation::Immediate, &Topology::knight());
+        knight.game_state[5] = Cell::MineCovered;
+        assert_eq!(knight.get_mine_neighbor_count(0, 0), 1);
+        assert_eq!(knight.get_mine_neighbor_count(1, 1), 0);
+    }
+
+    #[test]
+    fn every_topology_can_be_played_out() {
+        for name in ["torus", "hex", "knight", "custom-wrap:1,0;-1,0;0,1;0,-1"] {
+            let topology: Topology = name.parse().unwrap();
+            for seed in 0..10 {
+                let mut ms = MineSweeper::with_topology(
+                    12,
+                    10,
+                    15,
+                    seed,
+                    Generation::FirstClickSafe,
+                    &topology,
+                );
+                solver::autoplay(&mut ms);
+                assert_ne!(ms.game_status(), GameStatus::Playing);
+
+                let copy = MineSweeper::from_board_code(&ms.board_code()).unwrap();
+                assert_eq!(copy.topology(), topology);
+                assert_eq!(copy.board_code(), ms.board_code());
+            }
+        }
+    }
+
+    #[test]
+    fn undoing_an_early_flag_keeps_the_mine_under_it() {
+        let mut ms = MineSweeper::with_seed_and_generation(3, 3, 8, 1, Generation::FirstClickSafe);
+        ms.toggle_flag(0, 0);
+        ms.click(1, 1);
+        assert_eq!(ms.game_state[0], Cell::MineFlagged);
+        ms.undo();
+        ms.undo();
+        assert_eq!(ms.game_state[0], Cell::MineCovered);
+        ms.redo();
+        assert_eq!(ms.game_state[0], Cell::MineFlagged);
+        assert_eq!(ms.get_total_bombs(), 8);
+    }
+
+    #[test]
+    fn moves_off_the_board_do_nothing() {
+        let mut ms = corner_mine();
+        assert!(ms.click(15, 98).is_empty());
+        // would wrap around onto the next row if it weren't checked
+        assert!(ms.click(3, 0).is_empty());
+        assert!(ms.chord(0, 3).is_empty());
+        assert!(!ms.toggle_flag(usize::MAX, 0));
+        assert!(!ms.place_flag(3, 3));
+        assert_eq!(ms.history_len(), 0);
+    }
+
+    #[test]
+    fn status_before_the_first_click() {
+        let ms = MineSweeper::with_generation(9, 9, 10, Generation::FirstClickSafe);
+        assert_eq!(ms.game_status(), GameStatus::Playing);
     }
 }
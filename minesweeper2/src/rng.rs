@@ -0,0 +1,65 @@
+/// A small seeded random number generator (SplitMix64). It only does integer math, so the same
+/// seed gives the same numbers on wasm and natively, unlike `js_sys::Math::random` or `thread_rng`.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// a number from `bottom` up to but not including `top`
+    pub fn range(&mut self, bottom: usize, top: usize) -> usize {
+        let span = (top - bottom) as u128;
+        bottom + ((self.next_u64() as u128 * span) >> 64) as usize
+    }
+}
+
+/// this compiles only if compiling on the web and gets the js-sys crate for random number generation on the browswer
+#[cfg(target_arch = "wasm32")]
+pub fn random_seed() -> u64 {
+    use js_sys::Math::random;
+    // two draws since one f64 only has 53 bits of randomness
+    ((random() * u32::MAX as f64) as u64) << 32 | (random() * u32::MAX as f64) as u64
+}
+
+/// this compiles when not compiling to the web. This makes the game still playable on the cli
+#[cfg(not(target_arch = "wasm32"))]
+pub fn random_seed() -> u64 {
+    rand::random()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_numbers() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        for _ in 0..100 {
+            assert_eq!(a.range(0, 81), b.range(0, 81));
+        }
+        // pinned so a change to the generator, which would change every shared board, is noticed
+        assert_eq!(Rng::new(0).next_u64(), 0xE220_A839_7B1D_CDAF);
+    }
+
+    #[test]
+    fn range_stays_in_bounds() {
+        let mut rng = Rng::new(7);
+        for _ in 0..1000 {
+            let n = rng.range(3, 10);
+            assert!((3..10).contains(&n));
+        }
+    }
+}
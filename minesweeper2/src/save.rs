@@ -0,0 +1,359 @@
+//! Saving a game part way through and picking it back up later, as JSON or as compact bytes.
+//!
+//! The page keeps the JSON in localStorage and the terminal game writes it to a file. The bytes
+//! are for anywhere size matters, a 30x16 expert board takes about 250 of them:
+//!
+//! - `MSW` then a version byte, currently 1
+//! - width, height, mine count and elapsed milliseconds as LEB128 varints
+//! - the seed as 8 little endian bytes
+//! - the generation and whether question marks are on, one byte each
+//! - the topology name's length as a varint, then the name
+//! - the state codes, two per byte with the first in the high 4 bits
+//!
+//! Both keep the state codes of `cell`, which all fit in 4 bits as of layout version 2.
+//!
+//! Anything loaded is checked before it is trusted: the size has to match the state, every
+//! code has to be a real one and every revealed number has to match the mines around it.
+//! The move history isn't saved, so a loaded game starts with nothing to undo.
+
+use crate::cell::Cell;
+use crate::minesweeper2::{Generation, MineSweeper};
+use crate::topology::Topology;
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use wasm_bindgen::prelude::*;
+
+const MAGIC: &[u8; 3] = b"MSW";
+const VERSION: u8 = 1;
+
+/// A game frozen part way through
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SavedGame {
+    width: usize,
+    height: usize,
+    mine_count: usize,
+    seed: u64,
+    /// how long the game has been going, kept by the front end
+    elapsed_ms: u64,
+    generation: Generation,
+    question_marks: bool,
+    topology: String,
+    /// state codes, see `cell`
+    game_state: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl SavedGame {
+    /// Freezes `game`. The engine doesn't keep time, so the front end passes in how long
+    /// the game has been going.
+    pub fn new(game: &MineSweeper, elapsed_ms: u64) -> SavedGame {
+        SavedGame {
+            width: game.width(),
+            height: game.height(),
+            mine_count: game.get_total_bombs(),
+            seed: game.seed(),
+            elapsed_ms,
+            generation: game.generation(),
+            question_marks: game.question_marks(),
+            topology: game.topology().name(),
+            game_state: game.game_state().iter().map(|c| u8::from(*c)).collect(),
+        }
+    }
+
+    /// The game ready to carry on playing
+    pub fn game(&self) -> MineSweeper {
+        self.restore()
+            .expect("saved games are checked when they are made or loaded")
+    }
+
+    pub fn elapsed_ms(&self) -> u64 {
+        self.elapsed_ms
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("a saved game is always valid JSON")
+    }
+
+    pub fn from_json(json: &str) -> Result<SavedGame, String> {
+        let saved: SavedGame =
+            serde_json::from_str(json).map_err(|e| format!("Can't read saved game: {}", e))?;
+        saved.restore()?;
+        Ok(saved)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        for number in [
+            self.width as u64,
+            self.height as u64,
+            self.mine_count as u64,
+            self.elapsed_ms,
+        ] {
+            push_varint(&mut bytes, number);
+        }
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.push(self.generation as u8);
+        bytes.push(self.question_marks as u8);
+        push_varint(&mut bytes, self.topology.len() as u64);
+        bytes.extend_from_slice(self.topology.as_bytes());
+        for pair in self.game_state.chunks(2) {
+            bytes.push(pair[0] << 4 | pair.get(1).copied().unwrap_or(0));
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<SavedGame, String> {
+        let mut reader = Reader { bytes, at: 0 };
+        if reader.take(3)? != MAGIC {
+            return Err(String::from("Not a saved game"));
+        }
+        let version = reader.take(1)?[0];
+        if version != VERSION {
+            return Err(format!("Can't read saved games from version {}", version));
+        }
+        let width = reader.size()?;
+        let height = reader.size()?;
+        let mine_count = reader.size()?;
+        let elapsed_ms = reader.varint()?;
+        let mut seed = [0; 8];
+        seed.copy_from_slice(reader.take(8)?);
+        let generation = match reader.take(1)?[0] {
+            0 => Generation::Immediate,
+            1 => Generation::FirstClickSafe,
+            2 => Generation::NoGuess,
+            other => return Err(format!("{} is not a generation", other)),
+        };
+        let question_marks = match reader.take(1)?[0] {
+            0 => false,
+            1 => true,
+            other => return Err(format!("{} is not true or false", other)),
+        };
+        let name_len = reader.size()?;
+        let topology = String::from_utf8(reader.take(name_len)?.to_vec())
+            .map_err(|_| String::from("Topology name is not text"))?;
+        let spots = width
+            .checked_mul(height)
+            .ok_or_else(|| String::from("Board is too big"))?;
+        let packed = reader.take(spots.div_ceil(2))?;
+        if reader.at != bytes.len() {
+            return Err(String::from("Extra bytes after the saved game"));
+        }
+        let game_state = packed
+            .iter()
+            .flat_map(|byte| [byte >> 4, byte & 0xF])
+            .take(spots)
+            .collect();
+        let saved = SavedGame {
+            width,
+            height,
+            mine_count,
+            seed: u64::from_le_bytes(seed),
+            elapsed_ms,
+            generation,
+            question_marks,
+            topology,
+            game_state,
+        };
+        saved.restore()?;
+        Ok(saved)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl SavedGame {
+    /// Writes the game to `path` as JSON
+    pub fn save_to_file(&self, path: &std::path::Path) -> Result<(), String> {
+        std::fs::write(path, self.to_json())
+            .map_err(|e| format!("Can't save to {}: {}", path.display(), e))
+    }
+
+    pub fn load_from_file(path: &std::path::Path) -> Result<SavedGame, String> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| format!("Can't load {}: {}", path.display(), e))?;
+        SavedGame::from_json(&json)
+    }
+}
+
+impl SavedGame {
+    /// Checks everything and builds the game
+    fn restore(&self) -> Result<MineSweeper, String> {
+        let spots = self
+            .width
+            .checked_mul(self.height)
+            .ok_or_else(|| String::from("Board is too big"))?;
+        if spots == 0 {
+            return Err(String::from("Cannot have a height or width of zero"));
+        }
+        if self.game_state.len() != spots {
+            return Err(format!(
+                "A {}x{} board has {} spots, not {}",
+                self.width,
+                self.height,
+                spots,
+                self.game_state.len()
+            ));
+        }
+        if self.mine_count >= spots {
+            return Err(String::from("Cannot have more mines than spaces"));
+        }
+        let cells = self
+            .game_state
+            .iter()
+            .map(|code| Cell::try_from(*code))
+            .collect::<Result<Vec<Cell>, String>>()?;
+        let topology: Topology = self.topology.parse()?;
+        let mines = cells.iter().filter(|c| c.is_mine()).count();
+        if mines != 0 && mines != self.mine_count {
+            return Err(format!("Says {} mines but has {}", self.mine_count, mines));
+        }
+        // the mines go down on the first click, so nothing can be uncovered before then
+        if mines == 0 && self.mine_count > 0 && cells.iter().any(|c| !c.is_covered()) {
+            return Err(String::from("Spots are uncovered but no mines are placed"));
+        }
+        let game = MineSweeper::restore(
+            self.width,
+            self.height,
+            self.mine_count,
+            self.seed,
+            self.generation,
+            &topology,
+            self.question_marks,
+            cells.clone(),
+        );
+        for (idx, cell) in cells.iter().enumerate() {
+            let number = match cell.number() {
+                Some(number) => number,
+                None => continue,
+            };
+            let around = game
+                .neighbors(idx)
+                .iter()
+                .filter(|n| cells[**n].is_mine())
+                .count();
+            if around != number as usize {
+                return Err(format!(
+                    "Spot {} shows {} but has {} mines around it",
+                    idx, number, around
+                ));
+            }
+        }
+        Ok(game)
+    }
+}
+
+fn push_varint(bytes: &mut Vec<u8>, mut number: u64) {
+    while number >= 0x80 {
+        bytes.push(number as u8 | 0x80);
+        number >>= 7;
+    }
+    bytes.push(number as u8);
+}
+
+/// reads through the bytes of a saved game, erroring instead of running off the end
+struct Reader<'a> {
+    bytes: &'a [u8],
+    at: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .at
+            .checked_add(count)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| String::from("Saved game is cut short"))?;
+        let taken = &self.bytes[self.at..end];
+        self.at = end;
+        Ok(taken)
+    }
+
+    fn varint(&mut self) -> Result<u64, String> {
+        let mut number = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.take(1)?[0];
+            number |= ((byte & 0x7F) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(number);
+            }
+        }
+        Err(String::from("Number in saved game is too long"))
+    }
+
+    fn size(&mut self) -> Result<usize, String> {
+        let number = self.varint()?;
+        usize::try_from(number).map_err(|_| format!("{} is too big", number))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn played() -> MineSweeper {
+        let mut game =
+            MineSweeper::with_seed_and_generation(9, 9, 10, 3, Generation::FirstClickSafe);
+        game.click(4, 4);
+        game.toggle_flag(0, 0);
+        game
+    }
+
+    #[test]
+    fn json_and_bytes_round_trip() {
+        let saved = SavedGame::new(&played(), 12_345);
+        let from_json = SavedGame::from_json(&saved.to_json()).unwrap();
+        assert_eq!(from_json, saved);
+        let bytes = saved.to_bytes();
+        assert_eq!(bytes.len(), 4 + 5 + 8 + 2 + 7 + 41);
+        let from_bytes = SavedGame::from_bytes(&bytes).unwrap();
+        assert_eq!(from_bytes, saved);
+        assert_eq!(from_bytes.elapsed_ms(), 12_345);
+        assert_eq!(from_bytes.game().game_state(), played().game_state());
+        assert_eq!(saved.game_state[40], Cell::Empty as u8);
+    }
+
+    #[test]
+    fn games_carry_on_after_loading() {
+        let mut game = MineSweeper::with_seed_and_generation(9, 9, 10, 8, Generation::NoGuess);
+        let fresh = SavedGame::new(&game, 0).game();
+        // not clicked yet, so the mines come from the seed like they would have
+        let mut copy = fresh.clone();
+        game.click(2, 2);
+        copy.click(2, 2);
+        assert_eq!(copy.board_code(), game.board_code());
+        crate::solver::autoplay(&mut copy);
+        assert_ne!(copy.game_status(), crate::minesweeper2::GameStatus::Playing);
+    }
+
+    #[test]
+    fn rejects_tampered_saves() {
+        let saved = SavedGame::new(&played(), 0);
+        let mut json: serde_json::Value = serde_json::from_str(&saved.to_json()).unwrap();
+        json["width"] = 8.into();
+        assert!(SavedGame::from_json(&json.to_string()).is_err());
+
+        let mut bad_code = saved.clone();
+        bad_code.game_state[5] = 16;
+        assert!(SavedGame::from_json(&bad_code.to_json()).is_err());
+
+        let mut wrong_number = saved.clone();
+        let revealed = wrong_number
+            .game_state
+            .iter()
+            .position(|s| *s <= 8)
+            .unwrap();
+        wrong_number.game_state[revealed] = 8;
+        assert!(SavedGame::from_json(&wrong_number.to_json()).is_err());
+
+        let mut extra_mine = saved.clone();
+        let covered = extra_mine.game_state.iter().position(|s| *s == 9).unwrap();
+        extra_mine.game_state[covered] = 10;
+        assert!(SavedGame::from_json(&extra_mine.to_json()).is_err());
+
+        let bytes = saved.to_bytes();
+        assert!(SavedGame::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(SavedGame::from_bytes(b"nope").is_err());
+        assert!(SavedGame::from_json("{").is_err());
+    }
+}
@@ -0,0 +1,702 @@
+/// A logical solver that only looks at what the player can see: the revealed numbers, which
+/// spots are still covered and the total mine count. It never peeks at where the mines are.
+///
+/// Every revealed number gives a constraint: its covered neighbors hold exactly that many mines,
+/// less the ones already known. From those it finds
+/// - spots that must be safe or must be mines from a single constraint
+/// - more of them by comparing constraints where one's spots are all inside another's
+/// - and when nothing is certain, the exact chance of a mine under every covered spot, by
+///   counting every layout of mines along the edge of the uncovered area that fits the numbers
+use crate::minesweeper2::{GameStatus, Generation, MineSweeper};
+use std::collections::{HashMap, HashSet};
+use wasm_bindgen::prelude::*;
+
+/// how many steps the layout counting may take before settling for an estimate
+const MAX_SEARCH_STEPS: usize = 2_000_000;
+
+/// Spots the solver has proven, as indexes into the game state
+#[derive(Debug, Default, PartialEq)]
+pub struct Deductions {
+    pub safe: Vec<usize>,
+    pub mines: Vec<usize>,
+}
+
+impl Deductions {
+    pub fn is_empty(&self) -> bool {
+        self.safe.is_empty() && self.mines.is_empty()
+    }
+}
+
+/// `cells` hold exactly `mines` mines
+#[derive(Debug)]
+struct Constraint {
+    cells: Vec<usize>,
+    mines: usize,
+}
+
+/// one constraint for every revealed number that still has covered neighbors
+fn constraints(game: &MineSweeper, known_mines: &[bool]) -> Vec<Constraint> {
+    let state = game.game_state();
+    let mut constraints = Vec::new();
+    for (idx, &spot) in state.iter().enumerate() {
+        // flags are only the player's guess, so flagged spots count as covered here
+        let number = match spot.number() {
+            Some(number) => number,
+            None => continue,
+        };
+        let mut cells = Vec::new();
+        let mut found = 0;
+        for &neighbor in game.neighbors(idx).iter() {
+            if known_mines[neighbor] {
+                found += 1;
+            } else if state[neighbor].is_covered() {
+                cells.push(neighbor);
+            }
+        }
+        if !cells.is_empty() {
+            cells.sort_unstable();
+            constraints.push(Constraint {
+                cells,
+                mines: (number as usize).saturating_sub(found),
+            });
+        }
+    }
+    constraints
+}
+
+/// covered spots that aren't already known to be mines
+fn unknown_spots(game: &MineSweeper, known_mines: &[bool]) -> Vec<usize> {
+    let state = game.game_state();
+    (0..state.len())
+        .filter(|&idx| state[idx].is_covered() && !known_mines[idx])
+        .collect()
+}
+
+/// mines not yet known, or `None` if more are known than the board has
+fn remaining_mines(game: &MineSweeper, known_mines: &[bool]) -> Option<usize> {
+    let known = known_mines.iter().filter(|m| **m).count();
+    game.get_total_bombs().checked_sub(known)
+}
+
+/// Everything that can be proven from the current board. `known_mines` marks spots already
+/// proven to be mines, which are left out of the answer.
+pub fn deduce(game: &MineSweeper, known_mines: &[bool]) -> Deductions {
+    let constraints = constraints(game, known_mines);
+
+    let mut safe = HashSet::new();
+    let mut mines = HashSet::new();
+    let mut settle = |cells: &[usize], count: usize| {
+        if count == 0 {
+            safe.extend(cells.iter().copied());
+        } else if count == cells.len() {
+            mines.extend(cells.iter().copied());
+        }
+    };
+
+    for constraint in constraints.iter() {
+        settle(&constraint.cells, constraint.mines);
+    }
+
+    // If one constraint's spots all sit inside another's, the spots left over
+    // hold the difference in mines.
+    let mut touching: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (i, constraint) in constraints.iter().enumerate() {
+        for cell in constraint.cells.iter() {
+            touching.entry(*cell).or_default().push(i);
+        }
+    }
+    for (i, small) in constraints.iter().enumerate() {
+        let mut checked = HashSet::new();
+        for j in small.cells.iter().flat_map(|cell| touching[cell].iter()) {
+            if *j == i || !checked.insert(*j) {
+                continue;
+            }
+            let big = &constraints[*j];
+            if big.cells.len() <= small.cells.len()
+                || big.mines < small.mines
+                || !small
+                    .cells
+                    .iter()
+                    .all(|c| big.cells.binary_search(c).is_ok())
+            {
+                continue;
+            }
+            let rest: Vec<usize> = big
+                .cells
+                .iter()
+                .copied()
+                .filter(|c| small.cells.binary_search(c).is_err())
+                .collect();
+            settle(&rest, big.mines - small.mines);
+        }
+    }
+
+    // the mine counter is a constraint over every covered spot
+    if let Some(remaining) = remaining_mines(game, known_mines) {
+        settle(&unknown_spots(game, known_mines), remaining);
+    }
+
+    let mut safe: Vec<usize> = safe.into_iter().collect();
+    let mut mines: Vec<usize> = mines.into_iter().collect();
+    safe.sort_unstable();
+    mines.sort_unstable();
+    Deductions { safe, mines }
+}
+
+/// Calls `deduce` over and over, counting each round's mines as known, until it finds
+/// no new mines. Returns the known mines and the safe spots from the last round.
+pub fn deduce_all(game: &MineSweeper) -> (Vec<bool>, Vec<usize>) {
+    let mut known_mines = vec![false; game.game_state().len()];
+    loop {
+        let deductions = deduce(game, &known_mines);
+        let mut progress = false;
+        for idx in deductions.mines {
+            progress |= !known_mines[idx];
+            known_mines[idx] = true;
+        }
+        if !progress {
+            return (known_mines, deductions.safe);
+        }
+    }
+}
+
+/// The layouts of one group of edge spots that share numbers, counted by how many mines they use
+struct Component {
+    cells: Vec<usize>,
+    /// `layouts[k]` is how many layouts put exactly `k` mines in the group
+    layouts: Vec<f64>,
+    /// `mine_counts[k][i]` is how many of those put a mine on `cells[i]`
+    mine_counts: Vec<Vec<f64>>,
+}
+
+/// A depth first walk over every mine / no mine choice for the spots of one component
+struct Search {
+    /// for every constraint, its spots (as positions in the component) and mine count
+    constraints: Vec<(Vec<usize>, usize)>,
+    /// which constraints each spot is in
+    cell_constraints: Vec<Vec<usize>>,
+    placed: Vec<usize>,
+    open: Vec<usize>,
+    mine: Vec<bool>,
+    layouts: Vec<f64>,
+    mine_counts: Vec<Vec<f64>>,
+    steps: usize,
+}
+
+impl Search {
+    /// false if it ran out of steps
+    fn run(&mut self, i: usize, mines: usize) -> bool {
+        if self.steps == 0 {
+            return false;
+        }
+        self.steps -= 1;
+        if i == self.mine.len() {
+            self.layouts[mines] += 1.0;
+            for (j, mine) in self.mine.iter().enumerate() {
+                if *mine {
+                    self.mine_counts[mines][j] += 1.0;
+                }
+            }
+            return true;
+        }
+        for value in [false, true] {
+            let add = value as usize;
+            let fits = self.cell_constraints[i].iter().all(|&c| {
+                // after this spot there must still be room for exactly the right count
+                let placed = self.placed[c] + add;
+                let mines = self.constraints[c].1;
+                placed <= mines && placed + self.open[c] > mines
+            });
+            if !fits {
+                continue;
+            }
+            for &c in self.cell_constraints[i].iter() {
+                self.placed[c] += add;
+                self.open[c] -= 1;
+            }
+            self.mine[i] = value;
+            let finished = self.run(i + 1, mines + add);
+            for &c in self.cell_constraints[i].iter() {
+                self.placed[c] -= add;
+                self.open[c] += 1;
+            }
+            if !finished {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// splits the constraints into groups that share no spots
+fn split(constraints: &[Constraint]) -> Vec<(Vec<usize>, Vec<usize>)> {
+    let mut group_of: HashMap<usize, usize> = HashMap::new();
+    let mut groups: Vec<(Vec<usize>, Vec<usize>)> = Vec::new();
+    let mut seen = vec![false; constraints.len()];
+    for start in 0..constraints.len() {
+        if seen[start] {
+            continue;
+        }
+        let id = groups.len();
+        let mut group = (Vec::new(), Vec::new());
+        let mut queue = vec![start];
+        seen[start] = true;
+        while let Some(c) = queue.pop() {
+            group.1.push(c);
+            for cell in constraints[c].cells.iter() {
+                if group_of.insert(*cell, id).is_none() {
+                    group.0.push(*cell);
+                }
+            }
+            for (other, constraint) in constraints.iter().enumerate() {
+                if !seen[other]
+                    && constraint
+                        .cells
+                        .iter()
+                        .any(|cell| group_of.get(cell) == Some(&id))
+                {
+                    seen[other] = true;
+                    queue.push(other);
+                }
+            }
+        }
+        groups.push(group);
+    }
+    groups
+}
+
+fn enumerate(
+    cells: Vec<usize>,
+    ids: &[usize],
+    constraints: &[Constraint],
+    steps: &mut usize,
+) -> Option<Component> {
+    let position: HashMap<usize, usize> = cells.iter().enumerate().map(|(i, c)| (*c, i)).collect();
+    let mut search = Search {
+        constraints: ids
+            .iter()
+            .map(|&c| {
+                let local = constraints[c]
+                    .cells
+                    .iter()
+                    .map(|cell| position[cell])
+                    .collect();
+                (local, constraints[c].mines)
+            })
+            .collect(),
+        cell_constraints: vec![Vec::new(); cells.len()],
+        placed: vec![0; ids.len()],
+        open: ids.iter().map(|&c| constraints[c].cells.len()).collect(),
+        mine: vec![false; cells.len()],
+        layouts: vec![0.0; cells.len() + 1],
+        mine_counts: vec![vec![0.0; cells.len()]; cells.len() + 1],
+        steps: *steps,
+    };
+    for (c, (local, _)) in search.constraints.iter().enumerate() {
+        for cell in local {
+            search.cell_constraints[*cell].push(c);
+        }
+    }
+    let finished = search.run(0, 0);
+    *steps = search.steps;
+    if !finished {
+        return None;
+    }
+    // Only ratios matter, so scale down to keep the products of many groups from overflowing.
+    let biggest = search.layouts.iter().cloned().fold(0.0, f64::max);
+    if biggest > 0.0 {
+        for k in 0..search.layouts.len() {
+            search.layouts[k] /= biggest;
+            for count in search.mine_counts[k].iter_mut() {
+                *count /= biggest;
+            }
+        }
+    }
+    Some(Component {
+        cells,
+        layouts: search.layouts,
+        mine_counts: search.mine_counts,
+    })
+}
+
+fn convolve(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let mut out = vec![0.0; a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            out[i + j] += x * y;
+        }
+    }
+    out
+}
+
+/// natural log of n choose k
+fn ln_choose(n: usize, k: usize) -> f64 {
+    let k = k.min(n - k);
+    (1..=k).map(|i| ((n - k + i) as f64 / i as f64).ln()).sum()
+}
+
+/// The chance of a mine under every spot, from 0 to 1. Revealed spots are `None` and known
+/// mines are 1. Every layout of mines that fits the numbers and the mine count is equally
+/// likely, so a spot's chance is the share of layouts with a mine on it.
+///
+/// Spots along the edge of the uncovered area are counted exactly. The rest of the covered
+/// spots all share the same chance, from however many mines the edge leaves over. Returns
+/// `None` if no layout fits, which means the board contradicts itself.
+pub fn probabilities(game: &MineSweeper, known_mines: &[bool]) -> Option<Vec<Option<f64>>> {
+    let state = game.game_state();
+    let remaining = remaining_mines(game, known_mines)?;
+    let constraints = constraints(game, known_mines);
+    let mut chances: Vec<Option<f64>> = (0..state.len())
+        .map(|idx| if known_mines[idx] { Some(1.0) } else { None })
+        .collect();
+
+    let mut steps = MAX_SEARCH_STEPS;
+    let mut components = Vec::new();
+    let mut on_edge = vec![false; state.len()];
+    for (cells, ids) in split(&constraints) {
+        for cell in cells.iter() {
+            on_edge[*cell] = true;
+        }
+        match enumerate(cells, &ids, &constraints, &mut steps) {
+            Some(component) => components.push(component),
+            None => return Some(estimate(game, known_mines, &constraints)),
+        }
+    }
+    let interior: Vec<usize> = unknown_spots(game, known_mines)
+        .into_iter()
+        .filter(|idx| !on_edge[*idx])
+        .collect();
+
+    // weight of every total number of edge mines: how many ways the rest fit in the interior
+    let all = components
+        .iter()
+        .fold(vec![1.0], |acc, c| convolve(&acc, &c.layouts));
+    let ln_weights: Vec<Option<f64>> = (0..all.len() + interior.len().max(1))
+        .map(|t| {
+            let rest = remaining.checked_sub(t)?;
+            if rest <= interior.len() {
+                Some(ln_choose(interior.len(), rest))
+            } else {
+                None
+            }
+        })
+        .collect();
+    let top = (0..all.len())
+        .filter(|t| all[*t] > 0.0)
+        .filter_map(|t| ln_weights[t])
+        .fold(f64::NEG_INFINITY, f64::max);
+    let weight = |t: usize| {
+        ln_weights
+            .get(t)
+            .copied()
+            .flatten()
+            .map_or(0.0, |w| (w - top).exp())
+    };
+    let total: f64 = (0..all.len()).map(|t| all[t] * weight(t)).sum();
+    if total <= 0.0 {
+        return None;
+    }
+
+    for (c, component) in components.iter().enumerate() {
+        let others = components
+            .iter()
+            .enumerate()
+            .filter(|(other, _)| *other != c)
+            .fold(vec![1.0], |acc, (_, o)| convolve(&acc, &o.layouts));
+        for (i, cell) in component.cells.iter().enumerate() {
+            let mut mine = 0.0;
+            for (k, counts) in component.mine_counts.iter().enumerate() {
+                if counts[i] == 0.0 {
+                    continue;
+                }
+                let rest: f64 = others
+                    .iter()
+                    .enumerate()
+                    .map(|(t, ways)| ways * weight(k + t))
+                    .sum();
+                mine += counts[i] * rest;
+            }
+            chances[*cell] = Some(mine / total);
+        }
+    }
+    if !interior.is_empty() {
+        let expected: f64 = (0..all.len())
+            .map(|t| all[t] * weight(t) * remaining.saturating_sub(t) as f64)
+            .sum::<f64>()
+            / total;
+        let chance = expected / interior.len() as f64;
+        for idx in interior {
+            chances[idx] = Some(chance);
+        }
+    }
+    Some(chances)
+}
+
+/// A rough guess for boards with too many edge layouts to count: each edge spot gets the
+/// highest share of mines among its numbers, the rest share what is left evenly.
+fn estimate(
+    game: &MineSweeper,
+    known_mines: &[bool],
+    constraints: &[Constraint],
+) -> Vec<Option<f64>> {
+    let mut chances: Vec<Option<f64>> = known_mines
+        .iter()
+        .map(|known| if *known { Some(1.0) } else { None })
+        .collect();
+    for constraint in constraints {
+        let share = constraint.mines as f64 / constraint.cells.len() as f64;
+        for cell in constraint.cells.iter() {
+            chances[*cell] = Some(chances[*cell].map_or(share, |c| c.max(share)));
+        }
+    }
+    let unknown = unknown_spots(game, known_mines);
+    let edge_mines: f64 = unknown.iter().filter_map(|idx| chances[*idx]).sum();
+    let interior: Vec<usize> = unknown
+        .into_iter()
+        .filter(|idx| chances[*idx].is_none())
+        .collect();
+    let remaining = remaining_mines(game, known_mines).unwrap_or(0) as f64;
+    let chance = ((remaining - edge_mines) / interior.len().max(1) as f64).clamp(0.0, 1.0);
+    for idx in interior {
+        chances[idx] = Some(chance);
+    }
+    chances
+}
+
+/// A suggested next click
+#[wasm_bindgen]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Hint {
+    pub col: usize,
+    pub row: usize,
+    /// chance there is a mine under it. 0 means it is proven safe
+    pub probability: f64,
+}
+
+#[wasm_bindgen]
+impl Hint {
+    pub fn is_safe(&self) -> bool {
+        self.probability == 0.0
+    }
+}
+
+/// A spot proven safe if there is one, otherwise the covered spot least likely to be a mine.
+/// Flagged spots are never suggested. `None` once the game is over.
+pub fn hint(game: &MineSweeper) -> Option<Hint> {
+    if game.game_status() != GameStatus::Playing {
+        return None;
+    }
+    let width = game.width();
+    let at = |idx: usize, probability: f64| Hint {
+        col: idx % width,
+        row: idx / width,
+        probability,
+    };
+    let state = game.game_state();
+    if !game.mines_placed() {
+        // the middle opens the most on average, and is safe unless mines are already down
+        let middle = game.height() / 2 * width + width / 2;
+        let chance = match game.generation() {
+            Generation::Immediate => game.get_total_bombs() as f64 / state.len() as f64,
+            _ => 0.0,
+        };
+        return Some(at(middle, chance));
+    }
+
+    let (known_mines, safe) = deduce_all(game);
+    if let Some(idx) = safe.iter().find(|idx| !state[**idx].is_flagged()) {
+        return Some(at(*idx, 0.0));
+    }
+    let chances = probabilities(game, &known_mines)?;
+    (0..state.len())
+        .filter(|idx| state[*idx].is_covered() && !state[*idx].is_flagged() && !known_mines[*idx])
+        .filter_map(|idx| chances[idx].map(|chance| (idx, chance)))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(idx, chance)| at(idx, chance))
+}
+
+/// Plays the game to the end by always clicking the hint. Used to measure how often boards can
+/// be won, see `examples/win_rate.rs`.
+pub fn autoplay(game: &mut MineSweeper) -> GameStatus {
+    while let Some(next) = hint(game) {
+        game.click(next.col, next.row);
+    }
+    game.game_status()
+}
+
+/// Plays a copy of the game from a click on `start`, only ever clicking spots the solver has
+/// proven safe. True if that uncovers every spot without a mine.
+pub fn clears_without_guessing(game: &MineSweeper, start: usize) -> bool {
+    let mut game = game.clone();
+    let width = game.width();
+    game.click(start % width, start / width);
+    let mut known_mines = vec![false; game.width() * game.height()];
+    loop {
+        match game.game_status() {
+            GameStatus::Won => return true,
+            GameStatus::Lost => return false,
+            GameStatus::Playing => {}
+        }
+        let deductions = deduce(&game, &known_mines);
+        let before = game.game_state().to_vec();
+        let mut progress = false;
+        for idx in deductions.mines {
+            progress |= !known_mines[idx];
+            known_mines[idx] = true;
+        }
+        for idx in deductions.safe {
+            game.click(idx % width, idx / width);
+        }
+        // a flag on a safe spot blocks the click, so nothing new may have happened
+        if !progress && game.game_state() == before.as_slice() {
+            return false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::minesweeper2::Generation;
+    use crate::topology::Topology;
+
+    #[test]
+    fn single_number_settles_its_neighbors() {
+        // a 3x1 strip with the mine on the right: clicking the left shows 0, the middle 1
+        let mut ms = MineSweeper::with_generation(3, 1, 1, Generation::FirstClickSafe);
+        ms.click(0, 0);
+        let deductions = deduce(&ms, &[false; 3]);
+        assert_eq!(deductions.mines, vec![2]);
+        assert!(deductions.safe.is_empty());
+    }
+
+    /// every layout of the remaining mines over the covered spots that fits the revealed numbers
+    fn brute_force(game: &MineSweeper) -> Vec<Option<f64>> {
+        let state = game.game_state();
+        let covered: Vec<usize> = (0..state.len())
+            .filter(|i| state[*i].is_covered())
+            .collect();
+        let mines = game.get_total_bombs();
+        let mut hits = vec![0usize; state.len()];
+        let mut layouts = 0;
+        for layout in 0u32..1 << covered.len() {
+            if layout.count_ones() as usize != mines {
+                continue;
+            }
+            let mut mine = vec![false; state.len()];
+            for (bit, idx) in covered.iter().enumerate() {
+                mine[*idx] = layout >> bit & 1 == 1;
+            }
+            let fits = (0..state.len()).all(|i| match state[i].number() {
+                Some(number) => {
+                    game.neighbors(i).iter().filter(|n| mine[**n]).count() == number as usize
+                }
+                None => true,
+            });
+            if fits {
+                layouts += 1;
+                for idx in covered.iter() {
+                    hits[*idx] += mine[*idx] as usize;
+                }
+            }
+        }
+        (0..state.len())
+            .map(|i| {
+                if state[i].is_covered() {
+                    Some(hits[i] as f64 / layouts as f64)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn one_mine_among_three() {
+        // 2x2 with the mine in the bottom right. The top left shows a 1
+        let mut ms = MineSweeper::from_board_code("2x2-E").unwrap();
+        ms.click(0, 0);
+        let chances = probabilities(&ms, &[false; 4]).unwrap();
+        assert_eq!(chances[0], None);
+        for chance in chances[1..].iter() {
+            assert!((chance.unwrap() - 1.0 / 3.0).abs() < 1e-9);
+        }
+        let next = hint(&ms).unwrap();
+        assert!(!next.is_safe());
+    }
+
+    #[test]
+    fn probabilities_match_brute_force() {
+        let topologies = [
+            Topology::square(),
+            Topology::torus(),
+            Topology::hex(),
+            Topology::knight(),
+        ];
+        for seed in 0..60 {
+            let topology = &topologies[seed as usize % topologies.len()];
+            let mut ms =
+                MineSweeper::with_topology(5, 4, 5, seed, Generation::FirstClickSafe, topology);
+            ms.click(0, 0);
+            // play a few safe moves in so the edge is more interesting
+            for _ in 0..seed % 3 {
+                match hint(&ms) {
+                    Some(next) if next.is_safe() => {
+                        ms.click(next.col, next.row);
+                    }
+                    _ => break,
+                }
+            }
+            if ms.game_status() != GameStatus::Playing {
+                continue;
+            }
+            let exact = brute_force(&ms);
+            let chances = probabilities(&ms, &[false; 20]).unwrap();
+            for (idx, (a, b)) in exact.iter().zip(chances.iter()).enumerate() {
+                match (a, b) {
+                    (Some(a), Some(b)) => {
+                        assert!(
+                            (a - b).abs() < 1e-9,
+                            "{} seed {} spot {}",
+                            topology,
+                            seed,
+                            idx
+                        )
+                    }
+                    (None, None) => {}
+                    _ => panic!("seed {} spot {}: {:?} vs {:?}", seed, idx, a, b),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn hint_prefers_proven_safe_spots() {
+        let ms = MineSweeper::with_generation(3, 1, 1, Generation::FirstClickSafe);
+        assert_eq!(hint(&ms).map(|h| h.is_safe()), Some(true));
+
+        // a 4x1 row with the mine third. The 1 in the second spot proves the last is safe
+        let mut ms = MineSweeper::from_board_code("4x1-I").unwrap();
+        ms.click(0, 0);
+        let next = hint(&ms).unwrap();
+        assert_eq!((next.col, next.row, next.probability), (3, 0, 0.0));
+        ms.click(next.col, next.row);
+        assert_eq!(hint(&ms), None);
+    }
+
+    #[test]
+    fn autoplay_finishes_games() {
+        let mut won = 0;
+        for seed in 0..20 {
+            let mut ms =
+                MineSweeper::with_seed_and_generation(9, 9, 10, seed, Generation::FirstClickSafe);
+            match autoplay(&mut ms) {
+                GameStatus::Won => won += 1,
+                GameStatus::Lost => {}
+                GameStatus::Playing => panic!("autoplay stopped early"),
+            }
+        }
+        // beginner boards are won most of the time
+        assert!(won >= 14, "only won {} of 20", won);
+    }
+}
@@ -0,0 +1,294 @@
+//! Records of finished games, for win rates, best times and a leaderboard per board.
+//!
+//! Along with how long a game took, each record keeps the board's 3BV: the fewest clicks that
+//! could clear it, counting one per opening (a connected patch of empty spots, which opens
+//! with one click along with the numbers around it) plus one for every number not next to
+//! an opening. 3BV per second is the usual way to compare times on different boards.
+//!
+//! The page keeps `Stats` as JSON in localStorage and the terminal game in a file.
+
+use crate::minesweeper2::{GameStatus, MineSweeper};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use wasm_bindgen::prelude::*;
+
+/// The fewest clicks that clear the board, from where the mines are. 0 before they are placed.
+pub fn three_bv(game: &MineSweeper) -> usize {
+    let state = game.game_state();
+    if !game.mines_placed() {
+        return 0;
+    }
+    let mine: Vec<bool> = state.iter().map(|s| s.is_mine()).collect();
+    let empty: Vec<bool> = (0..state.len())
+        .map(|idx| !mine[idx] && game.neighbors(idx).iter().all(|n| !mine[*n]))
+        .collect();
+
+    let mut cleared = vec![false; state.len()];
+    let mut clicks = 0;
+    // each opening is one click, and uncovers its empty spots and the numbers around them
+    for start in 0..state.len() {
+        if !empty[start] || cleared[start] {
+            continue;
+        }
+        clicks += 1;
+        cleared[start] = true;
+        let mut queue = VecDeque::from(vec![start]);
+        while let Some(idx) = queue.pop_front() {
+            for &neighbor in game.neighbors(idx).iter() {
+                if cleared[neighbor] {
+                    continue;
+                }
+                cleared[neighbor] = true;
+                if empty[neighbor] {
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+    }
+    // every other number needs its own click
+    clicks
+        + (0..state.len())
+            .filter(|idx| !mine[*idx] && !cleared[*idx])
+            .count()
+}
+
+/// One finished game
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct GameRecord {
+    pub width: usize,
+    pub height: usize,
+    pub mines: usize,
+    pub won: bool,
+    pub duration_ms: u64,
+    pub three_bv: usize,
+    /// moves played, flags and chords included
+    pub clicks: usize,
+}
+
+#[wasm_bindgen]
+impl GameRecord {
+    /// The record for a game that has just ended, taking `duration_ms` from the front end's
+    /// clock. Errors if the game is still going.
+    pub fn from_game(game: &MineSweeper, duration_ms: u64) -> Result<GameRecord, String> {
+        let won = match game.game_status() {
+            GameStatus::Playing => return Err(String::from("The game isn't over yet")),
+            GameStatus::Won => true,
+            GameStatus::Lost => false,
+        };
+        Ok(GameRecord {
+            width: game.width(),
+            height: game.height(),
+            mines: game.get_total_bombs(),
+            won,
+            duration_ms,
+            three_bv: three_bv(game),
+            clicks: game.history_len(),
+        })
+    }
+
+    pub fn three_bv_per_second(&self) -> f64 {
+        per_second(self.three_bv, self.duration_ms)
+    }
+
+    pub fn clicks_per_second(&self) -> f64 {
+        per_second(self.clicks, self.duration_ms)
+    }
+}
+
+impl GameRecord {
+    fn is_board(&self, width: usize, height: usize, mines: usize) -> bool {
+        (self.width, self.height, self.mines) == (width, height, mines)
+    }
+}
+
+fn per_second(count: usize, duration_ms: u64) -> f64 {
+    if duration_ms == 0 {
+        return 0.0;
+    }
+    count as f64 * 1000.0 / duration_ms as f64
+}
+
+/// Every game recorded. Boards are told apart by width, height and mine count.
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Stats {
+    records: Vec<GameRecord>,
+}
+
+#[wasm_bindgen]
+impl Stats {
+    pub fn new() -> Stats {
+        Stats::default()
+    }
+
+    pub fn record(&mut self, record: GameRecord) {
+        self.records.push(record);
+    }
+
+    pub fn played(&self, width: usize, height: usize, mines: usize) -> usize {
+        self.board(width, height, mines).count()
+    }
+
+    pub fn won(&self, width: usize, height: usize, mines: usize) -> usize {
+        self.board(width, height, mines).filter(|r| r.won).count()
+    }
+
+    /// share of games won from 0 to 1, 0 if none were played
+    pub fn win_rate(&self, width: usize, height: usize, mines: usize) -> f64 {
+        let played = self.played(width, height, mines);
+        if played == 0 {
+            return 0.0;
+        }
+        self.won(width, height, mines) as f64 / played as f64
+    }
+
+    /// the fastest win, `undefined` before the first one
+    pub fn best_time_ms(&self, width: usize, height: usize, mines: usize) -> Option<u64> {
+        self.wins(width, height, mines).map(|r| r.duration_ms).min()
+    }
+
+    pub fn best_three_bv_per_second(&self, width: usize, height: usize, mines: usize) -> f64 {
+        self.wins(width, height, mines)
+            .map(|r| r.three_bv_per_second())
+            .fold(0.0, f64::max)
+    }
+
+    /// The times of the `count` fastest wins, fastest first
+    pub fn best_times_ms(
+        &self,
+        width: usize,
+        height: usize,
+        mines: usize,
+        count: usize,
+    ) -> Vec<u64> {
+        self.leaderboard(width, height, mines)
+            .iter()
+            .take(count)
+            .map(|r| r.duration_ms)
+            .collect()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("stats are always valid JSON")
+    }
+
+    pub fn from_json(json: &str) -> Result<Stats, String> {
+        serde_json::from_str(json).map_err(|e| format!("Can't read stats: {}", e))
+    }
+}
+
+impl Stats {
+    pub fn records(&self) -> &[GameRecord] {
+        &self.records
+    }
+
+    /// every board played, as width, height and mine count, in the order first played
+    pub fn boards(&self) -> Vec<(usize, usize, usize)> {
+        let mut boards = Vec::new();
+        for record in self.records.iter() {
+            let board = (record.width, record.height, record.mines);
+            if !boards.contains(&board) {
+                boards.push(board);
+            }
+        }
+        boards
+    }
+
+    /// wins on a board, fastest first
+    pub fn leaderboard(&self, width: usize, height: usize, mines: usize) -> Vec<GameRecord> {
+        let mut wins: Vec<GameRecord> = self.wins(width, height, mines).copied().collect();
+        wins.sort_by_key(|r| r.duration_ms);
+        wins
+    }
+
+    fn board(
+        &self,
+        width: usize,
+        height: usize,
+        mines: usize,
+    ) -> impl Iterator<Item = &GameRecord> {
+        self.records
+            .iter()
+            .filter(move |r| r.is_board(width, height, mines))
+    }
+
+    fn wins(&self, width: usize, height: usize, mines: usize) -> impl Iterator<Item = &GameRecord> {
+        self.board(width, height, mines).filter(|r| r.won)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Stats {
+    /// Reads the stats kept at `path`, or starts fresh if there is no file yet
+    pub fn load_from_file(path: &std::path::Path) -> Result<Stats, String> {
+        match std::fs::read_to_string(path) {
+            Ok(json) => Stats::from_json(&json),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Stats::new()),
+            Err(e) => Err(format!("Can't load {}: {}", path.display(), e)),
+        }
+    }
+
+    pub fn save_to_file(&self, path: &std::path::Path) -> Result<(), String> {
+        std::fs::write(path, self.to_json())
+            .map_err(|e| format!("Can't save to {}: {}", path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_openings_and_lone_numbers() {
+        // mine in the corner of 3x3: one opening clears everything
+        let game = MineSweeper::from_board_code("3x3-gA").unwrap();
+        assert_eq!(three_bv(&game), 1);
+        // 4x1 with the mine third: the opening at the left, then the lone number at the right
+        let game = MineSweeper::from_board_code("4x1-I").unwrap();
+        assert_eq!(three_bv(&game), 2);
+        // a mine in the middle of 3x3 leaves 8 numbers and no openings
+        let game = MineSweeper::from_board_code("3x3-CA").unwrap();
+        assert_eq!(three_bv(&game), 8);
+    }
+
+    #[test]
+    fn three_bv_is_never_more_than_the_safe_spots() {
+        for seed in 0..20 {
+            let game = MineSweeper::with_seed(16, 16, 40, seed);
+            let bv = three_bv(&game);
+            assert!((1..=16 * 16 - 40).contains(&bv), "{}", bv);
+        }
+    }
+
+    #[test]
+    fn records_finished_games() {
+        let mut game = MineSweeper::from_board_code("4x1-I").unwrap();
+        assert!(GameRecord::from_game(&game, 0).is_err());
+        game.click(0, 0);
+        game.click(3, 0);
+        let record = GameRecord::from_game(&game, 2000).unwrap();
+        assert!(record.won);
+        assert_eq!((record.three_bv, record.clicks), (2, 2));
+        assert_eq!(record.three_bv_per_second(), 1.0);
+
+        let mut stats = Stats::new();
+        stats.record(record);
+        stats.record(GameRecord {
+            duration_ms: 1500,
+            ..record
+        });
+        stats.record(GameRecord {
+            won: false,
+            duration_ms: 10,
+            ..record
+        });
+        stats.record(GameRecord { width: 9, ..record });
+        assert_eq!((stats.played(4, 1, 1), stats.won(4, 1, 1)), (3, 2));
+        assert_eq!(stats.best_time_ms(4, 1, 1), Some(1500));
+        assert_eq!(stats.best_times_ms(4, 1, 1, 5), vec![1500, 2000]);
+        assert_eq!(stats.best_time_ms(5, 5, 5), None);
+        assert_eq!(stats.boards(), vec![(4, 1, 1), (9, 1, 1)]);
+        assert_eq!(Stats::from_json(&stats.to_json()), Ok(stats));
+    }
+}
@@ -0,0 +1,338 @@
+//! Which spots count as neighbors of each other. Numbers, flood fill, first click safety and
+//! the solver all ask the board's topology instead of assuming the usual 8 around a square.
+//!
+//! - `square` is the classic grid, clipped at the edges
+//! - `torus` is the square grid with the edges wrapping around to the other side
+//! - `hex` is a grid of hexagons with every odd row pushed half a spot to the right
+//! - `knight` counts the spots a chess knight could jump to
+//! - `custom` takes any list of up to 8 offsets, wrapping around or not
+//!
+//! Spots have at most 8 neighbors so a revealed number still fits in the state codes 0 to 8.
+
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+use wasm_bindgen::prelude::*;
+
+/// the most neighbors a spot can have, since the numbers 0 to 8 are state codes
+pub const MAX_NEIGHBORS: usize = 8;
+
+const SQUARE: [[isize; 2]; 8] = [
+    [-1, -1],
+    [-1, 0],
+    [-1, 1],
+    [0, -1],
+    [0, 1],
+    [1, -1],
+    [1, 0],
+    [1, 1],
+];
+
+const KNIGHT: [[isize; 2]; 8] = [
+    [1, 2],
+    [2, 1],
+    [2, -1],
+    [1, -2],
+    [-1, -2],
+    [-2, -1],
+    [-2, 1],
+    [-1, 2],
+];
+
+/// offsets from a spot on an even row of a hex grid
+const HEX_EVEN_ROW: [[isize; 2]; 6] = [[-1, 0], [1, 0], [-1, -1], [0, -1], [-1, 1], [0, 1]];
+/// odd rows sit half a spot further right, so their diagonals lean the other way
+const HEX_ODD_ROW: [[isize; 2]; 6] = [[-1, 0], [1, 0], [0, -1], [1, -1], [0, 1], [1, 1]];
+
+/// How the front end should lay the grid out
+#[wasm_bindgen]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Shape {
+    Square = 0,
+    /// every odd row is drawn half a spot to the right
+    Hex = 1,
+}
+
+/// The neighbor rules for a board. The front end reads `shape`, `wraps` and `offsets` to
+/// know how to draw the board and which spots to highlight around a number.
+#[wasm_bindgen]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Topology {
+    shape: Shape,
+    /// neighbors as [col, row] steps away. Hex grids use these on even rows
+    offsets: Vec<[isize; 2]>,
+    /// the steps from odd rows, only different on hex grids
+    odd_row_offsets: Vec<[isize; 2]>,
+    /// whether stepping off an edge comes back on the other side
+    wraps: bool,
+}
+
+#[wasm_bindgen]
+impl Topology {
+    pub fn square() -> Topology {
+        Topology::flat(SQUARE.to_vec(), false)
+    }
+
+    pub fn torus() -> Topology {
+        Topology::flat(SQUARE.to_vec(), true)
+    }
+
+    pub fn hex() -> Topology {
+        Topology {
+            shape: Shape::Hex,
+            offsets: HEX_EVEN_ROW.to_vec(),
+            odd_row_offsets: HEX_ODD_ROW.to_vec(),
+            wraps: false,
+        }
+    }
+
+    pub fn knight() -> Topology {
+        Topology::flat(KNIGHT.to_vec(), false)
+    }
+
+    /// Neighbors given as column and row steps one after the other, so `[1, 0, -1, 0]`
+    /// means the spots to the right and left. Up to 8 of them, none repeated or zero.
+    pub fn custom(offsets: Vec<i32>, wraps: bool) -> Result<Topology, String> {
+        if !offsets.len().is_multiple_of(2) {
+            return Err(String::from("Offsets come in column and row pairs"));
+        }
+        let offsets: Vec<[isize; 2]> = offsets
+            .chunks(2)
+            .map(|pair| [pair[0] as isize, pair[1] as isize])
+            .collect();
+        if offsets.len() > MAX_NEIGHBORS {
+            return Err(format!(
+                "A spot can have at most {} neighbors, not {}",
+                MAX_NEIGHBORS,
+                offsets.len()
+            ));
+        }
+        for (i, offset) in offsets.iter().enumerate() {
+            if *offset == [0, 0] {
+                return Err(String::from("A spot can't be its own neighbor"));
+            }
+            if offsets[..i].contains(offset) {
+                return Err(format!(
+                    "{},{} is in the offsets twice",
+                    offset[0], offset[1]
+                ));
+            }
+        }
+        Ok(Topology::flat(offsets, wraps))
+    }
+
+    /// Reads a topology from its name, see `name`
+    pub fn from_name(name: &str) -> Result<Topology, String> {
+        name.parse()
+    }
+
+    /// `square`, `torus`, `hex`, `knight`, or for custom ones `custom:` (`custom-wrap:` if
+    /// they wrap) then the offsets like `1,0;-1,0`
+    pub fn name(&self) -> String {
+        self.to_string()
+    }
+
+    pub fn shape(&self) -> Shape {
+        self.shape
+    }
+
+    pub fn wraps(&self) -> bool {
+        self.wraps
+    }
+
+    /// The neighbor steps as column and row pairs, like `custom` takes them.
+    /// On hex grids these are the steps from even rows.
+    pub fn offsets(&self) -> Vec<i32> {
+        self.offsets
+            .iter()
+            .flat_map(|[col, row]| [*col as i32, *row as i32])
+            .collect()
+    }
+}
+
+impl Topology {
+    fn flat(offsets: Vec<[isize; 2]>, wraps: bool) -> Topology {
+        Topology {
+            shape: Shape::Square,
+            odd_row_offsets: offsets.clone(),
+            offsets,
+            wraps,
+        }
+    }
+
+    /// Every spot next to `idx` on a `width` by `height` board. Spots that wrap around onto
+    /// the same place, or onto `idx` itself, are only counted once or not at all.
+    pub(crate) fn neighbors(&self, width: usize, height: usize, idx: usize) -> Neighbors {
+        let col = (idx % width) as isize;
+        let row = (idx / width) as isize;
+        let offsets = if row % 2 == 1 {
+            &self.odd_row_offsets
+        } else {
+            &self.offsets
+        };
+        let (width_i, height_i) = (width as isize, height as isize);
+        let mut found = Neighbors {
+            spots: [0; MAX_NEIGHBORS],
+            len: 0,
+        };
+        for [x, y] in offsets.iter() {
+            let (mut c, mut r) = (col + x, row + y);
+            if self.wraps {
+                c = c.rem_euclid(width_i);
+                r = r.rem_euclid(height_i);
+            } else if c < 0 || r < 0 || c >= width_i || r >= height_i {
+                continue;
+            }
+            let spot = r as usize * width + c as usize;
+            if spot != idx && !found.contains(&spot) {
+                found.spots[found.len] = spot;
+                found.len += 1;
+            }
+        }
+        found
+    }
+}
+
+impl Default for Topology {
+    fn default() -> Topology {
+        Topology::square()
+    }
+}
+
+impl fmt::Display for Topology {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (name, named) in [
+            ("square", Topology::square()),
+            ("torus", Topology::torus()),
+            ("hex", Topology::hex()),
+            ("knight", Topology::knight()),
+        ] {
+            if *self == named {
+                return write!(f, "{}", name);
+            }
+        }
+        let offsets: Vec<String> = self
+            .offsets
+            .iter()
+            .map(|[col, row]| format!("{},{}", col, row))
+            .collect();
+        let kind = if self.wraps { "custom-wrap" } else { "custom" };
+        write!(f, "{}:{}", kind, offsets.join(";"))
+    }
+}
+
+impl FromStr for Topology {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Topology, String> {
+        match text {
+            "square" => return Ok(Topology::square()),
+            "torus" => return Ok(Topology::torus()),
+            "hex" => return Ok(Topology::hex()),
+            "knight" => return Ok(Topology::knight()),
+            _ => {}
+        }
+        let bad = || format!("{:?} is not a topology", text);
+        let (kind, offsets) = text.split_once(':').ok_or_else(bad)?;
+        let wraps = match kind {
+            "custom" => false,
+            "custom-wrap" => true,
+            _ => return Err(bad()),
+        };
+        let mut steps = Vec::new();
+        for pair in offsets.split(';').filter(|p| !p.is_empty()) {
+            let (col, row) = pair.split_once(',').ok_or_else(bad)?;
+            steps.push(col.trim().parse().map_err(|_| bad())?);
+            steps.push(row.trim().parse().map_err(|_| bad())?);
+        }
+        Topology::custom(steps, wraps)
+    }
+}
+
+/// The neighbors of one spot, kept on the stack since flood fill asks for a lot of them
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Neighbors {
+    spots: [usize; MAX_NEIGHBORS],
+    len: usize,
+}
+
+impl Deref for Neighbors {
+    type Target = [usize];
+
+    fn deref(&self) -> &[usize] {
+        &self.spots[..self.len]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(neighbors: Neighbors) -> Vec<usize> {
+        let mut spots = neighbors.to_vec();
+        spots.sort_unstable();
+        spots
+    }
+
+    #[test]
+    fn square_grids_clip_at_the_edges() {
+        let square = Topology::square();
+        assert_eq!(sorted(square.neighbors(3, 3, 0)), vec![1, 3, 4]);
+        assert_eq!(square.neighbors(3, 3, 4).len(), 8);
+    }
+
+    #[test]
+    fn tori_wrap_around() {
+        let torus = Topology::torus();
+        assert_eq!(
+            sorted(torus.neighbors(4, 4, 0)),
+            vec![1, 3, 4, 5, 7, 12, 13, 15]
+        );
+        // on a 2 wide board left and right are the same spot
+        assert_eq!(sorted(torus.neighbors(2, 3, 0)), vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn hex_rows_lean_by_parity() {
+        let hex = Topology::hex();
+        // (1, 1) is on an odd row, so it touches (1, 0) and (2, 0) above it
+        assert_eq!(sorted(hex.neighbors(3, 3, 4)), vec![1, 2, 3, 5, 7, 8]);
+        // (1, 2) is on an even row, so it touches (0, 1) and (1, 1) above it
+        assert_eq!(sorted(hex.neighbors(3, 3, 7)), vec![3, 4, 6, 8]);
+    }
+
+    #[test]
+    fn knights_jump() {
+        let knight = Topology::knight();
+        assert_eq!(sorted(knight.neighbors(3, 3, 0)), vec![5, 7]);
+        assert!(knight.neighbors(3, 3, 4).is_empty());
+    }
+
+    #[test]
+    fn names_round_trip() {
+        let cross = Topology::custom(vec![1, 0, -1, 0, 0, 1, 0, -1], true).unwrap();
+        for topology in [
+            Topology::square(),
+            Topology::hex(),
+            Topology::knight(),
+            cross,
+        ] {
+            assert_eq!(topology.name().parse(), Ok(topology));
+        }
+        assert_eq!(Topology::torus().name(), "torus");
+        assert_eq!(
+            Topology::custom(vec![2, 0], false).unwrap().name(),
+            "custom:2,0"
+        );
+    }
+
+    #[test]
+    fn rejects_bad_custom_topologies() {
+        assert!(Topology::custom(vec![1], false).is_err());
+        assert!(Topology::custom(vec![0, 0], false).is_err());
+        assert!(Topology::custom(vec![1, 0, 1, 0], false).is_err());
+        assert!(Topology::custom(vec![1; 18], false).is_err());
+        assert!("triangle".parse::<Topology>().is_err());
+        assert!("custom:1".parse::<Topology>().is_err());
+    }
+}
@@ -0,0 +1,186 @@
+//! Random games of clicks, flags, chords, undos and redos, some of them off the board,
+//! checking after every move that:
+//!
+//! - the mines never move and there are always as many as the game was made with
+//! - every revealed number is how many mines really are around it
+//! - nothing more happens on the board once a mine explodes, until it is undone
+//! - a move reports exactly the spots that look different after it
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use minesweeper2::cell::Cell;
+use minesweeper2::minesweeper2::{GameStatus, Generation, MineSweeper};
+use minesweeper2::topology::Topology;
+use proptest::prelude::*;
+
+#[derive(Copy, Clone, Debug)]
+enum Action {
+    Click(usize, usize),
+    Flag(usize, usize),
+    Chord(usize, usize),
+    Undo,
+    Redo,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Setup {
+    width: usize,
+    height: usize,
+    mines: usize,
+    seed: u64,
+    first_click_safe: bool,
+    torus: bool,
+    question_marks: bool,
+}
+
+/// moves anywhere on the board and a little way off it
+fn action(width: usize, height: usize) -> impl Strategy<Value = Action> {
+    let spot = (0..width + 2, 0..height + 2);
+    prop_oneof![
+        4 => spot.clone().prop_map(|(col, row)| Action::Click(col, row)),
+        2 => spot.clone().prop_map(|(col, row)| Action::Flag(col, row)),
+        1 => spot.prop_map(|(col, row)| Action::Chord(col, row)),
+        1 => Just(Action::Undo),
+        1 => Just(Action::Redo),
+    ]
+}
+
+fn game() -> impl Strategy<Value = (Setup, Vec<Action>)> {
+    (
+        2usize..10,
+        2usize..10,
+        any::<u64>(),
+        any::<bool>(),
+        any::<bool>(),
+        any::<bool>(),
+    )
+        .prop_flat_map(
+            |(width, height, seed, first_click_safe, torus, question_marks)| {
+                (0..width * height).prop_flat_map(move |mines| {
+                    let setup = Setup {
+                        width,
+                        height,
+                        mines,
+                        seed,
+                        first_click_safe,
+                        torus,
+                        question_marks,
+                    };
+                    (
+                        Just(setup),
+                        prop::collection::vec(action(width, height), 0..60),
+                    )
+                })
+            },
+        )
+}
+
+/// the 8 spots around `idx`, worked out here rather than trusting the engine's topology
+fn neighbors(setup: &Setup, idx: usize) -> Vec<usize> {
+    let (width, height) = (setup.width as isize, setup.height as isize);
+    let (col, row) = (idx as isize % width, idx as isize / width);
+    let mut found = Vec::new();
+    for d_col in -1..=1 {
+        for d_row in -1..=1 {
+            let (mut c, mut r) = (col + d_col, row + d_row);
+            if setup.torus {
+                c = c.rem_euclid(width);
+                r = r.rem_euclid(height);
+            } else if c < 0 || r < 0 || c >= width || r >= height {
+                continue;
+            }
+            let spot = (r * width + c) as usize;
+            if spot != idx && !found.contains(&spot) {
+                found.push(spot);
+            }
+        }
+    }
+    found
+}
+
+fn cells(game: &MineSweeper) -> Vec<Cell> {
+    (0..game.height())
+        .flat_map(|row| (0..game.width()).map(move |col| (col, row)))
+        .map(|(col, row)| game.cell(col, row).unwrap())
+        .collect()
+}
+
+fn mines(cells: &[Cell]) -> Vec<bool> {
+    cells.iter().map(|c| c.is_mine()).collect()
+}
+
+/// spots that look different to the player, so mines going down under covered spots on the
+/// first click don't count
+fn changed(before: &[Cell], after: &[Cell]) -> Vec<usize> {
+    (0..before.len())
+        .filter(|idx| before[*idx].symbol(true) != after[*idx].symbol(true))
+        .collect()
+}
+
+proptest! {
+    #[test]
+    fn invariants_hold_through_random_games((setup, actions) in game()) {
+        let generation = if setup.first_click_safe {
+            Generation::FirstClickSafe
+        } else {
+            Generation::Immediate
+        };
+        let topology = if setup.torus { Topology::torus() } else { Topology::square() };
+        let mut game = MineSweeper::with_topology(
+            setup.width,
+            setup.height,
+            setup.mines,
+            setup.seed,
+            generation,
+            &topology,
+        );
+        game.set_question_marks(setup.question_marks);
+        let mut layout: Option<Vec<bool>> = None;
+
+        for action in actions {
+            let before = cells(&game);
+            let lost = game.game_status() == GameStatus::Lost;
+            let mut reported = match action {
+                Action::Click(col, row) => game.click(col, row),
+                Action::Flag(col, row) => {
+                    let flagged = game.toggle_flag(col, row);
+                    let now = changed(&before, &cells(&game));
+                    prop_assert_eq!(flagged, !now.is_empty());
+                    now
+                }
+                Action::Chord(col, row) => game.chord(col, row),
+                Action::Undo => game.undo(),
+                Action::Redo => game.redo(),
+            };
+            let after = cells(&game);
+            reported.sort_unstable();
+            prop_assert_eq!(&reported, &changed(&before, &after), "{:?}", action);
+
+            if lost {
+                if let Action::Click(..) | Action::Flag(..) | Action::Chord(..) = action {
+                    prop_assert_eq!(&before, &after, "played on after a mine went off");
+                }
+            }
+
+            let now = mines(&after);
+            if now.iter().any(|mine| *mine) || setup.mines == 0 {
+                prop_assert_eq!(now.iter().filter(|mine| **mine).count(), setup.mines);
+                match &layout {
+                    Some(placed) => prop_assert_eq!(placed, &now, "the mines moved"),
+                    None => layout = Some(now.clone()),
+                }
+            }
+
+            for (idx, cell) in after.iter().enumerate() {
+                if let Some(number) = cell.number() {
+                    let around = neighbors(&setup, idx).iter().filter(|n| now[**n]).count();
+                    prop_assert_eq!(number as usize, around, "spot {} of {}", idx, game);
+                }
+            }
+            prop_assert_eq!(
+                after.contains(&Cell::MineExploded),
+                game.game_status() == GameStatus::Lost
+            );
+        }
+    }
+}
@@ -0,0 +1,65 @@
+//! The tests in `web.rs`, and a few more through the public API, run natively with
+//! `cargo test` instead of in a browser.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use minesweeper2::cell::Cell;
+use minesweeper2::history::Replay;
+use minesweeper2::minesweeper2::{GameStatus, Generation, MineSweeper};
+use minesweeper2::save::SavedGame;
+
+#[test]
+fn pass() {
+    assert_eq!(1 + 1, 2);
+}
+
+#[test]
+fn new_minesweeper() {
+    let ms = MineSweeper::new(2, 2, 2);
+    assert_eq!(ms.get_total_bombs(), 2);
+}
+
+#[test]
+fn state_buffer_holds_the_state_codes() {
+    let mut ms = MineSweeper::from_board_code("3x3-gA").unwrap();
+    ms.click(2, 2);
+    // what the page reads through the pointer
+    let state = unsafe { std::slice::from_raw_parts(ms.state(), 9) };
+    assert_eq!(state, &[10, 1, 0, 1, 1, 0, 0, 0, 0]);
+    assert_eq!(ms.spot(0, 0), Some(u8::from(Cell::MineCovered)));
+    assert_eq!(MineSweeper::state_layout_version(), 2);
+}
+
+#[test]
+fn a_whole_game_through_the_public_api() {
+    let mut ms = MineSweeper::with_seed_and_generation(9, 9, 10, 42, Generation::FirstClickSafe);
+    assert!(!ms.click(4, 4).is_empty());
+    assert_eq!(ms.get_total_bombs(), 10);
+    let replay = ms.replay();
+
+    let saved = SavedGame::from_json(&SavedGame::new(&ms, 1000).to_json()).unwrap();
+    assert_eq!(saved.game().board_code(), ms.board_code());
+
+    minesweeper2::solver::autoplay(&mut ms);
+    assert_ne!(ms.game_status(), GameStatus::Playing);
+
+    let mut stepped = Replay::from_text(&replay).unwrap();
+    stepped.step();
+    assert_eq!(stepped.game().to_string(), saved.game().to_string());
+}
+
+#[test]
+fn clicks_off_the_board_are_ignored() {
+    let mut ms = MineSweeper::new(5, 5, 3);
+    assert!(ms.click(15, 98).is_empty());
+    assert!(ms.chord(5, 0).is_empty());
+    assert!(!ms.toggle_flag(0, 5));
+    assert_eq!(ms.game_status(), GameStatus::Playing);
+
+    // nor can a spot off the board be read, not even as the next row's first spot
+    assert_eq!(ms.spot(5, 0), None);
+    assert_eq!(ms.spot(0, 5), None);
+    assert_eq!(ms.cell(5, 4), None);
+    assert_eq!(ms.cell(0, usize::MAX), None);
+    assert!(ms.cell(4, 4).is_some());
+}
@@ -1,4 +1,4 @@
-import { MineSweeper } from "../pkg/minesweeper2";
+import { MineSweeper, Generation, GameStatus, Topology, Shape, SavedGame, Stats, GameRecord } from "../pkg/minesweeper2";
 import { memory } from "wasm-game-of-life/minesweeper2_bg";
 console.log("okay");
 
@@ -11,7 +11,52 @@ let playing = false;
 let game = "";
 let w = 10;
 let h = 10;
+// when the current game started, moved back on load so the clock carries on
+let startTime = Date.now();
+const SAVE_KEY = "minesweeper2-save";
+const STATS_KEY = "minesweeper2-stats";
+// whether the current game is in the stats yet, so undoing a loss doesn't count twice
+let recorded = false;
 
+function loadStats() {
+  const json = localStorage.getItem(STATS_KEY);
+  if (json !== null) {
+    try {
+      return Stats.from_json(json);
+    } catch (error) {
+      console.log(`Starting stats over: ${error}`);
+    }
+  }
+  return Stats.new();
+}
+
+// adds the game that just ended to the stats and returns how it compares
+function recordGame() {
+  const stats = loadStats();
+  const record = GameRecord.from_game(game, BigInt(Date.now() - startTime));
+  const mines = game.get_total_bombs();
+  if (!recorded) {
+    stats.record(record);
+    localStorage.setItem(STATS_KEY, stats.to_json());
+    recorded = true;
+  }
+  const best = stats.best_time_ms(w, h, mines);
+  const summary =
+    `3BV ${record.three_bv}, ${record.three_bv_per_second().toFixed(2)} 3BV/s, ` +
+    `${record.clicks_per_second().toFixed(2)} clicks/s. ` +
+    `Won ${stats.won(w, h, mines)} of ${stats.played(w, h, mines)}` +
+    (best === undefined ? "." : `, best ${(Number(best) / 1000).toFixed(1)}s.`);
+  record.free();
+  stats.free();
+  document.getElementById("stats").innerText = summary;
+  return summary;
+}
+
+// the state codes this page knows how to draw, see src/cell.rs
+const STATE_LAYOUT_VERSION = 2;
+if (MineSweeper.state_layout_version() > STATE_LAYOUT_VERSION) {
+  console.warn("The game has state codes this page doesn't know how to draw");
+}
 const state_types = {
   0: "empty",
   1: "numbered",
@@ -27,6 +72,8 @@ const state_types = {
   11: "FlaggedNoMine",
   12: "FlaggedMine",
   13: "Exploded",
+  14: "QuestionedNoMine",
+  15: "QuestionedMine",
 };
 
 const resetBoard = () => {
@@ -56,11 +103,16 @@ const setupBoard = () => {
   playResetButton.innerText = "Reset";
   playing = true;
 
-  game = MineSweeper.new(width, height, mines);
+  const topology = Topology.from_name(document.getElementById("topology-input").value);
+  const seed = BigInt(Math.floor(Math.random() * Number.MAX_SAFE_INTEGER));
+  game = MineSweeper.with_topology(width, height, mines, seed, Generation.FirstClickSafe, topology);
+  topology.free();
   console.log(`Starting New Game. ${width}x${height} with ${mines} mines.`);
 
   h = height;
   w = width;
+  startTime = Date.now();
+  recorded = false;
 
   const cellsPtr = game.state();
   const cells = new Uint8Array(memory.buffer, cellsPtr, width * height);
@@ -79,26 +131,108 @@ playResetButton.addEventListener("click", (event) => {
   }
 });
 
-function clickBox(col, row) {
-  game.click(col, row);
-  const cellsPtr = game.state();
-  const cells = new Uint8Array(memory.buffer, cellsPtr, w * h);
-  debugger;
-
-  let game_over = false;
-  for (let i = 0; i < cells.length; i++) {
-    if (cells[i] == 13) {
-      game_over = true;
-      break;
-    }
+const hintButton = document.getElementById("hint");
+hintButton.addEventListener("click", (event) => {
+  if (!playing) {
+    return;
   }
-  if (game_over) {
-    resetBoard();
+  const hint = game.hint();
+  if (hint === undefined) {
     return;
   }
   createGrid();
+  const box = document.getElementById(`box-${hint.row * w + hint.col}`);
+  box.classList.add("Hint");
+  box.title = hint.is_safe()
+    ? "Safe"
+    : `${Math.round(hint.probability * 100)}% chance of a mine`;
+  hint.free();
+});
+
+document.getElementById("save").addEventListener("click", (event) => {
+  if (!playing) {
+    return;
+  }
+  const saved = SavedGame.new(game, BigInt(Date.now() - startTime));
+  localStorage.setItem(SAVE_KEY, saved.to_json());
+  saved.free();
+});
+
+document.getElementById("load").addEventListener("click", (event) => {
+  const json = localStorage.getItem(SAVE_KEY);
+  if (json === null) {
+    alert("No saved game");
+    return;
+  }
+  let saved;
+  try {
+    saved = SavedGame.from_json(json);
+  } catch (error) {
+    alert(`Saved game is damaged: ${error}`);
+    localStorage.removeItem(SAVE_KEY);
+    return;
+  }
+  game = saved.game();
+  recorded = false;
+  startTime = Date.now() - Number(saved.elapsed_ms());
+  saved.free();
+  w = game.width();
+  h = game.height();
+  playing = true;
+  document.getElementById("play-reset").innerText = "Reset";
+  createGrid();
+});
+
+// redraws only the boxes a move changed
+function drawChanged(changed) {
+  const cells = new Uint8Array(memory.buffer, game.state(), w * h);
+  for (const idx of changed) {
+    drawBox(document.getElementById(`box-${idx}`), cells[idx]);
+  }
+}
+
+document.getElementById("undo").addEventListener("click", (event) => {
+  if (playing) {
+    drawChanged(game.undo());
+  }
+});
 
-  console.log(`Click -> cells: ${cells}`);
+document.getElementById("redo").addEventListener("click", (event) => {
+  if (playing) {
+    drawChanged(game.redo());
+    checkGameOver();
+  }
+});
+
+function clickBox(col, row) {
+  drawChanged(game.click(col, row));
+  checkGameOver();
+}
+
+function flagBox(col, row) {
+  if (game.toggle_flag(col, row)) {
+    const idx = row * w + col;
+    const cells = new Uint8Array(memory.buffer, game.state(), w * h);
+    drawBox(document.getElementById(`box-${idx}`), cells[idx]);
+  }
+}
+
+function checkGameOver() {
+  const status = game.game_status();
+  if (status == GameStatus.Won) {
+    alert(`You cleared the board! ${recordGame()}`);
+    resetBoard();
+  } else if (status == GameStatus.Lost) {
+    recordGame();
+    if (document.getElementById("practice-input").checked) {
+      // practice mode keeps the board so the mistake can be undone
+      alert("Boom! Undo to try again.");
+      return;
+    }
+    alert("Boom! Game over.");
+    console.log(`Replay: ${game.replay()}`);
+    resetBoard();
+  }
 }
 
 function createGrid() {
@@ -106,34 +240,50 @@ function createGrid() {
   gameGrid.innerHTML = "";
   const cells = new Uint8Array(memory.buffer, game.state(), w * h);
   let idx = 0;
-  for (var y = 0; y < h; y++) {
+  for (let y = 0; y < h; y++) {
     let row = document.createElement("tr");
-    for (var x = 0; x < w; x++) {
+    // the topology says how to lay the board out, hex grids push odd rows half a box right
+    const topology = game.topology();
+    if (topology.shape() == Shape.Hex && y % 2 == 1) {
+      row.classList.add("hex-odd-row");
+    }
+    topology.free();
+    for (let x = 0; x < w; x++) {
       let gamestate = cells[idx];
       let box = document.createElement("td");
-      box.classList.add("grid-square");
-      box.classList.add(state_types[gamestate]);
-      box.id = `${x}${y}`;
+      box.id = `box-${idx}`;
       box.style.cssText += `width:${100 / h}%;`;
       box.style.cssText += `height:${100 / h}%;`;
       box.addEventListener("click", (e) => {
         console.log(`Box ${box.id} was clicked`);
         clickBox(x, y);
       });
-
-      // add appropriate text
-      if (gamestate == 0 || gamestate == 9 || gamestate == 10) {
-        // empty cell or covered, nothing to add
-      } else if (gamestate > 0 && gamestate < 9) {
-        box.innerText = `${gamestate}`;
-      } else if (gamestate == 11 || gamestate == 12) {
-        box.innerText = "🚩";
-      } else if (gamestate == 13) {
-        box.innerText = "💣";
-      }
+      box.addEventListener("contextmenu", (e) => {
+        e.preventDefault();
+        flagBox(x, y);
+      });
+      drawBox(box, gamestate);
       row.appendChild(box);
       idx += 1;
     }
     gameGrid.appendChild(row);
   }
 }
+
+// sets a box's color and text for its state code
+function drawBox(box, gamestate) {
+  box.className = "grid-square";
+  box.classList.add(state_types[gamestate]);
+  box.innerText = "";
+  if (gamestate == 0 || gamestate == 9 || gamestate == 10) {
+    // empty cell or covered, nothing to add
+  } else if (gamestate > 0 && gamestate < 9) {
+    box.innerText = `${gamestate}`;
+  } else if (gamestate == 11 || gamestate == 12) {
+    box.innerText = "🚩";
+  } else if (gamestate == 13) {
+    box.innerText = "💣";
+  } else if (gamestate == 14 || gamestate == 15) {
+    box.innerText = "❓";
+  }
+}
@@ -0,0 +1,138 @@
+// the b-tree against the standard library's, at a few orders. run with `cargo bench`
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use intermediate_buildup_projects::btree::BTree;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::collections::BTreeMap;
+
+const KEYS: u64 = 10_000;
+const ORDERS: [usize; 4] = [4, 16, 64, 256];
+
+// the keys 0 to KEYS, shuffled the same way every run
+fn shuffled() -> Vec<u64> {
+    let mut keys: Vec<u64> = (0..KEYS).collect();
+    keys.shuffle(&mut StdRng::seed_from_u64(2021));
+    keys
+}
+
+fn insert(c: &mut Criterion) {
+    let keys = shuffled();
+    let mut group = c.benchmark_group("insert");
+    group.bench_function("BTreeMap", |b| {
+        b.iter(|| {
+            let mut map = BTreeMap::new();
+            for &key in keys.iter() {
+                map.insert(key, key);
+            }
+            map
+        })
+    });
+    for order in ORDERS {
+        group.bench_with_input(BenchmarkId::new("BTree", order), &order, |b, &order| {
+            b.iter(|| {
+                let mut tree = BTree::with_order(order);
+                for &key in keys.iter() {
+                    tree.insert(key, key);
+                }
+                tree
+            })
+        });
+    }
+    group.finish();
+}
+
+fn get(c: &mut Criterion) {
+    let keys = shuffled();
+    let map: BTreeMap<u64, u64> = keys.iter().map(|&key| (key, key)).collect();
+    let mut group = c.benchmark_group("get");
+    group.bench_function("BTreeMap", |b| {
+        b.iter(|| {
+            keys.iter()
+                .filter_map(|key| map.get(black_box(key)))
+                .sum::<u64>()
+        })
+    });
+    for order in ORDERS {
+        let mut tree = BTree::with_order(order);
+        tree.extend(keys.iter().map(|&key| (key, key)));
+        group.bench_with_input(BenchmarkId::new("BTree", order), &tree, |b, tree| {
+            b.iter(|| {
+                keys.iter()
+                    .filter_map(|key| tree.get(black_box(key)))
+                    .sum::<u64>()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn remove(c: &mut Criterion) {
+    let keys = shuffled();
+    let map: BTreeMap<u64, u64> = keys.iter().map(|&key| (key, key)).collect();
+    let mut group = c.benchmark_group("remove");
+    group.bench_function("BTreeMap", |b| {
+        b.iter_batched(
+            || map.clone(),
+            |mut map| {
+                for key in keys.iter() {
+                    map.remove(key);
+                }
+                map
+            },
+            criterion::BatchSize::LargeInput,
+        )
+    });
+    for order in ORDERS {
+        group.bench_with_input(BenchmarkId::new("BTree", order), &order, |b, &order| {
+            b.iter_batched(
+                || {
+                    let mut tree = BTree::with_order(order);
+                    tree.extend(keys.iter().map(|&key| (key, key)));
+                    tree
+                },
+                |mut tree| {
+                    for key in keys.iter() {
+                        tree.remove(key);
+                    }
+                    tree
+                },
+                criterion::BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn iterate(c: &mut Criterion) {
+    let keys = shuffled();
+    let map: BTreeMap<u64, u64> = keys.iter().map(|&key| (key, key)).collect();
+    let mut group = c.benchmark_group("iterate");
+    group.bench_function("BTreeMap", |b| b.iter(|| map.values().sum::<u64>()));
+    group.bench_function("BTreeMap range", |b| {
+        b.iter(|| {
+            map.range(KEYS / 4..KEYS / 2)
+                .map(|(_, value)| value)
+                .sum::<u64>()
+        })
+    });
+    for order in ORDERS {
+        let mut tree = BTree::with_order(order);
+        tree.extend(keys.iter().map(|&key| (key, key)));
+        group.bench_with_input(BenchmarkId::new("BTree", order), &tree, |b, tree| {
+            b.iter(|| tree.iter().map(|(_, value)| value).sum::<u64>())
+        });
+        group.bench_with_input(BenchmarkId::new("BTree range", order), &tree, |b, tree| {
+            b.iter(|| {
+                tree.range(KEYS / 4..KEYS / 2)
+                    .map(|(_, value)| value)
+                    .sum::<u64>()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, insert, get, remove, iterate);
+criterion_main!(benches);
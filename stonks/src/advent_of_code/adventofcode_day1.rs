@@ -1,9 +1,13 @@
-use std::env;
-use std::fs::File;
-use std::io::prelude::*;
+use crate::filecontroler::file::open_file;
 
 pub fn num_of_increases() {
-    let data = open_file("message.txt");
+    let data = match open_file("message.txt") {
+        Ok(data) => data,
+        Err(e) => {
+            println!("Couldn't read the puzzle input: {}", e);
+            return;
+        }
+    };
     let nums: Vec<i32> = data
         .split("\n")
         .map(|n| n.parse::<i32>().expect("Error parsing"))
@@ -21,20 +25,3 @@ pub fn num_of_increases() {
 
     println!("count: {}", count);
 }
-
-fn open_file(filename: &str) -> String {
-    let curr_exe = env::current_exe().unwrap();
-    let curr_dir = curr_exe
-        .parent()
-        .unwrap()
-        .parent()
-        .unwrap()
-        .parent()
-        .unwrap();
-    let file_loc = curr_dir.join("data").join(filename);
-    println!("file: {:?}", file_loc);
-    let mut file = File::open(file_loc).unwrap();
-    let mut contents = String::new();
-    file.read_to_string(&mut contents).unwrap();
-    contents
-}
@@ -0,0 +1,502 @@
+// the ships each player hides on their map, and where they are allowed to go:
+// every ship in a straight unbroken line of its length, no two on the same spot,
+// and with the no touch rule not even next to each other diagonally
+
+use super::{BattleshipLocation, Map, Ship};
+use rand::Rng;
+
+pub const CLASSIC_FLEET: [Ship<'static>; 5] = [
+    Ship {
+        id: 1,
+        name: "Destroyer",
+        length: 2,
+    },
+    Ship {
+        id: 2,
+        name: "Submarine",
+        length: 3,
+    },
+    Ship {
+        id: 3,
+        name: "Cruiser",
+        length: 3,
+    },
+    Ship {
+        id: 4,
+        name: "Battle Ship",
+        length: 4,
+    },
+    Ship {
+        id: 5,
+        name: "Carrier",
+        length: 5,
+    },
+];
+
+/// how far across or down a map can go, so a map always fits in memory
+pub const MAX_MAP_SIDE: usize = 1000;
+
+/// The map size and the ships to hide on it
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config<'a> {
+    pub width: usize,
+    pub height: usize,
+    pub fleet: Vec<Ship<'a>>,
+}
+
+impl<'a> Config<'a> {
+    pub fn classic() -> Config<'static> {
+        Config {
+            width: 10,
+            height: 10,
+            fleet: CLASSIC_FLEET.to_vec(),
+        }
+    }
+
+    /// Reads a game's setup, one setting a line:
+    ///
+    /// ```text
+    /// # lines starting with # are skipped
+    /// size 12 15
+    /// Destroyer 2
+    /// Battle Ship 4
+    /// ```
+    ///
+    /// `size` is the width and height, the map is 10 x 10 without it. Every other line
+    /// is a ship's name and length, and the ships get the ids 1, 2, 3 and on in the
+    /// order they are listed, which is how the map files write them.
+    pub fn parse(contents: &'a str) -> Result<Config<'a>, String> {
+        let mut config = Config {
+            fleet: Vec::new(),
+            ..Config::classic()
+        };
+        for line in contents.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (name, number) = line
+                .rsplit_once(' ')
+                .ok_or_else(|| format!("Can't read the line '{}'", line))?;
+            let number: usize = number
+                .parse()
+                .map_err(|_| format!("'{}' isn't a number in the line '{}'", number, line))?;
+            let name = name.trim();
+
+            if let Some(width) = name.strip_prefix("size ") {
+                config.width = width
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("'{}' isn't a width", width))?;
+                config.height = number;
+                continue;
+            }
+            let id = u8::try_from(config.fleet.len() + 1)
+                .map_err(|_| String::from("A fleet can't have more than 255 ships"))?;
+            config.fleet.push(Ship {
+                id,
+                name,
+                length: number,
+            });
+        }
+
+        config.check()?;
+        Ok(config)
+    }
+
+    /// Checks the map and fleet make a game that can be played: a map of at least one
+    /// spot and at most `MAX_MAP_SIDE` across and down, and at least one ship, each of
+    /// them fitting on the map
+    pub fn check(&self) -> Result<(), String> {
+        if self.width == 0 || self.height == 0 {
+            return Err(String::from(
+                "The map needs a width and height of at least 1",
+            ));
+        }
+        if self.width > MAX_MAP_SIDE || self.height > MAX_MAP_SIDE {
+            return Err(format!(
+                "The map can't be wider or taller than {}",
+                MAX_MAP_SIDE
+            ));
+        }
+        if self.fleet.is_empty() {
+            return Err(String::from("The fleet needs at least one ship"));
+        }
+        if let Some(ship) = self
+            .fleet
+            .iter()
+            .find(|ship| ship.length == 0 || ship.length > self.width.max(self.height))
+        {
+            return Err(format!(
+                "The {} is {} long, which doesn't fit on the map",
+                ship.name, ship.length
+            ));
+        }
+        Ok(())
+    }
+}
+
+// how many times a random fleet tries each ship, and the whole fleet, before giving up
+const MAX_PLACEMENT_TRIES: usize = 1000;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Direction {
+    // along a row, to the right
+    Across,
+    // along a column, down
+    Down,
+}
+
+impl<'a> Map<'a> {
+    /// Puts `ship` on the map starting at `col`, `row` and going in `direction`.
+    /// Nothing changes if it would hang off the map, land on another ship or,
+    /// with `no_touch`, be right next to one.
+    pub fn place_ship(
+        &mut self,
+        ship: Ship<'a>,
+        col: usize,
+        row: usize,
+        direction: Direction,
+        no_touch: bool,
+    ) -> Result<(), String> {
+        let (start, size) = match direction {
+            Direction::Across => (col, self.width),
+            Direction::Down => (row, self.height),
+        };
+        let fits = match start.checked_add(ship.length) {
+            Some(end) => ship.length > 0 && end <= size,
+            None => false,
+        };
+        if !fits || col >= self.width || row >= self.height {
+            return Err(format!("The {} doesn't fit on the map there", ship.name));
+        }
+
+        let spots: Vec<usize> = (0..ship.length)
+            .map(|i| match direction {
+                Direction::Across => self.get_index(col + i, row),
+                Direction::Down => self.get_index(col, row + i),
+            })
+            .collect();
+        for &idx in spots.iter() {
+            if let Some(other) = self.ship_at(idx) {
+                return Err(format!(
+                    "The {} would overlap the {}",
+                    ship.name, other.name
+                ));
+            }
+            if no_touch {
+                if let Some(other) = self.neighbors(idx).find_map(|n| self.ship_at(n)) {
+                    return Err(format!("The {} would touch the {}", ship.name, other.name));
+                }
+            }
+        }
+
+        for idx in spots {
+            self.map[idx] = BattleshipLocation::OkShip(ship);
+        }
+        Ok(())
+    }
+
+    /// Checks every ship in `fleet` is on the map once, as a straight unbroken line of
+    /// its length, and that there are no other ships. With `no_touch` no two ships may
+    /// be next to each other, diagonally included.
+    pub fn check_fleet(&self, fleet: &[Ship<'a>], no_touch: bool) -> Result<(), String> {
+        for (i, ship) in fleet.iter().enumerate() {
+            if fleet[..i].iter().any(|other| other.id == ship.id) {
+                return Err(format!("There are two ships with the id {}", ship.id));
+            }
+        }
+        for idx in 0..self.map.len() {
+            if let Some(ship) = self.ship_at(idx) {
+                if !fleet.iter().any(|s| s.id == ship.id) {
+                    return Err(format!("Ship {} isn't in the fleet", ship.id));
+                }
+            }
+        }
+
+        for ship in fleet {
+            let spots = self.ship_spots(ship.id);
+            if spots.len() != ship.length {
+                return Err(format!(
+                    "The {} takes {} spots but there are {} on the map",
+                    ship.name,
+                    ship.length,
+                    spots.len()
+                ));
+            }
+
+            let rows: Vec<usize> = spots.iter().map(|idx| idx / self.width).collect();
+            let step = if rows.iter().all(|&row| row == rows[0]) {
+                1
+            } else if spots
+                .iter()
+                .all(|idx| idx % self.width == spots[0] % self.width)
+            {
+                self.width
+            } else {
+                return Err(format!("The {} isn't in a straight line", ship.name));
+            };
+            if spots.windows(2).any(|pair| pair[1] - pair[0] != step) {
+                return Err(format!("The {} has a gap in it", ship.name));
+            }
+
+            if no_touch {
+                for &idx in spots.iter() {
+                    let touching = self
+                        .neighbors(idx)
+                        .filter_map(|n| self.ship_at(n))
+                        .find(|other| other.id != ship.id);
+                    if let Some(other) = touching {
+                        return Err(format!("The {} touches the {}", ship.name, other.name));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// A map with every ship in `fleet` placed at random, following the same rules
+    /// as `check_fleet`. Fails if it can't find room for them all.
+    pub fn random_fleet<R: Rng + ?Sized>(
+        width: usize,
+        height: usize,
+        fleet: &[Ship<'a>],
+        no_touch: bool,
+        rng: &mut R,
+    ) -> Result<Map<'a>, String> {
+        // the big ships are the hard ones to fit, so they go first
+        let mut ships = fleet.to_vec();
+        ships.sort_by_key(|ship| std::cmp::Reverse(ship.length));
+
+        for _ in 0..MAX_PLACEMENT_TRIES {
+            let mut map = Map::new(width, height);
+            let placed = ships.iter().all(|&ship| {
+                (0..MAX_PLACEMENT_TRIES).any(|_| {
+                    let direction = if rng.gen() {
+                        Direction::Across
+                    } else {
+                        Direction::Down
+                    };
+                    let col = rng.gen_range(0..map.width);
+                    let row = rng.gen_range(0..map.height);
+                    map.place_ship(ship, col, row, direction, no_touch).is_ok()
+                })
+            });
+            if placed {
+                map.check_fleet(fleet, no_touch)?;
+                return Ok(map);
+            }
+        }
+        Err(String::from(
+            "Couldn't find room on the map for the whole fleet",
+        ))
+    }
+
+    pub(super) fn ship_at(&self, idx: usize) -> Option<Ship<'a>> {
+        match self.map[idx] {
+            BattleshipLocation::OkShip(ship)
+            | BattleshipLocation::HitShip(ship)
+            | BattleshipLocation::DestroyedShip(ship) => Some(ship),
+            BattleshipLocation::Empty | BattleshipLocation::Miss => None,
+        }
+    }
+
+    // where the ship with `id` is, in order
+    pub(super) fn ship_spots(&self, id: u8) -> Vec<usize> {
+        (0..self.map.len())
+            .filter(|&idx| self.ship_at(idx).map(|ship| ship.id) == Some(id))
+            .collect()
+    }
+
+    // the up to 8 spots around `idx`
+    pub(super) fn neighbors(&self, idx: usize) -> impl Iterator<Item = usize> + '_ {
+        let (col, row) = ((idx % self.width) as isize, (idx / self.width) as isize);
+        (-1..=1)
+            .flat_map(move |d_row| (-1..=1).map(move |d_col| (col + d_col, row + d_row)))
+            .filter(move |&(c, r)| {
+                (c, r) != (col, row)
+                    && c >= 0
+                    && r >= 0
+                    && (c as usize) < self.width
+                    && (r as usize) < self.height
+            })
+            .map(move |(c, r)| self.get_index(c as usize, r as usize))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn parse(contents: &str, no_touch: bool) -> Result<(), String> {
+        Map::new(10, 10).parse_map_config(contents, &CLASSIC_FLEET, no_touch)
+    }
+
+    #[test]
+    fn test_data_maps_are_legal() {
+        let player_1 = include_str!("../../data/player_1_battleship.txt");
+        let player_2 = include_str!("../../data/player_2_battleship.txt");
+        assert_eq!(parse(player_1, false), Ok(()));
+        assert_eq!(parse(player_2, false), Ok(()));
+        // the destroyer and submarine in player 1's map touch at a corner
+        assert_eq!(
+            parse(player_1, true),
+            Err("The Destroyer touches the Submarine".to_string())
+        );
+    }
+
+    #[test]
+    fn test_bad_fleets() {
+        let player_2 = include_str!("../../data/player_2_battleship.txt");
+        // the top of the battle ship moved down and to the side of its end
+        let bent = player_2
+            .replacen(". . . . . . . . . .", ". . . . . . . 4 . .", 1)
+            .replacen(". . . . . . . . 4 .\n2", ". . . . . . . . . .\n2", 1);
+        assert_eq!(
+            parse(&bent, false),
+            Err("The Battle Ship isn't in a straight line".to_string())
+        );
+
+        let short = player_2.replacen("5 5 5 5 5", "5 5 5 5 .", 1);
+        assert_eq!(
+            parse(&short, false),
+            Err("The Carrier takes 5 spots but there are 4 on the map".to_string())
+        );
+
+        let gap = player_2.replacen("5 5 5 5 5 .", "5 5 5 5 . 5", 1);
+        assert_eq!(
+            parse(&gap, false),
+            Err("The Carrier has a gap in it".to_string())
+        );
+
+        let stranger = player_2.replacen(". 3 3 3", "7 3 3 3", 1);
+        assert_eq!(
+            parse(&stranger, false),
+            Err("Strange character: 7".to_string())
+        );
+
+        assert!(parse(". . .", false).is_err());
+    }
+
+    #[test]
+    fn test_place_ship() {
+        let mut map = Map::new(10, 10);
+        let [destroyer, submarine, ..] = CLASSIC_FLEET;
+        assert_eq!(
+            map.place_ship(destroyer, 0, 0, Direction::Across, true),
+            Ok(())
+        );
+        assert_eq!(
+            map.place_ship(submarine, 1, 0, Direction::Down, false),
+            Err("The Submarine would overlap the Destroyer".to_string())
+        );
+        assert_eq!(
+            map.place_ship(submarine, 2, 1, Direction::Down, true),
+            Err("The Submarine would touch the Destroyer".to_string())
+        );
+        assert_eq!(
+            map.place_ship(submarine, 9, 8, Direction::Down, false),
+            Err("The Submarine doesn't fit on the map there".to_string())
+        );
+        assert_eq!(
+            map.place_ship(submarine, 2, 1, Direction::Down, false),
+            Ok(())
+        );
+        assert_eq!(map.ship_spots(2), vec![12, 22, 32]);
+
+        // nothing that can't fit gets as far as working out its spots
+        let raft = Ship {
+            id: 3,
+            name: "Raft",
+            length: 0,
+        };
+        let huge = Ship {
+            id: 3,
+            name: "Huge",
+            length: usize::MAX,
+        };
+        for (ship, col, row, direction) in [
+            (raft, 5, 5, Direction::Across),
+            (raft, 0, 0, Direction::Down),
+            (huge, 5, 5, Direction::Across),
+            (huge, 5, 5, Direction::Down),
+            (destroyer, usize::MAX, 5, Direction::Across),
+            (destroyer, 5, usize::MAX, Direction::Down),
+            (destroyer, 5, 10, Direction::Across),
+        ] {
+            assert_eq!(
+                map.place_ship(ship, col, row, direction, false),
+                Err(format!("The {} doesn't fit on the map there", ship.name))
+            );
+        }
+    }
+
+    #[test]
+    fn test_random_fleets_are_legal() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for no_touch in [false, true] {
+            for _ in 0..100 {
+                let map = Map::random_fleet(10, 10, &CLASSIC_FLEET, no_touch, &mut rng).unwrap();
+                assert_eq!(map.check_fleet(&CLASSIC_FLEET, no_touch), Ok(()));
+            }
+        }
+
+        let too_big = [Ship {
+            id: 1,
+            name: "Ark",
+            length: 11,
+        }];
+        assert!(Map::random_fleet(10, 10, &too_big, false, &mut rng).is_err());
+        assert!(Map::random_fleet(11, 3, &too_big, false, &mut rng).is_ok());
+    }
+
+    #[test]
+    fn test_config() {
+        let config =
+            Config::parse("# a long thin sea\nsize 20 4\n\nRaft 1\nBattle Ship 4\nLong Boat 12\n")
+                .unwrap();
+        assert_eq!((config.width, config.height), (20, 4));
+        assert_eq!(
+            config.fleet,
+            vec![
+                Ship {
+                    id: 1,
+                    name: "Raft",
+                    length: 1
+                },
+                Ship {
+                    id: 2,
+                    name: "Battle Ship",
+                    length: 4
+                },
+                Ship {
+                    id: 3,
+                    name: "Long Boat",
+                    length: 12
+                },
+            ]
+        );
+        let mut rng = StdRng::seed_from_u64(3);
+        let map = Map::random_fleet(20, 4, &config.fleet, true, &mut rng).unwrap();
+        assert_eq!(map.check_fleet(&config.fleet, true), Ok(()));
+
+        let classic =
+            Config::parse("Destroyer 2\nSubmarine 3\nCruiser 3\nBattle Ship 4\nCarrier 5").unwrap();
+        assert_eq!(classic, Config::classic());
+
+        assert!(Config::parse("").is_err());
+        assert!(Config::parse("Raft").is_err());
+        assert!(Config::parse("Raft one").is_err());
+        assert!(Config::parse("size 0 5\nRaft 1").is_err());
+        assert!(Config::parse("size 1000 1000\nRaft 1").is_ok());
+        assert!(Config::parse("size 1001 5\nRaft 1").is_err());
+        assert!(Config::parse("size 5 18446744073709551615\nRaft 1").is_err());
+        assert_eq!(
+            Config::parse("size 5 5\nLong Boat 6"),
+            Err(String::from(
+                "The Long Boat is 6 long, which doesn't fit on the map"
+            ))
+        );
+    }
+}
@@ -0,0 +1,534 @@
+// store 2 battle ship maps
+// print out maps either seen or just showing where the hits and misses are
+// take user input to setup (maybe just make a file)
+// take user input as a missel every turn
+// track location of missles
+// track ships remaining
+
+mod fleet;
+mod network;
+mod opponent;
+mod weapons;
+
+use crate::filecontroler::file;
+use fleet::{Config, CLASSIC_FLEET};
+use opponent::{Difficulty, Opponent};
+use std::io::{stdin, stdout, Write};
+use std::thread::sleep;
+use std::time::Duration;
+use weapons::{Arsenal, Impact, Shot};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Ship<'a> {
+    id: u8,
+    name: &'a str,
+    /// how many spots in a line the ship takes up
+    length: usize,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum BattleshipLocation<'a> {
+    Empty,
+    OkShip(Ship<'a>),
+    HitShip(Ship<'a>),
+    DestroyedShip(Ship<'a>),
+    Miss,
+}
+
+#[derive(Clone, Debug)]
+struct Map<'a> {
+    height: usize,
+    width: usize,
+    map: Vec<BattleshipLocation<'a>>,
+    // where sonar has found a ship, which shows on the map until it's hit
+    spotted: Vec<bool>,
+}
+
+impl<'a> Map<'a> {
+    pub fn new(width: usize, height: usize) -> Self {
+        let m = vec![BattleshipLocation::Empty; height * width];
+        Map {
+            height,
+            width,
+            map: m,
+            spotted: vec![false; height * width],
+        }
+    }
+
+    fn read_map_config(&mut self, filename: &str, fleet: &[Ship<'a>]) -> Result<(), String> {
+        let contents = open_file(filename)?;
+        self.parse_map_config(&contents, fleet, false)
+    }
+
+    /// Reads a map written as rows of `.` for water and ship ids, separated by spaces,
+    /// then checks the ships are all there and placed by the rules, see `check_fleet`.
+    fn parse_map_config(
+        &mut self,
+        contents: &str,
+        fleet: &[Ship<'a>],
+        no_touch: bool,
+    ) -> Result<(), String> {
+        let mut map: Vec<BattleshipLocation> = Vec::new();
+        for c in contents.split_whitespace() {
+            let loc = match c {
+                "." => BattleshipLocation::Empty,
+                _ => match fleet.iter().find(|ship| ship.id.to_string() == c) {
+                    Some(ship) => BattleshipLocation::OkShip(*ship),
+                    None => return Err(format!("Strange character: {}", c)),
+                },
+            };
+            map.push(loc);
+        }
+
+        if map.len() != self.width * self.height {
+            return Err(format!(
+                "The size of the map is not {} x {}, it has {} spots",
+                self.width,
+                self.height,
+                map.len()
+            ));
+        }
+
+        let old_map = std::mem::replace(&mut self.map, map);
+        if let Err(e) = self.check_fleet(fleet, no_touch) {
+            self.map = old_map;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// a missile at `col`, `row_idx`, see `missle_strike` for the other weapons
+    fn strike(&mut self, col: usize, row_idx: usize) -> Result<Impact, String> {
+        if row_idx >= self.height || col >= self.width {
+            return Err("row_idx or col is larger than map width".to_string());
+        }
+
+        let idx = self.get_index(col, row_idx);
+        return match self.map[idx] {
+            BattleshipLocation::Empty => {
+                self.map[idx] = BattleshipLocation::Miss;
+                Ok(Impact::Miss)
+            }
+            BattleshipLocation::OkShip(ship) => {
+                self.map[idx] = BattleshipLocation::HitShip(ship);
+                if !self.map.contains(&BattleshipLocation::OkShip(ship)) {
+                    // there is not okay part of the ship left
+                    self.map = self
+                        .map
+                        .iter_mut()
+                        .map(|&mut x| {
+                            if x == BattleshipLocation::HitShip(ship) {
+                                BattleshipLocation::DestroyedShip(ship)
+                            } else {
+                                x
+                            }
+                        })
+                        .collect::<Vec<BattleshipLocation>>();
+                    Ok(Impact::Sunk(ship.id))
+                } else {
+                    Ok(Impact::Hit)
+                }
+            }
+            BattleshipLocation::HitShip(_ship) => {
+                Err("You have already shot and hit this ship at this location".to_string())
+            }
+            BattleshipLocation::Miss => {
+                Err("You have already shot and missed at this location".to_string())
+            }
+            BattleshipLocation::DestroyedShip(_ship) => {
+                Err("You have already shot and sunk this ship".to_string())
+            }
+        };
+    }
+
+    fn all_destroyed(&self) -> bool {
+        self.map.iter().all(|x| match x {
+            BattleshipLocation::OkShip(_) => false,
+            _ => true,
+        })
+    }
+
+    // how many ships have a part not hit yet
+    fn ships_afloat(&self) -> usize {
+        self.view().remaining.len()
+    }
+
+    fn get_index(&self, col: usize, row: usize) -> usize {
+        self.width * row + col
+    }
+
+    fn print_map(&self, private: bool) {
+        // println!("Map - Top Secret?: {}", private);
+        print_grid(self.width, self.height, |col, row| {
+            match self.map[self.get_index(col, row)] {
+                BattleshipLocation::Miss => "o".to_string(),
+                BattleshipLocation::DestroyedShip(_) => "X".to_string(),
+                BattleshipLocation::HitShip(_) => "x".to_string(),
+                BattleshipLocation::Empty => ".".to_string(),
+                BattleshipLocation::OkShip(ship) => {
+                    if private {
+                        ship.id.to_string()
+                    } else if self.spotted[self.get_index(col, row)] {
+                        "?".to_string()
+                    } else {
+                        ".".to_string()
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// prints a map with the column numbers along the top and the row names down the side
+fn print_grid(width: usize, height: usize, spot: impl Fn(usize, usize) -> String) {
+    let spots: Vec<Vec<String>> = (0..height)
+        .map(|row| (0..width).map(|col| spot(col, row)).collect())
+        .collect();
+    // wide enough for every column number and whatever is in the spots
+    let cell = spots
+        .iter()
+        .flatten()
+        .map(String::len)
+        .chain([(width - 1).to_string().len()])
+        .max()
+        .unwrap_or(1);
+    let name = row_name(height - 1).len();
+    print!("{:name$}  ", "", name = name);
+    for x in 0..width {
+        print!("{:>cell$} ", x, cell = cell);
+    }
+    println!();
+    print!("{:name$} ", "", name = name);
+    for _ in 0..width {
+        print!("{}", "_".repeat(cell + 1));
+    }
+    println!();
+    for (row, spots) in spots.iter().enumerate() {
+        print!("{:>name$}| ", row_name(row), name = name);
+        for to_print in spots {
+            print!("{:>cell$} ", to_print, cell = cell)
+        }
+        println!();
+    }
+    println!();
+}
+
+/// Rows are named with letters like spreadsheet columns: A to Z, then AA, AB and on
+fn row_name(row: usize) -> String {
+    let mut n = row + 1;
+    let mut name = Vec::new();
+    while n > 0 {
+        n -= 1;
+        name.push((b'A' + (n % 26) as u8) as char);
+        n /= 26;
+    }
+    name.iter().rev().collect()
+}
+
+/// the row a name from `row_name` is for, upper or lower case
+fn row_index(name: &str) -> Option<usize> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let mut n: usize = 0;
+    for c in name.to_ascii_uppercase().bytes() {
+        n = n.checked_mul(26)?.checked_add((c - b'A' + 1) as usize)?;
+    }
+    Some(n - 1)
+}
+
+/// a file from the data directory, see `filecontroler::file`
+fn open_file(filename: &str) -> Result<String, String> {
+    file::open_file(filename).map_err(|e| format!("Cannot open {}: {}", filename, e))
+}
+
+/// asks for a shot until one is typed in that can be fired with what's in `arsenal`
+fn get_user_shot(message: &str, arsenal: &Arsenal) -> Shot {
+    loop {
+        let mut s = String::new();
+        println!("{}", message);
+        if *arsenal != Arsenal::NONE {
+            println!("Special weapons left: {}", arsenal);
+            println!("Fire them with e.g. 'bomb D5', 'torpedo D5 down' or 'sonar D5'.");
+        }
+        print!("Please tell me where to launch the missle! e.g. 'D5'.\n> ");
+        let _ = stdout().flush();
+        stdin()
+            .read_line(&mut s)
+            .expect("Did not enter a correct string");
+
+        match Shot::parse(&s) {
+            Ok(shot) if arsenal.left(shot.weapon) == Some(0) => {
+                println!("You have none of those left");
+            }
+            Ok(shot) => return shot,
+            Err(e) => println!("Please try again. {}", e),
+        }
+    }
+}
+
+/// One player's turn at `map`. With `salvo` they fire that many shots, otherwise
+/// they keep going until they miss.
+fn players_turn(player: usize, map: &mut Map, salvo: Option<usize>, arsenal: &mut Arsenal) {
+    println!("PLAYER {}'s TURN", player);
+    let mut fired = 0;
+    loop {
+        map.print_map(false);
+        if let Some(shots) = salvo {
+            println!("Shot {} of {}", fired + 1, shots);
+        }
+        let shot = get_user_shot(
+            &format!("Player {}, choose a location to strike!", player),
+            arsenal,
+        );
+        let result = match map.missle_strike(&shot) {
+            Ok(result) => result,
+            Err(e) => {
+                println!("{}", e);
+                continue;
+            }
+        };
+        arsenal.spend(shot.weapon);
+        println!("{}", result);
+        fired += 1;
+
+        if map.all_destroyed() {
+            break;
+        }
+        match salvo {
+            Some(shots) if fired < shots => {}
+            None if result.hit() => println!("Player {}, GO Again", player),
+            _ => break,
+        }
+    }
+}
+
+/// the computer's turn at `map`, see `players_turn`. It only fires missiles.
+fn computers_turn(opponent: &mut dyn Opponent, map: &mut Map, salvo: Option<usize>) {
+    println!("THE COMPUTER'S TURN");
+    let mut fired = 0;
+    loop {
+        let (col, row) = opponent.choose_shot(&map.view());
+        let impact = map
+            .strike(col, row)
+            .expect("the computer only shoots at spots it hasn't tried yet");
+        println!("The computer fires at {}{}. {}", row_name(row), col, impact);
+        fired += 1;
+        let again = match salvo {
+            Some(shots) => fired < shots,
+            None => impact != Impact::Miss,
+        };
+        if !again || map.all_destroyed() {
+            break;
+        }
+    }
+    map.print_map(true);
+}
+
+/// asks until one of `choices` is typed in, and returns which one
+fn get_choice(message: &str, choices: &[&str]) -> usize {
+    loop {
+        let mut s = String::new();
+        print!("{} ({})\n> ", message, choices.join("/"));
+        let _ = stdout().flush();
+        stdin()
+            .read_line(&mut s)
+            .expect("Did not enter a correct string");
+        let answer = s.trim().to_lowercase();
+        if let Some(i) = choices.iter().position(|choice| *choice == answer) {
+            return i;
+        }
+        println!("Please answer with one of {}", choices.join(", "));
+    }
+}
+
+const CONFIG_FILE: &str = "battleship_config.txt";
+
+fn take_turns() {
+    println!("\n++++++++++++++\nChanging Turns!\n++++++++++++++\n");
+    sleep(Duration::from_millis(1500));
+}
+
+/// the player's map from their file in the data directory, or a random fleet without one
+fn setup_map<'a>(filename: &str, config: &Config<'a>) -> Result<Map<'a>, String> {
+    let mut map = Map::new(config.width, config.height);
+    match map.read_map_config(filename, &config.fleet) {
+        Ok(()) => Ok(map),
+        Err(e) => {
+            println!("{}\nPlacing a random fleet instead.", e);
+            Map::random_fleet(
+                config.width,
+                config.height,
+                &config.fleet,
+                false,
+                &mut rand::thread_rng(),
+            )
+        }
+    }
+}
+
+/// the map size and fleet from the config file, the classic 10 x 10 game without one
+fn read_config(contents: &Result<String, String>) -> Config<'_> {
+    match contents
+        .as_deref()
+        .map_err(|e| e.clone())
+        .and_then(Config::parse)
+    {
+        Ok(config) => config,
+        Err(e) => {
+            println!("{}\nPlaying the classic game.", e);
+            Config::classic()
+        }
+    }
+}
+
+pub fn play_battleship() {
+    println!("Yeah battleship");
+    // with one player, the computer is player 2
+    let mut computer = match get_choice(
+        "How many players? Or host or join a game over the network.",
+        &["1", "2", "host", "join"],
+    ) {
+        0 => {
+            let level = get_choice("How good is the computer?", &["easy", "medium", "hard"]);
+            Some(Difficulty::ALL[level].opponent(rand::random()))
+        }
+        1 => None,
+        choice => {
+            network::play_over_network(choice == 2);
+            return;
+        }
+    };
+    // in salvo every ship still afloat fires a shot a turn
+    let salvo = get_choice("Which rules?", &["classic", "salvo"]) == 1;
+    let mut arsenals = match get_choice("Play with special weapons?", &["no", "yes"]) {
+        0 => [Arsenal::NONE; 2],
+        _ => [Arsenal::SPECIAL; 2],
+    };
+    let contents = open_file(CONFIG_FILE);
+    let config = read_config(&contents);
+    let maps = setup_map("player_1_battleship.txt", &config)
+        .and_then(|one| Ok((one, setup_map("player_2_battleship.txt", &config)?)));
+    let (mut player_one_map, mut player_two_map) = match maps {
+        Ok(maps) => maps,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+
+    let winner: u8 = 'outer: loop {
+        let shots = Some(player_two_map.ships_afloat()).filter(|_| salvo);
+        match computer.as_mut() {
+            Some(opponent) => computers_turn(opponent.as_mut(), &mut player_one_map, shots),
+            None => players_turn(2, &mut player_one_map, shots, &mut arsenals[1]),
+        }
+        if player_one_map.all_destroyed() {
+            break 'outer 2;
+        }
+        take_turns();
+
+        let shots = Some(player_one_map.ships_afloat()).filter(|_| salvo);
+        players_turn(1, &mut player_two_map, shots, &mut arsenals[0]);
+        if player_two_map.all_destroyed() {
+            break 'outer 1;
+        }
+        take_turns();
+    };
+
+    println!("Congratulations! Player {} won!", winner);
+    sleep(Duration::from_millis(1500));
+    println!("Player 1 map");
+    player_one_map.print_map(true);
+    sleep(Duration::from_millis(1500));
+    println!("Player 2 map");
+    player_two_map.print_map(true);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::weapons::{Cell, Shot, StrikeResult};
+    use super::{row_index, row_name, BattleshipLocation, Impact, Map};
+
+    #[test]
+    fn test_map() {
+        let map = Map::new(10, 10);
+
+        assert_eq!(map.map[0], BattleshipLocation::Empty);
+        assert_eq!(map.get_index(2, 2), 22 as usize);
+    }
+
+    fn missile(map: &mut Map, spot: &str) -> Result<StrikeResult, String> {
+        map.missle_strike(&Shot::parse(spot)?)
+    }
+
+    fn miss(col: usize, row: usize) -> Result<StrikeResult, String> {
+        Ok(StrikeResult::Fired(vec![Cell {
+            col,
+            row,
+            impact: Impact::Miss,
+        }]))
+    }
+
+    #[test]
+    fn test_map_bad_missle_strike() {
+        let mut map = Map::new(10, 10);
+
+        assert_eq!(
+            missile(&mut map, "A10"),
+            Err("row_idx or col is larger than map width".to_string())
+        );
+
+        assert_eq!(
+            missile(&mut map, "a15"),
+            Err("row_idx or col is larger than map width".to_string())
+        );
+
+        assert_eq!(
+            missile(&mut map, "K1"),
+            Err("row_idx or col is larger than map width".to_string())
+        );
+
+        assert_eq!(
+            missile(&mut map, "Y1"),
+            Err("row_idx or col is larger than map width".to_string())
+        );
+
+        assert_eq!(missile(&mut map, "B3"), miss(3, 1));
+        assert_eq!(
+            missile(&mut map, "B3"),
+            Err("You have already shot and missed at this location".to_string())
+        );
+    }
+
+    #[test]
+    fn test_big_map() {
+        let mut map = Map::new(30, 28);
+
+        assert_eq!(missile(&mut map, "K12"), miss(12, 10));
+        assert_eq!(map.map[map.get_index(12, 10)], BattleshipLocation::Miss);
+        assert_eq!(missile(&mut map, "ab29"), miss(29, 27));
+        assert_eq!(
+            missile(&mut map, "AC0"),
+            Err("row_idx or col is larger than map width".to_string())
+        );
+    }
+
+    #[test]
+    fn test_row_names() {
+        for (row, name) in [
+            (0, "A"),
+            (9, "J"),
+            (25, "Z"),
+            (26, "AA"),
+            (27, "AB"),
+            (702, "AAA"),
+        ] {
+            assert_eq!(row_name(row), name);
+            assert_eq!(row_index(name), Some(row));
+        }
+        assert_eq!(row_index("k"), Some(10));
+        assert_eq!(row_index(""), None);
+        assert_eq!(row_index("A1"), None);
+    }
+}
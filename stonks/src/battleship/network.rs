@@ -0,0 +1,869 @@
+// two players in their own processes, talking over tcp one line at a time.
+//
+// the host picks the map size and fleet and sends them over. after that the only
+// things sent are where each player shoots and what the shot hit, so neither player
+// sees the other's fleet during the game. instead each side sends a commitment at the
+// start, a sha256 of a random salt and its map, and reveals the salt and map at the
+// end. the other side then checks the map is the one committed to, that it follows
+// the rules, and that every result it was told during the game was true. a player
+// caught lying loses.
+//
+// the messages:
+//   SIZE <width> <height>          the host's map size
+//   SHIP <length> <name>           one per ship in the host's fleet, in id order
+//   COMMIT <sha256 hex>            after the setup, from both sides
+//   SHOT <col> <row>               rows by number
+//   RESULT MISS | RESULT HIT | RESULT SUNK <id> <spot> <spot> ...
+//   REVEAL <salt> <map>            the map written like the map files, on one line
+
+use super::fleet::Config;
+use super::opponent::{Opponent, Seen, View};
+use super::weapons::{Arsenal, Impact};
+use super::{get_user_shot, read_config, row_name, setup_map, Map, Ship, CONFIG_FILE};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::io::{stdin, stdout, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::str::FromStr;
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:7878";
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Outcome {
+    Miss,
+    Hit,
+    // the id of the ship sunk and every spot it was on
+    Sunk { id: u8, spots: Vec<usize> },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Message {
+    Size { width: usize, height: usize },
+    Ship { length: usize, name: String },
+    Commit(String),
+    Shot { col: usize, row: usize },
+    Result(Outcome),
+    Reveal { salt: u128, map: String },
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Message::Size { width, height } => write!(f, "SIZE {} {}", width, height),
+            Message::Ship { length, name } => write!(f, "SHIP {} {}", length, name),
+            Message::Commit(hash) => write!(f, "COMMIT {}", hash),
+            Message::Shot { col, row } => write!(f, "SHOT {} {}", col, row),
+            Message::Result(Outcome::Miss) => write!(f, "RESULT MISS"),
+            Message::Result(Outcome::Hit) => write!(f, "RESULT HIT"),
+            Message::Result(Outcome::Sunk { id, spots }) => {
+                write!(f, "RESULT SUNK {}", id)?;
+                spots.iter().try_for_each(|spot| write!(f, " {}", spot))
+            }
+            Message::Reveal { salt, map } => write!(f, "REVEAL {} {}", salt, map),
+        }
+    }
+}
+
+impl FromStr for Message {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Message, String> {
+        let bad = || format!("Can't read the message '{}'", line);
+        let (kind, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let numbers = || -> Result<Vec<usize>, String> {
+            rest.split_whitespace()
+                .map(|n| n.parse().map_err(|_| bad()))
+                .collect()
+        };
+        let message = match kind {
+            "SIZE" => match numbers()?[..] {
+                [width, height] => Message::Size { width, height },
+                _ => return Err(bad()),
+            },
+            "SHIP" => {
+                let (length, name) = rest.split_once(' ').ok_or_else(bad)?;
+                Message::Ship {
+                    length: length.parse().map_err(|_| bad())?,
+                    name: name.to_string(),
+                }
+            }
+            "COMMIT" if !rest.is_empty() => Message::Commit(rest.to_string()),
+            "SHOT" => match numbers()?[..] {
+                [col, row] => Message::Shot { col, row },
+                _ => return Err(bad()),
+            },
+            "RESULT" => match rest.split_once(' ').unwrap_or((rest, "")) {
+                ("MISS", "") => Message::Result(Outcome::Miss),
+                ("HIT", "") => Message::Result(Outcome::Hit),
+                ("SUNK", sunk) => {
+                    let mut numbers = sunk.split_whitespace().map(|n| n.parse::<usize>());
+                    let id = match numbers.next() {
+                        Some(Ok(id)) => u8::try_from(id).map_err(|_| bad())?,
+                        _ => return Err(bad()),
+                    };
+                    let spots = numbers.collect::<Result<_, _>>().map_err(|_| bad())?;
+                    Message::Result(Outcome::Sunk { id, spots })
+                }
+                _ => return Err(bad()),
+            },
+            "REVEAL" => {
+                let (salt, map) = rest.split_once(' ').ok_or_else(bad)?;
+                Message::Reveal {
+                    salt: salt.parse().map_err(|_| bad())?,
+                    map: map.to_string(),
+                }
+            }
+            _ => return Err(bad()),
+        };
+        Ok(message)
+    }
+}
+
+struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    fn new(stream: TcpStream) -> Result<Connection, String> {
+        let writer = stream
+            .try_clone()
+            .map_err(|e| format!("Can't use the connection: {}", e))?;
+        Ok(Connection {
+            reader: BufReader::new(stream),
+            writer,
+        })
+    }
+
+    fn send(&mut self, message: &Message) -> Result<(), String> {
+        writeln!(self.writer, "{}", message).map_err(|e| format!("Lost the other player: {}", e))
+    }
+
+    fn receive(&mut self) -> Result<Message, String> {
+        let mut line = String::new();
+        match self.reader.read_line(&mut line) {
+            Ok(0) => Err(String::from("The other player left")),
+            Ok(_) => line.trim_end().parse(),
+            Err(e) => Err(format!("Lost the other player: {}", e)),
+        }
+    }
+}
+
+fn unexpected(message: Message) -> String {
+    format!("Didn't expect the message '{}'", message)
+}
+
+/// the hash sent at the start of the game, that the map revealed at the end has to match
+fn commitment(salt: u128, map: &str) -> String {
+    Sha256::digest(format!("{}:{}", salt, map).as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+impl<'a> Map<'a> {
+    // the map written like the map files, ship ids and `.` for water, all on one line
+    fn layout(&self) -> String {
+        (0..self.map.len())
+            .map(|idx| match self.ship_at(idx) {
+                Some(ship) => ship.id.to_string(),
+                None => String::from("."),
+            })
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    // shoots at `idx` and says what it hit the way the other player is told
+    fn outcome_of_strike(&mut self, idx: usize) -> Result<Outcome, String> {
+        Ok(match self.strike(idx % self.width, idx / self.width)? {
+            Impact::Miss => Outcome::Miss,
+            Impact::Hit => Outcome::Hit,
+            Impact::Sunk(id) => Outcome::Sunk {
+                id,
+                spots: self.ship_spots(id),
+            },
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum GameResult {
+    Won,
+    Lost,
+    // the other player cheated, and why that's known
+    Cheated(String),
+}
+
+/// The map size and fleet the host sent, with the names owned so a `Config` can borrow them
+pub struct Setup {
+    pub width: usize,
+    pub height: usize,
+    pub ships: Vec<(usize, String)>,
+}
+
+impl Setup {
+    pub fn config(&self) -> Result<Config<'_>, String> {
+        let mut fleet = Vec::new();
+        for (i, (length, name)) in self.ships.iter().enumerate() {
+            let id = u8::try_from(i + 1)
+                .map_err(|_| String::from("A fleet can't have more than 255 ships"))?;
+            fleet.push(Ship {
+                id,
+                name,
+                length: *length,
+            });
+        }
+        let config = Config {
+            width: self.width,
+            height: self.height,
+            fleet,
+        };
+        config
+            .check()
+            .map_err(|e| format!("The host sent a game that can't be played: {}", e))?;
+        Ok(config)
+    }
+}
+
+/// A connection that has agreed on the map and fleet, ready to play once there's a map
+pub struct Pending {
+    connection: Connection,
+    host: bool,
+    // the host's, which the joining side already has
+    their_commitment: Option<String>,
+}
+
+/// Starts a game as the host on a player that just connected, sending them `config`
+pub fn host(stream: TcpStream, config: &Config) -> Result<Pending, String> {
+    let mut connection = Connection::new(stream)?;
+    connection.send(&Message::Size {
+        width: config.width,
+        height: config.height,
+    })?;
+    for ship in config.fleet.iter() {
+        connection.send(&Message::Ship {
+            length: ship.length,
+            name: ship.name.to_string(),
+        })?;
+    }
+    Ok(Pending {
+        connection,
+        host: true,
+        their_commitment: None,
+    })
+}
+
+/// Joins the game hosted at the other end of `stream`, and finds out what it's played with
+pub fn join(stream: TcpStream) -> Result<(Setup, Pending), String> {
+    let mut connection = Connection::new(stream)?;
+    let mut setup = Setup {
+        width: 0,
+        height: 0,
+        ships: Vec::new(),
+    };
+    let their_commitment = loop {
+        match connection.receive()? {
+            Message::Size { width, height } => {
+                setup.width = width;
+                setup.height = height;
+            }
+            Message::Ship { length, name } => setup.ships.push((length, name)),
+            Message::Commit(hash) => break hash,
+            other => return Err(unexpected(other)),
+        }
+    };
+    let pending = Pending {
+        connection,
+        host: false,
+        their_commitment: Some(their_commitment),
+    };
+    Ok((setup, pending))
+}
+
+impl Pending {
+    /// Plays the whole game with `own` as this side's map, `shooter` picking where to shoot.
+    /// The host shoots first.
+    pub fn play<'a>(
+        mut self,
+        config: &Config<'a>,
+        own: Map<'a>,
+        shooter: &mut dyn Opponent,
+    ) -> Result<GameResult, String> {
+        let salt: u128 = rand::random();
+        self.connection
+            .send(&Message::Commit(commitment(salt, &own.layout())))?;
+        let their_commitment = match self.their_commitment.take() {
+            Some(hash) => hash,
+            None => match self.connection.receive()? {
+                Message::Commit(hash) => hash,
+                other => return Err(unexpected(other)),
+            },
+        };
+        let mut game = Game {
+            connection: self.connection,
+            config,
+            own,
+            salt,
+            their_commitment,
+            view: View {
+                width: config.width,
+                height: config.height,
+                spots: vec![Seen::Unknown; config.width * config.height],
+                remaining: config.fleet.iter().map(|ship| ship.length).collect(),
+            },
+            shots: Vec::new(),
+            sunk: Vec::new(),
+        };
+        game.run(shooter, self.host)
+    }
+}
+
+// what happens after a shot
+enum Next {
+    // the same player shoots again
+    Again,
+    // the other player's turn
+    Pass,
+    Over(GameResult),
+}
+
+struct Game<'a, 'c> {
+    connection: Connection,
+    config: &'c Config<'a>,
+    own: Map<'a>,
+    salt: u128,
+    their_commitment: String,
+    // the other player's map as far as this side knows
+    view: View,
+    // every shot fired at the other player and what they said it hit
+    shots: Vec<(usize, Outcome)>,
+    sunk: Vec<u8>,
+}
+
+impl<'a, 'c> Game<'a, 'c> {
+    fn run(&mut self, shooter: &mut dyn Opponent, first: bool) -> Result<GameResult, String> {
+        let mut my_turn = first;
+        loop {
+            let next = if my_turn {
+                self.shoot(shooter)?
+            } else {
+                self.defend()?
+            };
+            match next {
+                Next::Again => {}
+                Next::Pass => my_turn = !my_turn,
+                Next::Over(GameResult::Cheated(reason)) => return Ok(GameResult::Cheated(reason)),
+                Next::Over(result) => return self.finish(result),
+            }
+        }
+    }
+
+    fn ship(&self, id: u8) -> Option<&Ship<'a>> {
+        self.config.fleet.iter().find(|ship| ship.id == id)
+    }
+
+    fn describe(&self, outcome: &Outcome) -> String {
+        match outcome {
+            Outcome::Miss => String::from("Miss"),
+            Outcome::Hit => String::from("Hit"),
+            Outcome::Sunk { id, .. } => match self.ship(*id) {
+                Some(ship) => format!("Sunk the {}!", ship.name),
+                None => format!("Sunk the ship {}!", id),
+            },
+        }
+    }
+
+    fn shoot(&mut self, shooter: &mut dyn Opponent) -> Result<Next, String> {
+        if !self.view.spots.contains(&Seen::Unknown) {
+            return Ok(Next::Over(GameResult::Cheated(String::from(
+                "Every spot has been shot at and their fleet still isn't sunk",
+            ))));
+        }
+        let (col, row) = shooter.choose_shot(&self.view);
+        let idx = row * self.view.width + col;
+        if col >= self.view.width || self.view.spots.get(idx) != Some(&Seen::Unknown) {
+            return Err(format!("Can't shoot at {}{}", row_name(row), col));
+        }
+        self.connection.send(&Message::Shot { col, row })?;
+        let outcome = match self.connection.receive()? {
+            Message::Result(outcome) => outcome,
+            other => return Err(unexpected(other)),
+        };
+        println!(
+            "You fire at {}{}. {}",
+            row_name(row),
+            col,
+            self.describe(&outcome)
+        );
+        self.shots.push((idx, outcome.clone()));
+
+        match outcome {
+            Outcome::Miss => {
+                self.view.spots[idx] = Seen::Miss;
+                Ok(Next::Pass)
+            }
+            Outcome::Hit => {
+                self.view.spots[idx] = Seen::Hit;
+                Ok(Next::Again)
+            }
+            Outcome::Sunk { id, spots } => {
+                self.view.spots[idx] = Seen::Hit;
+                // the ship has to be one not sunk yet, as long as it should be, and
+                // only on spots that were hit
+                let length = match self.ship(id) {
+                    Some(ship) if !self.sunk.contains(&id) => ship.length,
+                    _ => {
+                        return Ok(Next::Over(GameResult::Cheated(format!(
+                            "They sank a ship {} that can't be sunk",
+                            id
+                        ))))
+                    }
+                };
+                let fits = spots.len() == length
+                    && spots.contains(&idx)
+                    && spots
+                        .iter()
+                        .all(|&spot| self.view.spots.get(spot) == Some(&Seen::Hit));
+                if !fits {
+                    return Ok(Next::Over(GameResult::Cheated(format!(
+                        "The spots they say ship {} was on don't add up",
+                        id
+                    ))));
+                }
+                for spot in spots {
+                    self.view.spots[spot] = Seen::Sunk;
+                }
+                self.sunk.push(id);
+                if let Some(i) = self.view.remaining.iter().position(|&l| l == length) {
+                    self.view.remaining.remove(i);
+                }
+                if self.sunk.len() == self.config.fleet.len() {
+                    Ok(Next::Over(GameResult::Won))
+                } else {
+                    Ok(Next::Again)
+                }
+            }
+        }
+    }
+
+    fn defend(&mut self) -> Result<Next, String> {
+        let (col, row) = match self.connection.receive()? {
+            Message::Shot { col, row } => (col, row),
+            other => return Err(unexpected(other)),
+        };
+        if col >= self.own.width || row >= self.own.height {
+            return Ok(Next::Over(GameResult::Cheated(String::from(
+                "They shot off the map",
+            ))));
+        }
+        let outcome = match self.own.outcome_of_strike(self.own.get_index(col, row)) {
+            Ok(outcome) => outcome,
+            Err(_) => {
+                return Ok(Next::Over(GameResult::Cheated(format!(
+                    "They shot at {}{} twice",
+                    row_name(row),
+                    col
+                ))))
+            }
+        };
+        self.connection.send(&Message::Result(outcome.clone()))?;
+        println!(
+            "They fire at {}{}. {}",
+            row_name(row),
+            col,
+            self.describe(&outcome)
+        );
+
+        if outcome == Outcome::Miss {
+            Ok(Next::Pass)
+        } else if self.own.all_destroyed() {
+            Ok(Next::Over(GameResult::Lost))
+        } else {
+            Ok(Next::Again)
+        }
+    }
+
+    // both sides show their maps, and each checks the other's against everything it was told
+    fn finish(&mut self, result: GameResult) -> Result<GameResult, String> {
+        let revealed = self
+            .connection
+            .send(&Message::Reveal {
+                salt: self.salt,
+                map: self.own.layout(),
+            })
+            .and_then(|()| self.connection.receive())
+            .and_then(|message| match message {
+                Message::Reveal { salt, map } => Ok((salt, map)),
+                other => Err(unexpected(other)),
+            });
+        let (salt, map) = match revealed {
+            Ok(reveal) => reveal,
+            // a loser who won't show their map could have moved their ships
+            Err(e) if result == GameResult::Won => {
+                return Ok(GameResult::Cheated(format!(
+                    "They lost but didn't show their map: {}",
+                    e
+                )))
+            }
+            Err(e) => return Err(e),
+        };
+        match self.check_reveal(salt, &map) {
+            Ok(()) => Ok(result),
+            Err(reason) => Ok(GameResult::Cheated(reason)),
+        }
+    }
+
+    fn check_reveal(&self, salt: u128, layout: &str) -> Result<(), String> {
+        if commitment(salt, layout) != self.their_commitment {
+            return Err(String::from(
+                "The map they showed isn't the one they started with",
+            ));
+        }
+        let mut map = Map::new(self.config.width, self.config.height);
+        map.parse_map_config(layout, &self.config.fleet, false)
+            .map_err(|e| format!("Their map breaks the rules: {}", e))?;
+        for (idx, told) in self.shots.iter() {
+            let outcome = map.outcome_of_strike(*idx)?;
+            if outcome != *told {
+                return Err(format!(
+                    "They said {}{} was '{}' but it was '{}'",
+                    row_name(idx / map.width),
+                    idx % map.width,
+                    self.describe(told),
+                    self.describe(&outcome)
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// a person at the terminal picking where to shoot
+struct Human;
+
+impl Opponent for Human {
+    fn choose_shot(&mut self, view: &View) -> (usize, usize) {
+        println!("YOUR TURN");
+        super::print_grid(view.width, view.height, |col, row| {
+            match view.spots[row * view.width + col] {
+                Seen::Unknown => ".",
+                Seen::Miss => "o",
+                Seen::Hit => "x",
+                Seen::Sunk => "X",
+            }
+            .to_string()
+        });
+        loop {
+            // games over the network are played with missiles only
+            let shot = get_user_shot("Choose a location to strike!", &Arsenal::NONE);
+            let (col, row) = (shot.col, shot.row);
+            if row >= view.height || col >= view.width {
+                println!("That isn't on the map");
+            } else if view.spots[row * view.width + col] != Seen::Unknown {
+                println!("You have already shot there");
+            } else {
+                return (col, row);
+            }
+        }
+    }
+}
+
+fn get_address(message: &str) -> String {
+    let mut s = String::new();
+    print!("{} (leave empty for {})\n> ", message, DEFAULT_ADDRESS);
+    let _ = stdout().flush();
+    stdin()
+        .read_line(&mut s)
+        .expect("Did not enter a correct string");
+    match s.trim() {
+        "" => DEFAULT_ADDRESS.to_string(),
+        address => address.to_string(),
+    }
+}
+
+/// Hosts a game, waiting for the other player to join, or joins one
+pub fn play_over_network(hosting: bool) {
+    let played = if hosting { host_game() } else { join_game() };
+    match played {
+        Ok(GameResult::Won) => println!("Congratulations! You won!"),
+        Ok(GameResult::Lost) => println!("You lost, better luck next time."),
+        Ok(GameResult::Cheated(reason)) => {
+            println!("The other player cheated, so you win! {}", reason)
+        }
+        Err(e) => println!("The game stopped: {}", e),
+    }
+}
+
+fn host_game() -> Result<GameResult, String> {
+    let contents = super::open_file(CONFIG_FILE);
+    let config = read_config(&contents);
+    let own = setup_map("player_1_battleship.txt", &config)?;
+    let address = get_address("Which address should the game be hosted on?");
+    let listener =
+        TcpListener::bind(&address).map_err(|e| format!("Can't host on {}: {}", address, e))?;
+    println!("Waiting for someone to join on {}", address);
+    let (stream, joined) = listener
+        .accept()
+        .map_err(|e| format!("No one could join: {}", e))?;
+    println!("{} joined the game", joined);
+    own.print_map(true);
+    host(stream, &config)?.play(&config, own, &mut Human)
+}
+
+fn join_game() -> Result<GameResult, String> {
+    let address = get_address("Which address is the game hosted on?");
+    let stream =
+        TcpStream::connect(&address).map_err(|e| format!("Can't join {}: {}", address, e))?;
+    let (setup, pending) = join(stream)?;
+    let config = setup.config()?;
+    let own = setup_map("player_2_battleship.txt", &config)?;
+    own.print_map(true);
+    pending.play(&config, own, &mut Human)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::battleship::opponent::Difficulty;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::thread;
+    use std::time::Duration;
+
+    fn connected() -> (TcpStream, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let joining = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (hosting, _) = listener.accept().unwrap();
+        for stream in [&hosting, &joining] {
+            stream
+                .set_read_timeout(Some(Duration::from_secs(10)))
+                .unwrap();
+        }
+        (hosting, joining)
+    }
+
+    fn random_map<'a>(config: &Config<'a>, seed: u64) -> Map<'a> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        Map::random_fleet(config.width, config.height, &config.fleet, false, &mut rng).unwrap()
+    }
+
+    #[test]
+    fn test_messages_round_trip() {
+        let messages = [
+            Message::Size {
+                width: 12,
+                height: 30,
+            },
+            Message::Ship {
+                length: 4,
+                name: String::from("Battle Ship"),
+            },
+            Message::Commit(commitment(7, ". 1 1")),
+            Message::Shot { col: 11, row: 27 },
+            Message::Result(Outcome::Miss),
+            Message::Result(Outcome::Hit),
+            Message::Result(Outcome::Sunk {
+                id: 3,
+                spots: vec![4, 5, 6],
+            }),
+            Message::Reveal {
+                salt: u128::MAX,
+                map: String::from(". 1 1 ."),
+            },
+        ];
+        for message in messages {
+            assert_eq!(message.to_string().parse(), Ok(message));
+        }
+        assert!("SHOT 1".parse::<Message>().is_err());
+        assert!("RESULT SUNK".parse::<Message>().is_err());
+        assert!("HELLO".parse::<Message>().is_err());
+    }
+
+    #[test]
+    fn test_game_over_localhost() {
+        let (hosting, joining) = connected();
+        let host_side = thread::spawn(move || {
+            let config = Config::classic();
+            let own = random_map(&config, 1);
+            let mut computer = Difficulty::Hard.opponent(1);
+            host(hosting, &config)
+                .unwrap()
+                .play(&config, own, computer.as_mut())
+        });
+
+        let (setup, pending) = join(joining).unwrap();
+        let config = setup.config().unwrap();
+        assert_eq!(config, Config::classic());
+        let own = random_map(&config, 2);
+        let mut computer = Difficulty::Medium.opponent(2);
+        let joined = pending.play(&config, own, computer.as_mut()).unwrap();
+        let hosted = host_side.join().unwrap().unwrap();
+
+        let mut results = [hosted, joined];
+        results.sort_by_key(|result| result == &GameResult::Lost);
+        assert_eq!(results, [GameResult::Won, GameResult::Lost]);
+    }
+
+    #[test]
+    fn test_joining_refuses_a_game_that_cant_be_played() {
+        let setups = [
+            "SIZE 10 10\nSHIP 0 Raft\nCOMMIT 00\n",
+            "SIZE 18446744073709551615 18446744073709551615\nSHIP 2 Raft\nCOMMIT 00\n",
+            "SIZE 1001 10\nSHIP 2 Raft\nCOMMIT 00\n",
+        ];
+        for lines in setups {
+            let (mut hosting, joining) = connected();
+            hosting.write_all(lines.as_bytes()).unwrap();
+            let (setup, _pending) = join(joining).unwrap();
+            assert!(setup.config().is_err(), "{}", lines);
+        }
+    }
+
+    #[test]
+    fn test_host_catches_a_swapped_map() {
+        let (hosting, joining) = connected();
+        let host_side = thread::spawn(move || {
+            let config = Config::classic();
+            let own = random_map(&config, 3);
+            let mut computer = Difficulty::Hard.opponent(3);
+            host(hosting, &config)
+                .unwrap()
+                .play(&config, own, computer.as_mut())
+        });
+
+        // the joining side commits to one map, then plays and reveals another
+        let (setup, mut pending) = join(joining).unwrap();
+        let config = setup.config().unwrap();
+        let committed = random_map(&config, 4);
+        let played = random_map(&config, 5);
+        pending
+            .connection
+            .send(&Message::Commit(commitment(9, &committed.layout())))
+            .unwrap();
+        let mut game = Game {
+            connection: pending.connection,
+            config: &config,
+            own: played,
+            salt: 9,
+            their_commitment: pending.their_commitment.unwrap(),
+            view: Map::new(10, 10).view(),
+            shots: Vec::new(),
+            sunk: Vec::new(),
+        };
+        game.view.remaining = vec![2, 3, 3, 4, 5];
+        let mut computer = Difficulty::Easy.opponent(4);
+        let _ = game.run(computer.as_mut(), false);
+
+        assert_eq!(
+            host_side.join().unwrap(),
+            Ok(GameResult::Cheated(String::from(
+                "The map they showed isn't the one they started with"
+            )))
+        );
+    }
+
+    #[test]
+    fn test_leaving_instead_of_revealing_is_cheating() {
+        let (hosting, joining) = connected();
+        let host_side = thread::spawn(move || {
+            let config = Config::classic();
+            let own = random_map(&config, 8);
+            let mut computer = Difficulty::Hard.opponent(8);
+            host(hosting, &config)
+                .unwrap()
+                .play(&config, own, computer.as_mut())
+        });
+
+        // the joining side answers honestly but only ever shoots at water, and when it
+        // has lost it hangs up rather than show its map
+        let (_, pending) = join(joining).unwrap();
+        let mut map = random_map(&Config::classic(), 9);
+        let hosts_map = random_map(&Config::classic(), 8);
+        let mut water = (0..100).filter(|&idx| hosts_map.ship_at(idx).is_none());
+        let mut connection = pending.connection;
+        connection
+            .send(&Message::Commit(commitment(1, &map.layout())))
+            .unwrap();
+        loop {
+            match connection.receive().unwrap() {
+                Message::Shot { col, row } => {
+                    let outcome = map.outcome_of_strike(row * 10 + col).unwrap();
+                    let missed = outcome == Outcome::Miss;
+                    connection.send(&Message::Result(outcome)).unwrap();
+                    if missed {
+                        let idx = water.next().unwrap();
+                        let shot = Message::Shot {
+                            col: idx % 10,
+                            row: idx / 10,
+                        };
+                        connection.send(&shot).unwrap();
+                        assert_eq!(connection.receive(), Ok(Message::Result(Outcome::Miss)));
+                    }
+                }
+                Message::Reveal { .. } => break,
+                other => panic!("{}", other),
+            }
+        }
+        drop(connection);
+
+        match host_side.join().unwrap() {
+            Ok(GameResult::Cheated(reason)) if reason.starts_with("They lost but didn't") => {}
+            other => panic!("the host didn't notice: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_host_catches_a_lie_about_a_hit() {
+        let (hosting, joining) = connected();
+        let host_side = thread::spawn(move || {
+            let config = Config::classic();
+            let own = random_map(&config, 6);
+            let mut computer = Difficulty::Easy.opponent(6);
+            host(hosting, &config)
+                .unwrap()
+                .play(&config, own, computer.as_mut())
+        });
+
+        // the joining side says every shot at it missed, and shoots at every spot in turn
+        let (_, pending) = join(joining).unwrap();
+        let map = random_map(&Config::classic(), 7);
+        let mut connection = pending.connection;
+        connection
+            .send(&Message::Commit(commitment(1, &map.layout())))
+            .unwrap();
+        let mut spots = 0..100;
+        let mut sunk = 0;
+        while let Ok(message) = connection.receive() {
+            match message {
+                Message::Shot { .. } => connection.send(&Message::Result(Outcome::Miss)).unwrap(),
+                Message::Reveal { .. } => {
+                    let reveal = Message::Reveal {
+                        salt: 1,
+                        map: map.layout(),
+                    };
+                    connection.send(&reveal).unwrap();
+                    break;
+                }
+                other => panic!("{}", other),
+            }
+            while sunk < 5 {
+                let idx = spots.next().unwrap();
+                let shot = Message::Shot {
+                    col: idx % 10,
+                    row: idx / 10,
+                };
+                connection.send(&shot).unwrap();
+                match connection.receive() {
+                    Ok(Message::Result(Outcome::Miss)) => break,
+                    Ok(Message::Result(Outcome::Sunk { .. })) => sunk += 1,
+                    _ => {}
+                }
+            }
+        }
+
+        match host_side.join().unwrap() {
+            Ok(GameResult::Cheated(_)) => {}
+            other => panic!("the host didn't notice: {:?}", other),
+        }
+    }
+}
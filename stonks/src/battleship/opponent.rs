@@ -0,0 +1,372 @@
+// the computer player. it only ever gets to see what a person shooting at the map
+// would: where it missed, where it hit, which ships are sunk and how long the ships
+// still afloat are, never where they are
+
+use super::{BattleshipLocation, Map};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Seen {
+    Unknown,
+    Miss,
+    // hit a ship that is still afloat
+    Hit,
+    Sunk,
+}
+
+/// The map as the shooter sees it
+#[derive(Clone, Debug, PartialEq)]
+pub struct View {
+    pub width: usize,
+    pub height: usize,
+    pub spots: Vec<Seen>,
+    /// the lengths of the ships not sunk yet
+    pub remaining: Vec<usize>,
+}
+
+impl View {
+    fn unknown(&self) -> Vec<usize> {
+        (0..self.spots.len())
+            .filter(|&idx| self.spots[idx] == Seen::Unknown)
+            .collect()
+    }
+
+    // the spot `d_col`, `d_row` away from `idx`, if that's still on the map
+    fn step(&self, idx: usize, d_col: isize, d_row: isize) -> Option<usize> {
+        let col = (idx % self.width) as isize + d_col;
+        let row = (idx / self.width) as isize + d_row;
+        if col < 0 || row < 0 || col as usize >= self.width || row as usize >= self.height {
+            return None;
+        }
+        Some(row as usize * self.width + col as usize)
+    }
+
+    // the spots a ship of `length` would cover from `idx` going right or down
+    fn placement(&self, idx: usize, length: usize, across: bool) -> Option<Vec<usize>> {
+        let (d_col, d_row) = if across { (1, 0) } else { (0, 1) };
+        (0..length as isize)
+            .map(|i| self.step(idx, d_col * i, d_row * i))
+            .collect()
+    }
+}
+
+impl<'a> Map<'a> {
+    pub fn view(&self) -> View {
+        let spots = self
+            .map
+            .iter()
+            .map(|location| match location {
+                BattleshipLocation::Empty | BattleshipLocation::OkShip(_) => Seen::Unknown,
+                BattleshipLocation::Miss => Seen::Miss,
+                BattleshipLocation::HitShip(_) => Seen::Hit,
+                BattleshipLocation::DestroyedShip(_) => Seen::Sunk,
+            })
+            .collect();
+
+        let mut afloat = Vec::new();
+        for location in self.map.iter() {
+            if let BattleshipLocation::OkShip(ship) | BattleshipLocation::HitShip(ship) = location {
+                if !afloat.contains(ship) {
+                    afloat.push(*ship);
+                }
+            }
+        }
+        View {
+            width: self.width,
+            height: self.height,
+            spots,
+            remaining: afloat.iter().map(|ship| ship.length).collect(),
+        }
+    }
+}
+
+pub trait Opponent {
+    /// the column and row to shoot at next, always a spot not shot at yet
+    fn choose_shot(&mut self, view: &View) -> (usize, usize);
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Difficulty {
+    // shoots anywhere
+    Easy,
+    // hunts at random until it hits, then goes after the rest of that ship
+    Medium,
+    // shoots where the ships left are most likely to be
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard];
+
+    pub fn opponent(self, seed: u64) -> Box<dyn Opponent> {
+        let rng = StdRng::seed_from_u64(seed);
+        match self {
+            Difficulty::Easy => Box::new(RandomOpponent { rng }),
+            Difficulty::Medium => Box::new(HuntTargetOpponent { rng }),
+            Difficulty::Hard => Box::new(DensityOpponent { rng }),
+        }
+    }
+}
+
+fn col_row(view: &View, idx: usize) -> (usize, usize) {
+    (idx % view.width, idx / view.width)
+}
+
+fn random_spot<R: Rng>(view: &View, rng: &mut R) -> (usize, usize) {
+    let idx = *view
+        .unknown()
+        .choose(rng)
+        .expect("there is always somewhere left to shoot while a ship is afloat");
+    col_row(view, idx)
+}
+
+pub struct RandomOpponent {
+    rng: StdRng,
+}
+
+impl Opponent for RandomOpponent {
+    fn choose_shot(&mut self, view: &View) -> (usize, usize) {
+        random_spot(view, &mut self.rng)
+    }
+}
+
+pub struct HuntTargetOpponent {
+    rng: StdRng,
+}
+
+impl HuntTargetOpponent {
+    // where to shoot to finish off the ships already hit: past the ends of a line of
+    // hits, or around a lone hit
+    fn targets(view: &View) -> Vec<usize> {
+        let hits: Vec<usize> = (0..view.spots.len())
+            .filter(|&idx| view.spots[idx] == Seen::Hit)
+            .collect();
+        let is_hit = |spot: Option<usize>| spot.map(|idx| view.spots[idx]) == Some(Seen::Hit);
+
+        let mut line_ends = Vec::new();
+        let mut around = Vec::new();
+        for &hit in hits.iter() {
+            for (d_col, d_row) in [(1, 0), (0, 1)] {
+                let in_line =
+                    is_hit(view.step(hit, d_col, d_row)) || is_hit(view.step(hit, -d_col, -d_row));
+                for sign in [1, -1] {
+                    let mut spot = view.step(hit, sign * d_col, sign * d_row);
+                    while in_line && is_hit(spot) {
+                        spot = spot.and_then(|idx| view.step(idx, sign * d_col, sign * d_row));
+                    }
+                    if let Some(idx) = spot {
+                        let targets = if in_line { &mut line_ends } else { &mut around };
+                        if view.spots[idx] == Seen::Unknown && !targets.contains(&idx) {
+                            targets.push(idx);
+                        }
+                    }
+                }
+            }
+        }
+        if line_ends.is_empty() {
+            around
+        } else {
+            line_ends
+        }
+    }
+}
+
+impl Opponent for HuntTargetOpponent {
+    fn choose_shot(&mut self, view: &View) -> (usize, usize) {
+        if let Some(&idx) = Self::targets(view).choose(&mut self.rng) {
+            return col_row(view, idx);
+        }
+        // while every ship left is at least 2 long, hunting on every other spot finds them all
+        let checkerboard: Vec<usize> = view
+            .unknown()
+            .into_iter()
+            .filter(|idx| (idx % view.width + idx / view.width).is_multiple_of(2))
+            .filter(|_| view.remaining.iter().all(|&length| length >= 2))
+            .collect();
+        match checkerboard.choose(&mut self.rng) {
+            Some(&idx) => col_row(view, idx),
+            None => random_spot(view, &mut self.rng),
+        }
+    }
+}
+
+pub struct DensityOpponent {
+    rng: StdRng,
+}
+
+// how much more a placement through a hit counts than one through open water
+const HIT_WEIGHT: u32 = 50;
+
+impl DensityOpponent {
+    /// For every spot not shot at yet, how many ways the ships left could be placed over
+    /// it. Placements through hits count for more, so it goes after ships it has found.
+    pub fn scores(view: &View) -> Vec<u32> {
+        let mut scores = vec![0; view.spots.len()];
+        for &length in view.remaining.iter() {
+            for idx in 0..view.spots.len() {
+                for across in [true, false] {
+                    let spots = match view.placement(idx, length, across) {
+                        Some(spots) => spots,
+                        None => continue,
+                    };
+                    if spots
+                        .iter()
+                        .any(|&s| matches!(view.spots[s], Seen::Miss | Seen::Sunk))
+                    {
+                        continue;
+                    }
+                    let hits = spots
+                        .iter()
+                        .filter(|&&s| view.spots[s] == Seen::Hit)
+                        .count();
+                    let weight = 1 + HIT_WEIGHT * hits as u32;
+                    for &s in spots.iter() {
+                        if view.spots[s] == Seen::Unknown {
+                            scores[s] += weight;
+                        }
+                    }
+                }
+            }
+        }
+        scores
+    }
+}
+
+impl Opponent for DensityOpponent {
+    fn choose_shot(&mut self, view: &View) -> (usize, usize) {
+        let scores = Self::scores(view);
+        let best = scores.iter().copied().max().unwrap_or(0);
+        if best == 0 {
+            return random_spot(view, &mut self.rng);
+        }
+        let tied: Vec<usize> = (0..scores.len())
+            .filter(|&idx| scores[idx] == best)
+            .collect();
+        let idx = *tied.choose(&mut self.rng).unwrap();
+        col_row(view, idx)
+    }
+}
+
+/// how many shots `opponent` takes to sink everything on `map`
+pub fn shots_to_win(opponent: &mut dyn Opponent, map: &mut Map) -> usize {
+    let mut shots = 0;
+    while !map.all_destroyed() {
+        let (col, row) = opponent.choose_shot(&map.view());
+        map.strike(col, row)
+            .expect("the computer only shoots at spots it hasn't tried yet");
+        shots += 1;
+    }
+    shots
+}
+
+/// The average number of shots each difficulty takes to win, over the same `games`
+/// random fleets. The `benchmark` test runs it:
+///
+/// ```text
+/// cargo test --release benchmark -- --ignored --nocapture
+/// ```
+///
+/// `--release` matters, the hard one has a lot of counting to do.
+pub fn benchmark_opponents(games: usize) {
+    let mut rng = StdRng::seed_from_u64(2021);
+    let fleets: Vec<Map> = (0..games)
+        .map(|_| Map::random_fleet(10, 10, &super::CLASSIC_FLEET, false, &mut rng).unwrap())
+        .collect();
+    println!("Average shots to win over {} random fleets", games);
+    for difficulty in Difficulty::ALL {
+        let total: usize = fleets
+            .iter()
+            .enumerate()
+            .map(|(i, fleet)| {
+                let mut opponent = difficulty.opponent(i as u64);
+                shots_to_win(opponent.as_mut(), &mut fleet.clone())
+            })
+            .sum();
+        println!(
+            "{:?}: {:.1}",
+            difficulty,
+            total as f64 / games.max(1) as f64
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::battleship::CLASSIC_FLEET;
+
+    fn average(difficulty: Difficulty, games: usize) -> f64 {
+        let mut rng = StdRng::seed_from_u64(1);
+        let total: usize = (0..games)
+            .map(|i| {
+                let mut map = Map::random_fleet(10, 10, &CLASSIC_FLEET, false, &mut rng).unwrap();
+                shots_to_win(difficulty.opponent(i as u64).as_mut(), &mut map)
+            })
+            .sum();
+        total as f64 / games as f64
+    }
+
+    // prints the averages rather than checking anything, see `benchmark_opponents`
+    #[test]
+    #[ignore]
+    fn test_benchmark_opponents() {
+        benchmark_opponents(1000);
+    }
+
+    #[test]
+    fn test_view_hides_the_ships() {
+        let mut map = Map::new(10, 10);
+        map.parse_map_config(
+            include_str!("../../data/player_1_battleship.txt"),
+            &CLASSIC_FLEET,
+            false,
+        )
+        .unwrap();
+        map.strike(0, 0).unwrap();
+        map.strike(1, 0).unwrap();
+        map.strike(2, 1).unwrap();
+        map.strike(9, 9).unwrap();
+
+        let view = map.view();
+        assert_eq!(view.spots[0], Seen::Sunk);
+        assert_eq!(view.spots[12], Seen::Hit);
+        assert_eq!(view.spots[99], Seen::Miss);
+        assert_eq!(view.spots[22], Seen::Unknown);
+        // the destroyer is sunk, the rest in the order they first show up on the map
+        assert_eq!(view.remaining, vec![3, 5, 3, 4]);
+    }
+
+    #[test]
+    fn test_hunt_target_follows_a_line_of_hits() {
+        let mut view = Map::new(10, 10).view();
+        view.spots[44] = Seen::Hit;
+        assert_eq!(HuntTargetOpponent::targets(&view), vec![45, 43, 54, 34]);
+        view.spots[45] = Seen::Hit;
+        view.spots[43] = Seen::Miss;
+        assert_eq!(HuntTargetOpponent::targets(&view), vec![46]);
+    }
+
+    #[test]
+    fn test_density_goes_after_hits() {
+        let mut view = Map::new(10, 10).view();
+        view.remaining = CLASSIC_FLEET.iter().map(|ship| ship.length).collect();
+        let scores = DensityOpponent::scores(&view);
+        // the middle fits more ships than the corners
+        assert!(scores[44] > scores[0]);
+        view.spots[0] = Seen::Hit;
+        let mut opponent = Difficulty::Hard.opponent(0);
+        let shot = opponent.choose_shot(&view);
+        assert!(shot == (1, 0) || shot == (0, 1));
+    }
+
+    #[test]
+    fn test_harder_opponents_win_faster() {
+        let easy = average(Difficulty::Easy, 30);
+        let medium = average(Difficulty::Medium, 30);
+        let hard = average(Difficulty::Hard, 30);
+        assert!(easy > medium, "easy {} medium {}", easy, medium);
+        assert!(medium > hard, "medium {} hard {}", medium, hard);
+    }
+}
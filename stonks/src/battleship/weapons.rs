@@ -0,0 +1,477 @@
+// what a player can fire. missiles hit one spot and never run out, the special
+// weapons reach further but every player only gets a few of each
+
+use super::fleet::Direction;
+use super::{row_index, row_name, BattleshipLocation, Map};
+use std::fmt;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Weapon {
+    Missile,
+    // hits the 3 x 3 square around the spot
+    Bomb,
+    // runs from the spot to the edge of the map, stopping at the first ship it hits
+    Torpedo(Direction),
+    // finds the ships in the 3 x 3 square around the spot without harming them
+    Sonar,
+}
+
+/// What to fire and where
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Shot {
+    pub weapon: Weapon,
+    pub col: usize,
+    pub row: usize,
+}
+
+impl Shot {
+    pub fn missile(col: usize, row: usize) -> Shot {
+        Shot {
+            weapon: Weapon::Missile,
+            col,
+            row,
+        }
+    }
+
+    /// Reads a shot the way players type it: the row's letters then the column's number
+    /// for a missile, like `D5`, and the weapon's name first for anything else, like
+    /// `bomb D5`, `sonar D5` or `torpedo D5 down`. Torpedoes go across unless told down.
+    pub fn parse(input: &str) -> Result<Shot, String> {
+        let mut words = input.split_whitespace();
+        let first = words
+            .next()
+            .ok_or_else(|| String::from("Nothing was entered"))?;
+        let (mut weapon, spot) = match first.to_lowercase().as_str() {
+            "bomb" => (Weapon::Bomb, words.next()),
+            "torpedo" => (Weapon::Torpedo(Direction::Across), words.next()),
+            "sonar" => (Weapon::Sonar, words.next()),
+            _ => (Weapon::Missile, Some(first)),
+        };
+        let spot = spot.ok_or_else(|| format!("Where should the {} go?", first))?;
+        if let Weapon::Torpedo(direction) = &mut weapon {
+            match words.next().map(str::to_lowercase).as_deref() {
+                None | Some("across") => {}
+                Some("down") => *direction = Direction::Down,
+                Some(other) => {
+                    return Err(format!(
+                        "A torpedo goes 'across' or 'down', not '{}'",
+                        other
+                    ))
+                }
+            }
+        }
+        if let Some(extra) = words.next() {
+            return Err(format!("Didn't expect '{}' after {}", extra, spot));
+        }
+
+        let letters = spot.chars().take_while(|c| c.is_ascii_alphabetic()).count();
+        let (row, col) = spot.split_at(letters);
+        if row.is_empty() {
+            return Err(format!(
+                "A spot starts with its row letters, like B3, not {}",
+                spot
+            ));
+        }
+        if col.is_empty() {
+            return Err(format!(
+                "A spot ends with its column number, like B3, not {}",
+                spot
+            ));
+        }
+        let row = row_index(row).ok_or_else(|| format!("Unknown row '{}' in {}", row, spot))?;
+        let col = col
+            .parse()
+            .map_err(|_| format!("Unknown column '{}' in {}", col, spot))?;
+        Ok(Shot { weapon, col, row })
+    }
+}
+
+/// What a shot did to one spot
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Impact {
+    Miss,
+    Hit,
+    // hit the last part afloat of the ship with this id
+    Sunk(u8),
+}
+
+impl fmt::Display for Impact {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Impact::Miss => write!(f, "Miss"),
+            Impact::Hit => write!(f, "Hit"),
+            Impact::Sunk(id) => write!(f, "Sunk the ship {}!", id),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Cell {
+    pub col: usize,
+    pub row: usize,
+    pub impact: Impact,
+}
+
+/// Every spot a shot reached
+#[derive(Clone, Debug, PartialEq)]
+pub enum StrikeResult {
+    /// what a missile, bomb or torpedo did to each spot it reached, in order
+    Fired(Vec<Cell>),
+    /// every spot the sonar swept, and which of those have an unharmed part of a ship
+    Sonar {
+        swept: Vec<(usize, usize)>,
+        ships: Vec<(usize, usize)>,
+    },
+}
+
+impl StrikeResult {
+    /// whether any ship was hit, which in the classic game means going again
+    pub fn hit(&self) -> bool {
+        match self {
+            StrikeResult::Fired(cells) => cells.iter().any(|cell| cell.impact != Impact::Miss),
+            StrikeResult::Sonar { .. } => false,
+        }
+    }
+}
+
+fn spot_name(col: usize, row: usize) -> String {
+    format!("{}{}", row_name(row), col)
+}
+
+impl fmt::Display for StrikeResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StrikeResult::Fired(cells) if cells.len() == 1 => write!(f, "{}", cells[0].impact),
+            StrikeResult::Fired(cells) => {
+                let cells: Vec<String> = cells
+                    .iter()
+                    .map(|cell| format!("{} {}", spot_name(cell.col, cell.row), cell.impact))
+                    .collect();
+                write!(f, "{}", cells.join(", "))
+            }
+            StrikeResult::Sonar { ships, .. } if ships.is_empty() => {
+                write!(f, "Sonar found nothing")
+            }
+            StrikeResult::Sonar { ships, .. } => {
+                let ships: Vec<String> = ships
+                    .iter()
+                    .map(|&(col, row)| spot_name(col, row))
+                    .collect();
+                write!(f, "Sonar found ships at {}", ships.join(", "))
+            }
+        }
+    }
+}
+
+/// How many of each special weapon a player has left, there are always missiles
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Arsenal {
+    pub bombs: usize,
+    pub torpedoes: usize,
+    pub sonars: usize,
+}
+
+impl Arsenal {
+    pub const NONE: Arsenal = Arsenal {
+        bombs: 0,
+        torpedoes: 0,
+        sonars: 0,
+    };
+
+    pub const SPECIAL: Arsenal = Arsenal {
+        bombs: 1,
+        torpedoes: 2,
+        sonars: 2,
+    };
+
+    /// how many times `weapon` can still be fired, `None` for missiles
+    pub fn left(&self, weapon: Weapon) -> Option<usize> {
+        match weapon {
+            Weapon::Missile => None,
+            Weapon::Bomb => Some(self.bombs),
+            Weapon::Torpedo(_) => Some(self.torpedoes),
+            Weapon::Sonar => Some(self.sonars),
+        }
+    }
+
+    /// uses up one `weapon`, once it has been fired
+    pub fn spend(&mut self, weapon: Weapon) {
+        let left = match weapon {
+            Weapon::Missile => return,
+            Weapon::Bomb => &mut self.bombs,
+            Weapon::Torpedo(_) => &mut self.torpedoes,
+            Weapon::Sonar => &mut self.sonars,
+        };
+        *left = left.saturating_sub(1);
+    }
+}
+
+impl fmt::Display for Arsenal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "bombs {}, torpedoes {}, sonar {}",
+            self.bombs, self.torpedoes, self.sonars
+        )
+    }
+}
+
+impl<'a> Map<'a> {
+    /// Fires `shot` at the map. Fails without changing anything when it's off the map,
+    /// or when everything it would hit has been shot at already.
+    pub fn missle_strike(&mut self, shot: &Shot) -> Result<StrikeResult, String> {
+        if shot.row >= self.height || shot.col >= self.width {
+            return Err("row_idx or col is larger than map width".to_string());
+        }
+
+        let idx = self.get_index(shot.col, shot.row);
+        let spots = match shot.weapon {
+            Weapon::Missile => {
+                let impact = self.strike(shot.col, shot.row)?;
+                return Ok(StrikeResult::Fired(vec![Cell {
+                    col: shot.col,
+                    row: shot.row,
+                    impact,
+                }]));
+            }
+            Weapon::Bomb => self
+                .square(idx)
+                .into_iter()
+                .filter(|&idx| !self.shot_at(idx))
+                .collect(),
+            Weapon::Torpedo(direction) => self.torpedo_path(idx, direction),
+            Weapon::Sonar => {
+                let swept = self.square(idx);
+                let ships: Vec<usize> = swept
+                    .iter()
+                    .copied()
+                    .filter(|&idx| matches!(self.map[idx], BattleshipLocation::OkShip(_)))
+                    .collect();
+                for &idx in ships.iter() {
+                    self.spotted[idx] = true;
+                }
+                return Ok(StrikeResult::Sonar {
+                    swept: swept.iter().map(|&idx| self.col_row(idx)).collect(),
+                    ships: ships.iter().map(|&idx| self.col_row(idx)).collect(),
+                });
+            }
+        };
+        if spots.is_empty() {
+            return Err(String::from(
+                "Everywhere that would hit has been shot at already",
+            ));
+        }
+
+        let mut cells = Vec::new();
+        for idx in spots {
+            let (col, row) = self.col_row(idx);
+            let impact = self.strike(col, row)?;
+            cells.push(Cell { col, row, impact });
+        }
+        Ok(StrikeResult::Fired(cells))
+    }
+
+    fn col_row(&self, idx: usize) -> (usize, usize) {
+        (idx % self.width, idx / self.width)
+    }
+
+    fn shot_at(&self, idx: usize) -> bool {
+        matches!(
+            self.map[idx],
+            BattleshipLocation::Miss
+                | BattleshipLocation::HitShip(_)
+                | BattleshipLocation::DestroyedShip(_)
+        )
+    }
+
+    // `idx` and the up to 8 spots around it, in order
+    fn square(&self, idx: usize) -> Vec<usize> {
+        let mut spots: Vec<usize> = self.neighbors(idx).chain([idx]).collect();
+        spots.sort_unstable();
+        spots
+    }
+
+    // the spots a torpedo hits: all the water not shot at yet from `idx` to the
+    // edge of the map, up to and including the first part of a ship not hit yet
+    fn torpedo_path(&self, idx: usize, direction: Direction) -> Vec<usize> {
+        let (mut col, mut row) = self.col_row(idx);
+        let mut path = Vec::new();
+        while col < self.width && row < self.height {
+            let idx = self.get_index(col, row);
+            if !self.shot_at(idx) {
+                path.push(idx);
+                if let BattleshipLocation::OkShip(_) = self.map[idx] {
+                    break;
+                }
+            }
+            match direction {
+                Direction::Across => col += 1,
+                Direction::Down => row += 1,
+            }
+        }
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::battleship::CLASSIC_FLEET;
+
+    fn player_1() -> Map<'static> {
+        let mut map = Map::new(10, 10);
+        map.parse_map_config(
+            include_str!("../../data/player_1_battleship.txt"),
+            &CLASSIC_FLEET,
+            false,
+        )
+        .unwrap();
+        map
+    }
+
+    fn cells(result: &StrikeResult) -> Vec<(usize, usize, Impact)> {
+        match result {
+            StrikeResult::Fired(cells) => cells
+                .iter()
+                .map(|cell| (cell.col, cell.row, cell.impact))
+                .collect(),
+            StrikeResult::Sonar { .. } => panic!("expected a shot, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_parse_shots() {
+        assert_eq!(Shot::parse("D5"), Ok(Shot::missile(5, 3)));
+        assert_eq!(Shot::parse(" ab12 "), Ok(Shot::missile(12, 27)));
+        assert_eq!(
+            Shot::parse("Bomb c3"),
+            Ok(Shot {
+                weapon: Weapon::Bomb,
+                col: 3,
+                row: 2,
+            })
+        );
+        assert_eq!(
+            Shot::parse("torpedo A0 down").map(|shot| shot.weapon),
+            Ok(Weapon::Torpedo(Direction::Down))
+        );
+        assert_eq!(
+            Shot::parse("torpedo A0").map(|shot| shot.weapon),
+            Ok(Weapon::Torpedo(Direction::Across))
+        );
+        assert_eq!(
+            Shot::parse("sonar J9").map(|shot| shot.weapon),
+            Ok(Weapon::Sonar)
+        );
+
+        assert_eq!(
+            Shot::parse("7Y"),
+            Err("A spot starts with its row letters, like B3, not 7Y".to_string())
+        );
+        assert_eq!(
+            Shot::parse("D"),
+            Err("A spot ends with its column number, like B3, not D".to_string())
+        );
+        assert_eq!(
+            Shot::parse("D5x"),
+            Err("Unknown column '5x' in D5x".to_string())
+        );
+        assert!(Shot::parse("bomb").is_err());
+        assert!(Shot::parse("torpedo A0 up").is_err());
+        assert!(Shot::parse("D5 D6").is_err());
+        assert!(Shot::parse("").is_err());
+    }
+
+    #[test]
+    fn test_bomb() {
+        let mut map = player_1();
+        map.missle_strike(&Shot::missile(0, 0)).unwrap();
+        let bomb = Shot {
+            weapon: Weapon::Bomb,
+            col: 1,
+            row: 1,
+        };
+        // the destroyer is at A0 and A1, the submarine goes down from B2
+        let result = map.missle_strike(&bomb).unwrap();
+        assert_eq!(
+            cells(&result),
+            vec![
+                (1, 0, Impact::Sunk(1)),
+                (2, 0, Impact::Miss),
+                (0, 1, Impact::Miss),
+                (1, 1, Impact::Miss),
+                (2, 1, Impact::Hit),
+                (0, 2, Impact::Miss),
+                (1, 2, Impact::Miss),
+                (2, 2, Impact::Hit),
+            ]
+        );
+        assert!(result.hit());
+        assert!(map.missle_strike(&bomb).is_err());
+    }
+
+    #[test]
+    fn test_torpedo_stops_at_the_first_ship() {
+        let mut map = player_1();
+        let torpedo = Shot {
+            weapon: Weapon::Torpedo(Direction::Down),
+            col: 2,
+            row: 0,
+        };
+        assert_eq!(
+            cells(&map.missle_strike(&torpedo).unwrap()),
+            vec![(2, 0, Impact::Miss), (2, 1, Impact::Hit)]
+        );
+        // the next one runs past what's already been shot
+        assert_eq!(
+            cells(&map.missle_strike(&torpedo).unwrap()),
+            vec![(2, 2, Impact::Hit)]
+        );
+
+        let off_the_end = Shot {
+            weapon: Weapon::Torpedo(Direction::Across),
+            col: 9,
+            row: 9,
+        };
+        assert_eq!(
+            cells(&map.missle_strike(&off_the_end).unwrap()),
+            vec![(9, 9, Impact::Miss)]
+        );
+        assert!(map.missle_strike(&off_the_end).is_err());
+    }
+
+    #[test]
+    fn test_sonar_finds_ships_without_harm() {
+        let mut map = player_1();
+        let sonar = Shot {
+            weapon: Weapon::Sonar,
+            col: 0,
+            row: 0,
+        };
+        let result = map.missle_strike(&sonar).unwrap();
+        assert_eq!(
+            result,
+            StrikeResult::Sonar {
+                swept: vec![(0, 0), (1, 0), (0, 1), (1, 1)],
+                ships: vec![(0, 0), (1, 0)],
+            }
+        );
+        assert!(!result.hit());
+        assert_eq!(result.to_string(), "Sonar found ships at A0, A1");
+        assert!(map.spotted[0] && map.spotted[1] && !map.spotted[10]);
+        assert_eq!(map.view(), player_1().view());
+    }
+
+    #[test]
+    fn test_arsenal() {
+        let mut arsenal = Arsenal::SPECIAL;
+        assert_eq!(arsenal.left(Weapon::Missile), None);
+        assert_eq!(arsenal.left(Weapon::Bomb), Some(1));
+        arsenal.spend(Weapon::Bomb);
+        arsenal.spend(Weapon::Bomb);
+        arsenal.spend(Weapon::Missile);
+        assert_eq!(arsenal.left(Weapon::Bomb), Some(0));
+        assert_eq!(arsenal.left(Weapon::Torpedo(Direction::Down)), Some(2));
+        assert_eq!(arsenal.to_string(), "bombs 0, torpedoes 2, sonar 2");
+    }
+}
@@ -1 +1,650 @@
+// a map kept as a b-tree. every node holds up to `order - 1` keys in order, and
+// apart from the root at least half of `order` rounded up, less one. an inner node
+// has one more child than it has keys, and every leaf is the same depth down.
+// inserting splits nodes that get too full on the way back up, removing borrows from
+// or merges with a neighbour when one gets too empty, so the tree only ever grows or
+// shrinks at the root
 
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt;
+use std::mem;
+use std::ops::{Bound, RangeBounds};
+
+/// the order a tree gets from `BTree::new`
+pub const DEFAULT_ORDER: usize = 16;
+
+struct Node<K, V> {
+    keys: Vec<K>,
+    values: Vec<V>,
+    // empty for a leaf
+    children: Vec<Node<K, V>>,
+}
+
+// what inserting into a node did, for its parent to finish off
+enum Insert<K, V> {
+    Added,
+    Replaced(V),
+    // the node was too full and gave its upper half away, with this key between the halves
+    Split(K, V, Node<K, V>),
+}
+
+impl<K: Ord, V> Node<K, V> {
+    fn new() -> Self {
+        Node {
+            keys: Vec::new(),
+            values: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    // where `key` is in this node, or which child it would be under
+    fn search<Q>(&self, key: &Q) -> Result<usize, usize>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.keys.binary_search_by(|k| k.borrow().cmp(key))
+    }
+
+    fn insert(&mut self, key: K, value: V, order: usize) -> Insert<K, V> {
+        let i = match self.search(&key) {
+            Ok(i) => return Insert::Replaced(mem::replace(&mut self.values[i], value)),
+            Err(i) => i,
+        };
+        if self.is_leaf() {
+            self.keys.insert(i, key);
+            self.values.insert(i, value);
+        } else {
+            match self.children[i].insert(key, value, order) {
+                Insert::Split(key, value, right) => {
+                    self.keys.insert(i, key);
+                    self.values.insert(i, value);
+                    self.children.insert(i + 1, right);
+                }
+                done => return done,
+            }
+        }
+
+        if self.keys.len() < order {
+            return Insert::Added;
+        }
+        let mid = self.keys.len() / 2;
+        let right = Node {
+            keys: self.keys.split_off(mid + 1),
+            values: self.values.split_off(mid + 1),
+            children: if self.is_leaf() {
+                Vec::new()
+            } else {
+                self.children.split_off(mid + 1)
+            },
+        };
+        let value = self.values.pop().unwrap();
+        let key = self.keys.pop().unwrap();
+        Insert::Split(key, value, right)
+    }
+
+    // removes `key` from under this node, leaving this node maybe too small for its
+    // parent to fix but every node below it fine
+    fn remove<Q>(&mut self, key: &Q, min_keys: usize) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match self.search(key) {
+            Ok(i) if self.is_leaf() => Some((self.keys.remove(i), self.values.remove(i))),
+            Ok(i) => {
+                // the key just before this one takes its place
+                let (key, value) = self.children[i].pop_last(min_keys);
+                let removed = (
+                    mem::replace(&mut self.keys[i], key),
+                    mem::replace(&mut self.values[i], value),
+                );
+                self.fix_child(i, min_keys);
+                Some(removed)
+            }
+            Err(_) if self.is_leaf() => None,
+            Err(i) => {
+                let removed = self.children[i].remove(key, min_keys)?;
+                self.fix_child(i, min_keys);
+                Some(removed)
+            }
+        }
+    }
+
+    fn pop_last(&mut self, min_keys: usize) -> (K, V) {
+        if self.is_leaf() {
+            let value = self
+                .values
+                .pop()
+                .expect("nodes below the root are never empty");
+            return (self.keys.pop().unwrap(), value);
+        }
+        let last = self.children.len() - 1;
+        let popped = self.children[last].pop_last(min_keys);
+        self.fix_child(last, min_keys);
+        popped
+    }
+
+    // gives child `i` a key from a neighbour with some to spare, or merges it with one
+    // when neither has, if it's got too few
+    fn fix_child(&mut self, i: usize, min_keys: usize) {
+        if self.children[i].keys.len() >= min_keys {
+            return;
+        }
+        if i > 0 && self.children[i - 1].keys.len() > min_keys {
+            let (left, right) = self.children.split_at_mut(i);
+            let (left, child) = (&mut left[i - 1], &mut right[0]);
+            let key = mem::replace(&mut self.keys[i - 1], left.keys.pop().unwrap());
+            let value = mem::replace(&mut self.values[i - 1], left.values.pop().unwrap());
+            child.keys.insert(0, key);
+            child.values.insert(0, value);
+            if let Some(grandchild) = left.children.pop() {
+                child.children.insert(0, grandchild);
+            }
+        } else if i + 1 < self.children.len() && self.children[i + 1].keys.len() > min_keys {
+            let (left, right) = self.children.split_at_mut(i + 1);
+            let (child, right) = (&mut left[i], &mut right[0]);
+            let key = mem::replace(&mut self.keys[i], right.keys.remove(0));
+            let value = mem::replace(&mut self.values[i], right.values.remove(0));
+            child.keys.push(key);
+            child.values.push(value);
+            if !right.is_leaf() {
+                child.children.push(right.children.remove(0));
+            }
+        } else {
+            // merge the child with its left neighbour if it has one, otherwise its right
+            let left = i.saturating_sub(1);
+            let right = self.children.remove(left + 1);
+            let child = &mut self.children[left];
+            child.keys.push(self.keys.remove(left));
+            child.values.push(self.values.remove(left));
+            child.keys.extend(right.keys);
+            child.values.extend(right.values);
+            child.children.extend(right.children);
+        }
+    }
+}
+
+/// A sorted map from `K` to `V`, with nodes of up to `order` children
+pub struct BTree<K, V> {
+    root: Node<K, V>,
+    order: usize,
+    len: usize,
+}
+
+impl<K: Ord, V> BTree<K, V> {
+    pub fn new() -> Self {
+        Self::with_order(DEFAULT_ORDER)
+    }
+
+    /// An empty tree whose nodes have up to `order` children, and so `order - 1` keys.
+    ///
+    /// # Panics
+    ///
+    /// If `order` is less than 3, the smallest a node can be split in two from.
+    pub fn with_order(order: usize) -> Self {
+        assert!(
+            order >= 3,
+            "a b-tree's order has to be at least 3, not {}",
+            order
+        );
+        BTree {
+            root: Node::new(),
+            order,
+            len: 0,
+        }
+    }
+
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // the fewest keys a node other than the root may have
+    fn min_keys(&self) -> usize {
+        self.order.div_ceil(2) - 1
+    }
+
+    /// Puts `value` in the tree under `key`, and gives back the value that was there
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.root.insert(key, value, self.order) {
+            Insert::Replaced(old) => return Some(old),
+            Insert::Added => {}
+            Insert::Split(key, value, right) => {
+                let left = mem::replace(&mut self.root, Node::new());
+                self.root.keys.push(key);
+                self.root.values.push(value);
+                self.root.children = vec![left, right];
+            }
+        }
+        self.len += 1;
+        None
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut node = &self.root;
+        loop {
+            match node.search(key) {
+                Ok(i) => return Some(&node.values[i]),
+                Err(_) if node.is_leaf() => return None,
+                Err(i) => node = &node.children[i],
+            }
+        }
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut node = &mut self.root;
+        loop {
+            match node.search(key) {
+                Ok(i) => return Some(&mut node.values[i]),
+                Err(_) if node.is_leaf() => return None,
+                Err(i) => node = &mut node.children[i],
+            }
+        }
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Takes `key` out of the tree, and gives back its value if it was there
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let min_keys = self.min_keys();
+        let (_, value) = self.root.remove(key, min_keys)?;
+        self.len -= 1;
+        // the root's last key went down into a merge, so its only child takes over
+        if self.root.keys.is_empty() && !self.root.is_leaf() {
+            self.root = self.root.children.pop().unwrap();
+        }
+        Some(value)
+    }
+
+    /// every key and value, in order of the keys
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter {
+            stack: Vec::new(),
+            stop: None,
+        };
+        iter.descend(&self.root, Bound::Unbounded);
+        iter
+    }
+
+    /// The keys and values with keys in `range`, in order.
+    ///
+    /// # Panics
+    ///
+    /// If the range starts after it ends, or starts and ends at the same key with
+    /// both ends excluded, the same as `std::collections::BTreeMap::range`.
+    pub fn range<Q, R>(&self, range: R) -> Iter<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let (start, end) = (range.start_bound(), range.end_bound());
+        if let (Bound::Included(s) | Bound::Excluded(s), Bound::Included(e) | Bound::Excluded(e)) =
+            (start, end)
+        {
+            match s.cmp(e) {
+                Ordering::Greater => panic!("range start is greater than range end"),
+                Ordering::Equal
+                    if matches!((start, end), (Bound::Excluded(_), Bound::Excluded(_))) =>
+                {
+                    panic!("range start and end are equal and excluded")
+                }
+                _ => {}
+            }
+        }
+
+        // the first key past the end, which is where the iterator stops
+        let stop = match end {
+            Bound::Unbounded => None,
+            Bound::Included(e) => Some(Bound::Excluded(e)),
+            Bound::Excluded(e) => Some(Bound::Included(e)),
+        }
+        .and_then(|past_end| {
+            let mut past = Iter {
+                stack: Vec::new(),
+                stop: None,
+            };
+            past.descend(&self.root, past_end);
+            past.next().map(|(key, _)| key)
+        });
+
+        let mut iter = Iter {
+            stack: Vec::new(),
+            stop,
+        };
+        iter.descend(&self.root, start);
+        iter
+    }
+}
+
+impl<K: Ord, V> Default for BTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for BTree<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for BTree<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut tree = BTree::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for BTree<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<'a, K: Ord, V> IntoIterator for &'a BTree<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Iter<'a, K, V> {
+        self.iter()
+    }
+}
+
+/// An in order walk over a `BTree`, see `BTree::iter` and `BTree::range`
+pub struct Iter<'a, K, V> {
+    // the nodes on the way down to the next key, and the key each is up to
+    stack: Vec<(&'a Node<K, V>, usize)>,
+    // the key to stop at without giving it, `None` to go to the end
+    stop: Option<&'a K>,
+}
+
+impl<'a, K: Ord, V> Iter<'a, K, V> {
+    // walks down from `node` to the first key that's after `start`
+    fn descend<Q>(&mut self, mut node: &'a Node<K, V>, start: Bound<&Q>)
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        loop {
+            let i = match start {
+                Bound::Unbounded => 0,
+                Bound::Included(key) => node.search(key).unwrap_or_else(|i| i),
+                Bound::Excluded(key) => match node.search(key) {
+                    Ok(i) => i + 1,
+                    Err(i) => i,
+                },
+            };
+            self.stack.push((node, i));
+            match node.children.get(i) {
+                Some(child) => node = child,
+                None => return,
+            }
+        }
+    }
+}
+
+impl<'a, K: Ord, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        loop {
+            let (node, i) = self.stack.last_mut()?;
+            let node: &'a Node<K, V> = node;
+            if *i == node.keys.len() {
+                self.stack.pop();
+                continue;
+            }
+            let key = &node.keys[*i];
+            if self.stop.is_some_and(|stop| std::ptr::eq(stop, key)) {
+                self.stack.clear();
+                return None;
+            }
+            let value = &node.values[*i];
+            *i += 1;
+            // everything in the child after this key comes next
+            if let Some(child) = node.children.get(*i) {
+                self.descend(child, Bound::<&K>::Unbounded);
+            }
+            return Some((key, value));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::collections::BTreeMap;
+
+    impl<K: Ord, V> Node<K, V> {
+        // checks the node's keys are in order and between `low` and `high` and that
+        // everything under it follows the rules, and gives back how deep its leaves
+        // are and how many keys are under it
+        fn check(
+            &self,
+            low: Option<&K>,
+            high: Option<&K>,
+            min_keys: usize,
+            order: usize,
+        ) -> (usize, usize) {
+            assert!(self.keys.len() >= min_keys, "a node has too few keys");
+            assert!(self.keys.len() < order, "a node has too many keys");
+            assert_eq!(self.keys.len(), self.values.len());
+            assert!(self.keys.windows(2).all(|pair| pair[0] < pair[1]));
+            assert!(low.is_none_or(|low| self.keys.first().is_none_or(|first| low < first)));
+            assert!(high.is_none_or(|high| self.keys.last().is_none_or(|last| last < high)));
+            if self.is_leaf() {
+                return (1, self.keys.len());
+            }
+
+            assert_eq!(self.children.len(), self.keys.len() + 1);
+            let below = order.div_ceil(2) - 1;
+            let mut depths = Vec::new();
+            let mut count = self.keys.len();
+            for (i, child) in self.children.iter().enumerate() {
+                let low = if i == 0 { low } else { Some(&self.keys[i - 1]) };
+                let (depth, under) = child.check(low, self.keys.get(i).or(high), below, order);
+                depths.push(depth);
+                count += under;
+            }
+            assert!(
+                depths.windows(2).all(|pair| pair[0] == pair[1]),
+                "leaves at different depths"
+            );
+            (depths[0] + 1, count)
+        }
+    }
+
+    impl<K: Ord, V> BTree<K, V> {
+        fn check(&self) {
+            // an inner root needs a key to have two children, a leaf root can be empty
+            let min_keys = if self.root.is_leaf() { 0 } else { 1 };
+            let (_, count) = self.root.check(None, None, min_keys, self.order);
+            assert_eq!(count, self.len);
+        }
+    }
+
+    fn bound(rng: &mut StdRng, keys: u32) -> Bound<u32> {
+        match rng.gen_range(0..3) {
+            0 => Bound::Included(rng.gen_range(0..keys)),
+            1 => Bound::Excluded(rng.gen_range(0..keys)),
+            _ => Bound::Unbounded,
+        }
+    }
+
+    // std's ranges panic on the same start and end ours do
+    fn bad_range(start: Bound<u32>, end: Bound<u32>) -> bool {
+        match (start, end) {
+            (Bound::Excluded(s), Bound::Excluded(e)) => s >= e,
+            (Bound::Included(s) | Bound::Excluded(s), Bound::Included(e) | Bound::Excluded(e)) => {
+                s > e
+            }
+            _ => false,
+        }
+    }
+
+    // makes the same random changes to a `BTree` and a `BTreeMap`, checking they
+    // always agree
+    fn differential(order: usize, keys: u32, steps: usize, seed: u64) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut tree = BTree::with_order(order);
+        let mut map = BTreeMap::new();
+        for step in 0..steps {
+            let key = rng.gen_range(0..keys);
+            match rng.gen_range(0..10) {
+                // more inserts than removes, so the tree grows
+                0..=4 => assert_eq!(tree.insert(key, step), map.insert(key, step)),
+                5..=7 => assert_eq!(tree.remove(&key), map.remove(&key)),
+                8 => {
+                    if let Some(value) = tree.get_mut(&key) {
+                        *value += 1;
+                    }
+                    if let Some(value) = map.get_mut(&key) {
+                        *value += 1;
+                    }
+                }
+                _ => {
+                    let (start, end) = (bound(&mut rng, keys), bound(&mut rng, keys));
+                    if !bad_range(start, end) {
+                        assert!(tree.range((start, end)).eq(map.range((start, end))));
+                    }
+                }
+            }
+            assert_eq!(tree.get(&key), map.get(&key));
+            assert_eq!(tree.len(), map.len());
+            if step.is_multiple_of(100) {
+                tree.check();
+                assert!(tree.iter().eq(map.iter()));
+            }
+        }
+        tree.check();
+        assert!(tree.iter().eq(map.iter()));
+
+        // and emptied again
+        let mut left: Vec<u32> = map.keys().copied().collect();
+        while !left.is_empty() {
+            let key = left.swap_remove(rng.gen_range(0..left.len()));
+            assert_eq!(tree.remove(&key), map.remove(&key));
+            if left.len().is_multiple_of(50) {
+                tree.check();
+            }
+        }
+        assert!(tree.is_empty());
+        tree.check();
+    }
+
+    #[test]
+    fn test_against_btreemap() {
+        for (seed, order) in [3, 4, 5, 6, 7, 16, 101].into_iter().enumerate() {
+            differential(order, 500, 20_000, seed as u64);
+            // few keys, so the same ones keep being replaced and removed
+            differential(order, 20, 2_000, seed as u64);
+        }
+    }
+
+    #[test]
+    fn test_insert_get_remove() {
+        let mut tree = BTree::with_order(3);
+        assert_eq!(tree.get(&1), None);
+        assert_eq!(tree.remove(&1), None);
+        for i in (0..100).rev() {
+            assert_eq!(tree.insert(i, i * 10), None);
+            tree.check();
+        }
+        assert_eq!(tree.insert(7, 0), Some(70));
+        assert_eq!(tree.len(), 100);
+        assert_eq!(tree.get(&7), Some(&0));
+        assert!(tree.contains_key(&99) && !tree.contains_key(&100));
+
+        for i in (0..100).step_by(2) {
+            assert!(tree.remove(&i).is_some());
+            tree.check();
+        }
+        assert_eq!(tree.len(), 50);
+        assert!(tree.iter().map(|(&key, _)| key).eq((1..100).step_by(2)));
+    }
+
+    #[test]
+    fn test_borrowed_keys() {
+        let mut tree: BTree<String, usize> = ["carrier", "destroyer", "submarine"]
+            .iter()
+            .map(|name| (name.to_string(), name.len()))
+            .collect();
+        assert_eq!(tree.get("destroyer"), Some(&9));
+        assert_eq!(
+            tree.range::<str, _>((Bound::Included("d"), Bound::Excluded("t")))
+                .map(|(key, _)| key.as_str())
+                .collect::<Vec<_>>(),
+            ["destroyer", "submarine"]
+        );
+        assert_eq!(tree.remove("carrier"), Some(7));
+        assert_eq!(format!("{:?}", tree), r#"{"destroyer": 9, "submarine": 9}"#);
+    }
+
+    #[test]
+    fn test_ranges() {
+        let tree: BTree<u32, ()> = (0..50).map(|i| (i * 2, ())).collect();
+        let keys = |iter: Iter<u32, ()>| iter.map(|(&key, _)| key).collect::<Vec<u32>>();
+        assert_eq!(keys(tree.range(10..16)), [10, 12, 14]);
+        assert_eq!(keys(tree.range(9..=16)), [10, 12, 14, 16]);
+        assert_eq!(keys(tree.range(95..)), [96, 98]);
+        assert_eq!(keys(tree.range(..3)), [0, 2]);
+        assert_eq!(keys(tree.range(11..12)), []);
+        assert_eq!(keys(tree.range(500..)), []);
+        assert_eq!(
+            keys(tree.range((Bound::Excluded(10), Bound::Included(14)))),
+            [12, 14]
+        );
+        assert_eq!(tree.range(..).count(), 50);
+    }
+
+    #[test]
+    #[should_panic(expected = "range start is greater than range end")]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn test_backwards_range() {
+        let tree: BTree<u32, ()> = BTree::new();
+        let _ = tree.range(5..3);
+    }
+
+    #[test]
+    #[should_panic(expected = "order has to be at least 3")]
+    fn test_order_too_small() {
+        let _: BTree<u32, ()> = BTree::with_order(2);
+    }
+}
@@ -1,38 +1,271 @@
 pub mod file {
+    // the files the programs read and write all live in one data directory. it's found
+    // the same way wherever the program is run from: the directory in STONKS_DATA_DIR
+    // if that's set, otherwise `data` in the crate's directory, otherwise `data` in the
+    // working directory
 
     use std::env;
-    use std::fs::{File, OpenOptions};
-    use std::io::prelude::*;
-    pub fn open_file(filename: &str) -> String {
-        let curr_exe = env::current_exe().unwrap();
-        let curr_dir = curr_exe
-            .parent()
-            .unwrap()
-            .parent()
-            .unwrap()
-            .parent()
-            .unwrap();
-        let file_loc = curr_dir.join("data").join(filename);
-        println!("file: {:?}", file_loc);
-        let mut file = File::open(file_loc).unwrap();
-        let mut contents = String::new();
-        file.read_to_string(&mut contents).unwrap();
-        contents
-    }
-    pub fn append_file(filename: &str, to_append: &str) {
-        let curr_exe = env::current_exe().unwrap();
-        let curr_dir = curr_exe
-            .parent()
-            .unwrap()
-            .parent()
-            .unwrap()
-            .parent()
-            .unwrap();
-        let file_loc = curr_dir.join("data").join(filename);
-        println!("file: {:?}", file_loc);
-        let mut file = OpenOptions::new().append(true).open(file_loc).unwrap();
-        if let Err(e) = file.write(to_append.as_bytes()) {
-            eprintln!("Couldn't write to file: {}", e);
+    use std::fmt;
+    use std::fs::{self, File, OpenOptions};
+    use std::io::{self, prelude::*};
+    use std::path::{Path, PathBuf};
+    use std::process;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    pub const DATA_DIR_VAR: &str = "STONKS_DATA_DIR";
+
+    #[derive(Debug)]
+    pub enum FileError {
+        /// STONKS_DATA_DIR is set to something that isn't a directory
+        BadDataDirVar(PathBuf),
+        /// none of these were a directory
+        NoDataDir(Vec<PathBuf>),
+        /// reading or writing the file at the path failed
+        Io { path: PathBuf, error: io::Error },
+    }
+
+    impl fmt::Display for FileError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                FileError::BadDataDirVar(path) => {
+                    write!(
+                        f,
+                        "{} is set to {:?}, which isn't a directory",
+                        DATA_DIR_VAR, path
+                    )
+                }
+                FileError::NoDataDir(tried) => {
+                    write!(f, "Couldn't find the data directory, tried {:?}", tried)
+                }
+                FileError::Io { path, error } => write!(f, "{:?}: {}", path, error),
+            }
+        }
+    }
+
+    impl std::error::Error for FileError {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            match self {
+                FileError::Io { error, .. } => Some(error),
+                _ => None,
+            }
+        }
+    }
+
+    fn io_error(path: &Path) -> impl FnOnce(io::Error) -> FileError + '_ {
+        move |error| FileError::Io {
+            path: path.to_path_buf(),
+            error,
+        }
+    }
+
+    // tells apart the temporary files of writes happening at the same time
+    static NEXT_TEMP: AtomicUsize = AtomicUsize::new(0);
+
+    /// A directory of data files
+    #[derive(Clone, Debug, PartialEq)]
+    pub struct DataDir {
+        dir: PathBuf,
+    }
+
+    impl DataDir {
+        /// The data directory, see the top of this module for where it looks
+        pub fn find() -> Result<DataDir, FileError> {
+            Self::resolve(
+                env::var_os(DATA_DIR_VAR).map(PathBuf::from),
+                Path::new(env!("CARGO_MANIFEST_DIR")),
+                env::current_dir().ok().as_deref(),
+            )
+        }
+
+        fn resolve(
+            from_var: Option<PathBuf>,
+            manifest_dir: &Path,
+            working_dir: Option<&Path>,
+        ) -> Result<DataDir, FileError> {
+            if let Some(dir) = from_var {
+                if !dir.is_dir() {
+                    return Err(FileError::BadDataDirVar(dir));
+                }
+                return Ok(DataDir { dir });
+            }
+            let mut tried = vec![manifest_dir.join("data")];
+            tried.extend(working_dir.map(|dir| dir.join("data")));
+            match tried.iter().find(|dir| dir.is_dir()) {
+                Some(dir) => Ok(DataDir { dir: dir.clone() }),
+                None => Err(FileError::NoDataDir(tried)),
+            }
+        }
+
+        pub fn at(dir: impl Into<PathBuf>) -> DataDir {
+            DataDir { dir: dir.into() }
+        }
+
+        pub fn path(&self, filename: &str) -> PathBuf {
+            self.dir.join(filename)
+        }
+
+        pub fn read(&self, filename: &str) -> Result<String, FileError> {
+            let path = self.path(filename);
+            let mut contents = String::new();
+            File::open(&path)
+                .and_then(|mut file| file.read_to_string(&mut contents))
+                .map_err(io_error(&path))?;
+            Ok(contents)
+        }
+
+        /// adds `to_append` to the end of the file, making it if it isn't there
+        pub fn append(&self, filename: &str, to_append: &str) -> Result<(), FileError> {
+            let path = self.path(filename);
+            OpenOptions::new()
+                .append(true)
+                .create(true)
+                .open(&path)
+                .and_then(|mut file| file.write_all(to_append.as_bytes()))
+                .map_err(io_error(&path))
+        }
+
+        /// Makes the file hold `contents`, all at once: the contents are written next to
+        /// it first and moved over it, so it's never seen half written, even if the
+        /// program stops partway through.
+        pub fn write(&self, filename: &str, contents: &str) -> Result<(), FileError> {
+            let path = self.path(filename);
+            let name = path
+                .file_name()
+                .ok_or_else(|| FileError::Io {
+                    path: path.clone(),
+                    error: io::Error::new(io::ErrorKind::InvalidInput, "not a file name"),
+                })?
+                .to_string_lossy();
+            let temp = path.with_file_name(format!(
+                ".{}.{}-{}.tmp",
+                name,
+                process::id(),
+                NEXT_TEMP.fetch_add(1, Ordering::Relaxed)
+            ));
+
+            let written = File::create(&temp)
+                .and_then(|mut file| {
+                    file.write_all(contents.as_bytes())?;
+                    file.sync_all()
+                })
+                .and_then(|()| fs::rename(&temp, &path));
+            if let Err(error) = written {
+                let _ = fs::remove_file(&temp);
+                return Err(FileError::Io { path, error });
+            }
+            Ok(())
+        }
+
+        /// Reads the file, and `write`s back what `edit` makes of it
+        pub fn replace(
+            &self,
+            filename: &str,
+            edit: impl FnOnce(String) -> String,
+        ) -> Result<(), FileError> {
+            let contents = self.read(filename)?;
+            self.write(filename, &edit(contents))
+        }
+    }
+
+    /// the contents of a file in the data directory
+    pub fn open_file(filename: &str) -> Result<String, FileError> {
+        DataDir::find()?.read(filename)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        // an empty directory for one test, gone when it's dropped
+        struct Scratch(PathBuf);
+
+        impl Scratch {
+            fn new(name: &str) -> Scratch {
+                let dir = env::temp_dir().join(format!("stonks-{}-{}", name, process::id()));
+                let _ = fs::remove_dir_all(&dir);
+                fs::create_dir_all(&dir).unwrap();
+                Scratch(dir)
+            }
+        }
+
+        impl Drop for Scratch {
+            fn drop(&mut self) {
+                let _ = fs::remove_dir_all(&self.0);
+            }
+        }
+
+        #[test]
+        fn test_finds_the_crates_data() {
+            let data = DataDir::find().unwrap();
+            assert!(data.read("message.txt").unwrap().starts_with("155\n"));
+            assert!(matches!(
+                data.read("not_there.txt"),
+                Err(FileError::Io { error, .. }) if error.kind() == io::ErrorKind::NotFound
+            ));
+        }
+
+        #[test]
+        fn test_resolve_order() {
+            let scratch = Scratch::new("resolve");
+            let nowhere = scratch.0.join("nowhere");
+            let working = scratch.0.join("working");
+            fs::create_dir_all(working.join("data")).unwrap();
+
+            // the variable wins, and it's an error when it isn't a directory
+            assert_eq!(
+                DataDir::resolve(
+                    Some(scratch.0.clone()),
+                    Path::new(env!("CARGO_MANIFEST_DIR")),
+                    None
+                )
+                .unwrap(),
+                DataDir::at(&scratch.0)
+            );
+            assert!(matches!(
+                DataDir::resolve(Some(nowhere.clone()), &working, None),
+                Err(FileError::BadDataDirVar(dir)) if dir == nowhere
+            ));
+            // then the crate, then the working directory
+            assert_eq!(
+                DataDir::resolve(None, &working, Some(&nowhere)).unwrap(),
+                DataDir::at(working.join("data"))
+            );
+            assert_eq!(
+                DataDir::resolve(None, &nowhere, Some(&working)).unwrap(),
+                DataDir::at(working.join("data"))
+            );
+            match DataDir::resolve(None, &nowhere, Some(&nowhere)) {
+                Err(FileError::NoDataDir(tried)) => assert_eq!(tried.len(), 2),
+                other => panic!("{:?}", other),
+            }
+        }
+
+        #[test]
+        fn test_write_append_and_replace() {
+            let scratch = Scratch::new("write");
+            let data = DataDir::at(&scratch.0);
+
+            data.write("scores.txt", "1\n").unwrap();
+            data.append("scores.txt", "2\n").unwrap();
+            data.append("new.txt", "3\n").unwrap();
+            data.replace("scores.txt", |scores| scores.replace('1', "one"))
+                .unwrap();
+            assert_eq!(data.read("scores.txt").unwrap(), "one\n2\n");
+            assert_eq!(data.read("new.txt").unwrap(), "3\n");
+            data.write("scores.txt", "").unwrap();
+            assert_eq!(data.read("scores.txt").unwrap(), "");
+
+            // nothing left behind from the writes
+            let mut files: Vec<_> = fs::read_dir(&scratch.0)
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name())
+                .collect();
+            files.sort();
+            assert_eq!(files, ["new.txt", "scores.txt"]);
+
+            assert!(data.replace("missing.txt", |s| s).is_err());
+            assert!(data.write("no_such_dir/scores.txt", "1").is_err());
+            assert_eq!(fs::read_dir(&scratch.0).unwrap().count(), 2);
         }
     }
 }
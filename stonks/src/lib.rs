@@ -0,0 +1,4 @@
+//! The parts of the crate that are used from outside it, so far the b-tree the
+//! benchmarks in `benches` compare with the standard library's.
+
+pub mod btree;